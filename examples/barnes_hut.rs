@@ -0,0 +1,53 @@
+// Compares the Barnes-Hut field sums against direct summation over every
+// pair, for a single creature of increasing size: the error in U_val and
+// U_grad after one step, relative to their RMS, and the time per step.
+//
+//     cargo run --example barnes_hut
+
+use std::time::Instant;
+
+use particle_lenia::{Fields, Simulation};
+
+const SIZES: [usize; 3] = [500, 2000, 8000];
+const THETAS: [f32; 3] = [0.25, 0.5, 1.0];
+const TIMED_STEPS: u32 = 5;
+
+// Fields after the first step, which were summed at the shared initial
+// positions, and the mean time of the steps after it.
+fn run(particles: usize, theta: Option<f32>) -> (Vec<Fields>, f64) {
+    let mut args = vec!["--stress".to_string(), "1".to_string(), particles.to_string(), "--seed".to_string(), "1".to_string()];
+    if let Some(theta) = theta {
+        args.extend(["--barnes-hut".to_string(), theta.to_string()]);
+    }
+    let mut simulation = Simulation::from_args(args).unwrap_or_else(|err| err.exit());
+
+    simulation.step();
+    let fields = simulation.state().particles.into_iter().map(|particle| particle.fields).collect();
+    let start = Instant::now();
+    for _ in 0..TIMED_STEPS {
+        simulation.step();
+    }
+    (fields, start.elapsed().as_secs_f64() * 1000.0 / TIMED_STEPS as f64)
+}
+
+fn rms(values: impl Iterator<Item = f32>) -> f32 {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), value| (sum + value * value, count + 1));
+    (sum / count.max(1) as f32).sqrt()
+}
+
+fn main() {
+    println!("{:>9} {:>7} {:>12} {:>13} {:>10} {:>8}", "particles", "theta", "U_val error", "U_grad error", "ms/step", "speedup");
+    for particles in SIZES {
+        let (direct, direct_ms) = run(particles, None);
+        println!("{:>9} {:>7} {:>12} {:>13} {:>10.2} {:>8}", particles, "direct", "-", "-", direct_ms, "-");
+
+        let value_scale = rms(direct.iter().map(|fields| fields.U_val));
+        let gradient_scale = rms(direct.iter().map(|fields| fields.U_grad.length()));
+        for theta in THETAS {
+            let (approximate, ms) = run(particles, Some(theta));
+            let value_error = rms(direct.iter().zip(&approximate).map(|(a, b)| a.U_val - b.U_val)) / value_scale;
+            let gradient_error = rms(direct.iter().zip(&approximate).map(|(a, b)| (a.U_grad - b.U_grad).length())) / gradient_scale;
+            println!("{:>9} {:>7} {:>12.2e} {:>13.2e} {:>10.2} {:>7.1}x", particles, theta, value_error, gradient_error, ms, direct_ms / ms);
+        }
+    }
+}
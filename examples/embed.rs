@@ -0,0 +1,29 @@
+// Adds the simulation to an app of its own through the plugins, stepping it
+// at a fixed 30 steps a second instead of once a frame and logging the
+// step's particle count from a system outside the crate.
+//
+//     cargo run --example embed
+
+use bevy::prelude::*;
+use particle_lenia::{Fields, ParticleLeniaPlugin, ParticleRenderPlugin, Scene, SceneCameraPlugin, ScenePlugin};
+
+fn count_particles(mut frames: Local<u32>, particle_query: Query<&Fields>) {
+    *frames += 1;
+    if frames.is_multiple_of(120) {
+        info!("{} particles", particle_query.iter().count());
+    }
+}
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .insert_resource(FixedTime::new_from_secs(1.0 / 30.0))
+        .add_plugins((
+            ParticleLeniaPlugin::in_schedule(FixedUpdate),
+            ScenePlugin { scene: Scene::single(7) },
+            ParticleRenderPlugin,
+            SceneCameraPlugin,
+        ))
+        .add_systems(Update, count_particles)
+        .run();
+}
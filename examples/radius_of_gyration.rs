@@ -0,0 +1,37 @@
+// Tracks each creature's radius of gyration, the RMS distance of its particles
+// from their centroid, through a headless run of the pair demo.
+//
+//     cargo run --example radius_of_gyration
+
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use particle_lenia::{SimState, Simulation};
+
+fn radius_of_gyration(state: &SimState) -> BTreeMap<Entity, f32> {
+    let mut creatures: BTreeMap<Entity, Vec<Vec3>> = BTreeMap::new();
+    for particle in state.particles.iter() {
+        creatures.entry(particle.creature).or_default().push(particle.position);
+    }
+
+    creatures.into_iter()
+        .map(|(creature, positions)| {
+            let centroid = positions.iter().sum::<Vec3>() / positions.len() as f32;
+            let mean_square = positions.iter().map(|x| x.distance_squared(centroid)).sum::<f32>() / positions.len() as f32;
+            (creature, mean_square.sqrt())
+        })
+        .collect()
+}
+
+fn main() {
+    let mut simulation = Simulation::from_args(["--demo", "pair", "--seed", "1"]).unwrap_or_else(|err| err.exit());
+
+    for _ in 0..500 {
+        simulation.step_with_callback(|state| {
+            if state.step % 100 == 0 {
+                let radii: Vec<_> = radius_of_gyration(state).into_values().map(|r| format!("{:.3}", r)).collect();
+                println!("step {}: radius of gyration {}", state.step, radii.join(", "));
+            }
+        });
+    }
+}
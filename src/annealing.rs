@@ -0,0 +1,114 @@
+use std::{fs, path::Path};
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{ForceTerms, SimStep};
+
+/// A stretch of steps and the force terms active during it.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Phase {
+    pub steps: u64,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default = "enabled")]
+    pub growth: bool,
+    #[serde(default = "enabled")]
+    pub repulsion: bool,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+/// Phases the simulation cycles through, e.g. spreading out under repulsion
+/// alone and then pulling back together under the full dynamics.
+#[derive(Resource, Clone, Debug, Deserialize)]
+pub struct PhaseSchedule {
+    pub phases: Vec<Phase>,
+}
+
+impl PhaseSchedule {
+    /// Reads a schedule from JSON, e.g.
+    /// `{"phases": [{"steps": 200, "name": "spread", "growth": false}, {"steps": 800}]}`.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = fs::read_to_string(path).map_err(|err| err.to_string())?;
+        let schedule: Self = serde_json::from_str(&json).map_err(|err| err.to_string())?;
+        if schedule.phases.is_empty() || schedule.phases.iter().any(|phase| phase.steps == 0) {
+            return Err("expected at least one phase, each at least one step long".to_string());
+        }
+        Ok(schedule)
+    }
+
+    /// Index of the phase `step` falls in and how many steps into it it is.
+    pub fn phase_at(&self, step: u64) -> (usize, u64) {
+        let mut step = step % self.phases.iter().map(|phase| phase.steps).sum::<u64>();
+        for (index, phase) in self.phases.iter().enumerate() {
+            if step < phase.steps {
+                return (index, step);
+            }
+            step -= phase.steps;
+        }
+        unreachable!()
+    }
+}
+
+/// Where the schedule is at, as of the current step.
+#[derive(Resource, Default)]
+pub struct CurrentPhase {
+    pub index: Option<usize>,
+    pub steps_in: u64,
+}
+
+impl CurrentPhase {
+    /// One line describing the phase, for the overlay.
+    pub fn describe(&self, schedule: &PhaseSchedule) -> Option<String> {
+        let index = self.index?;
+        let phase = &schedule.phases[index];
+        let terms = match (phase.growth, phase.repulsion) {
+            (true, true) => "growth and repulsion",
+            (true, false) => "growth only",
+            (false, true) => "repulsion only",
+            (false, false) => "no forces",
+        };
+        let name = phase.name.clone().unwrap_or_else(|| format!("phase {}", index + 1));
+        Some(format!("{} ({}), step {} of {}", name, terms, self.steps_in + 1, phase.steps))
+    }
+}
+
+/// Switches the active force terms as the step count moves through the
+/// schedule. The terms are only set when a phase begins, so G still toggles
+/// growth for the rest of a phase.
+pub struct AnnealingPlugin {
+    pub schedule: PhaseSchedule,
+}
+
+impl Plugin for AnnealingPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.schedule.clone())
+            .init_resource::<CurrentPhase>()
+            // Before Update, so substeps and safe mode retries see the same terms.
+            .add_systems(PreUpdate, apply_phase);
+    }
+}
+
+fn apply_phase(
+    schedule: Res<PhaseSchedule>,
+    step: Res<SimStep>,
+    mut current: ResMut<CurrentPhase>,
+    mut force_terms: ResMut<ForceTerms>,
+) {
+    let (index, steps_in) = schedule.phase_at(step.0);
+    current.steps_in = steps_in;
+    if current.index == Some(index) {
+        return;
+    }
+
+    let phase = &schedule.phases[index];
+    force_terms.growth = phase.growth;
+    force_terms.repulsion = phase.repulsion;
+    current.index = Some(index);
+    if let Some(description) = current.describe(&schedule) {
+        info!("step {}: {}", step.0, description);
+    }
+}
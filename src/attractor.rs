@@ -0,0 +1,164 @@
+use bevy::prelude::*;
+
+use crate::{calculate_fields, clamp_forces, emitter::emitter_modifier_pressed, picking::Cursor, substeps::Substep, Creature, Fields, ForceTerms, Particle};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FalloffKind {
+    Constant,
+    Linear { range: f32 },
+    InverseSquare { softening: f32 },
+    Gaussian { sigma: f32 },
+}
+
+impl FalloffKind {
+    pub fn weight(self, r: f32) -> f32 {
+        match self {
+            FalloffKind::Constant => 1.0,
+            FalloffKind::Linear { range } => f32::max(0.0, 1.0 - r / range),
+            FalloffKind::InverseSquare { softening } => 1.0 / (r * r + softening * softening),
+            FalloffKind::Gaussian { sigma } => {
+                let t = r / sigma;
+                (-t * t).exp()
+            }
+        }
+    }
+
+    // With the length scales in `settings`.
+    fn next(self, settings: &AttractorSettings) -> Self {
+        match self {
+            FalloffKind::Constant => FalloffKind::Linear { range: settings.range },
+            FalloffKind::Linear { .. } => FalloffKind::InverseSquare { softening: settings.softening },
+            FalloffKind::InverseSquare { .. } => FalloffKind::Gaussian { sigma: settings.sigma },
+            FalloffKind::Gaussian { .. } => FalloffKind::Constant,
+        }
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Attractor {
+    pub strength: f32,
+    pub falloff: FalloffKind,
+}
+
+/// Template for the next attractor placed with the mouse: its strength, the
+/// falloff F last cycled to, and the length scale each falloff is given.
+#[derive(Resource, Clone, Debug)]
+pub struct AttractorSettings {
+    pub strength: f32,
+    pub falloff: FalloffKind,
+    pub range: f32,
+    pub softening: f32,
+    pub sigma: f32,
+}
+
+impl Default for AttractorSettings {
+    fn default() -> Self {
+        Self {
+            strength: 0.5,
+            falloff: FalloffKind::Constant,
+            range: 20.0,
+            softening: 2.0,
+            sigma: 8.0,
+        }
+    }
+}
+
+impl AttractorSettings {
+    fn attractor(&self) -> Attractor {
+        Attractor {
+            strength: self.strength,
+            falloff: self.falloff,
+        }
+    }
+}
+
+pub struct AttractorPlugin {
+    pub settings: AttractorSettings,
+}
+
+impl Plugin for AttractorPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.settings.clone())
+            .add_systems(Update, (place_attractors, cycle_falloff, draw_attractors))
+            .add_systems(Update, apply_attractors.after(calculate_fields).before(clamp_forces))
+            .add_systems(Substep, apply_attractors.after(calculate_fields).before(clamp_forces));
+    }
+}
+
+fn place_attractors(
+    mut commands: Commands,
+    buttons: Res<Input<MouseButton>>,
+    keys: Res<Input<KeyCode>>,
+    settings: Res<AttractorSettings>,
+    cursor: Cursor,
+    attractor_query: Query<Entity, With<Attractor>>,
+) {
+    if keys.just_pressed(KeyCode::Back) {
+        for entity in attractor_query.iter() {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    // Shift-right-clicks place emitters instead.
+    if !buttons.just_pressed(MouseButton::Right) || emitter_modifier_pressed(&keys) {
+        return;
+    }
+
+    if let Some(position) = cursor.world_position() {
+        commands.spawn((
+            TransformBundle::from_transform(Transform::from_translation(position.extend(0.0))),
+            settings.attractor(),
+        ));
+    }
+}
+
+fn cycle_falloff(
+    keys: Res<Input<KeyCode>>,
+    mut settings: ResMut<AttractorSettings>,
+) {
+    if keys.just_pressed(KeyCode::F) {
+        settings.falloff = settings.falloff.next(&settings);
+        info!("attractor falloff: {:?}", settings.falloff);
+    }
+}
+
+fn apply_attractors(
+    force_terms: Res<ForceTerms>,
+    attractor_query: Query<(&Transform, &Attractor)>,
+    creature_query: Query<(&Transform, &Children), With<Creature>>,
+    mut particle_query: Query<(&Transform, &mut Fields), With<Particle>>,
+) {
+    if !force_terms.external || attractor_query.is_empty() {
+        return;
+    }
+
+    for (creature_transform, children) in creature_query.iter() {
+        for child in children.iter() {
+            let (transform, mut fields) = particle_query.get_mut(*child).unwrap();
+            let x = creature_transform.translation + transform.translation;
+
+            for (attractor_transform, attractor) in attractor_query.iter() {
+                let d = attractor_transform.translation - x;
+                let r = d.length();
+                if r > f32::EPSILON {
+                    fields.E_grad -= (d / r) * attractor.strength * attractor.falloff.weight(r);
+                }
+            }
+        }
+    }
+}
+
+fn draw_attractors(
+    mut gizmos: Gizmos,
+    attractor_query: Query<(&Transform, &Attractor)>,
+) {
+    for (transform, attractor) in attractor_query.iter() {
+        let color = match attractor.falloff {
+            FalloffKind::Constant => Color::YELLOW,
+            FalloffKind::Linear { .. } => Color::ORANGE,
+            FalloffKind::InverseSquare { .. } => Color::RED,
+            FalloffKind::Gaussian { .. } => Color::CYAN,
+        };
+        gizmos.circle_2d(transform.translation.truncate(), 0.5, color);
+    }
+}
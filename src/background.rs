@@ -0,0 +1,99 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+use bevy::{app::AppExit, prelude::*};
+
+use crate::{
+    cli::Args,
+    simulation::{SimState, Simulation},
+    Creature, Fields, Particle, SimStep,
+};
+
+/// Steps a headless copy of the simulation on its own thread, as fast as it
+/// goes, while the window draws whichever step finished last. The window's
+/// copy spawns the same scene from the same options but never steps it, so
+/// edits made in the window don't reach the simulation thread.
+pub struct BackgroundSimPlugin {
+    pub args: Args,
+}
+
+// The simulation thread builds each state outside the lock and swaps it in,
+// and the window takes it out, so neither waits on the other for longer than
+// the swap.
+#[derive(Resource)]
+struct BackgroundSim {
+    latest: Arc<Mutex<Option<SimState>>>,
+    running: Arc<AtomicBool>,
+}
+
+impl Plugin for BackgroundSimPlugin {
+    fn build(&self, app: &mut App) {
+        let latest = Arc::new(Mutex::new(None));
+        let running = Arc::new(AtomicBool::new(true));
+
+        let args = Args {
+            headless: true,
+            background_sim: false,
+            ..self.args.clone()
+        };
+        let thread_latest = Arc::clone(&latest);
+        let thread_running = Arc::clone(&running);
+        thread::Builder::new()
+            .name("simulation".to_string())
+            .spawn(move || {
+                let mut simulation = Simulation::new(&args);
+                while thread_running.load(Ordering::Relaxed) {
+                    simulation.step();
+                    let state = simulation.state();
+                    *thread_latest.lock().unwrap() = Some(state);
+                }
+            })
+            .expect("failed to start the simulation thread");
+
+        app.insert_resource(BackgroundSim { latest, running })
+            .add_systems(PreUpdate, apply_latest_state)
+            .add_systems(Last, stop_on_exit);
+    }
+}
+
+// Creatures match up by spawn order and particles by child order, the order
+// `Simulation::state` lists them in.
+#[allow(clippy::type_complexity)]
+fn apply_latest_state(
+    background: Res<BackgroundSim>,
+    mut step: ResMut<SimStep>,
+    creature_query: Query<(Entity, &Transform, &Children), With<Creature>>,
+    mut particle_query: Query<(&mut Transform, &mut Fields), (With<Particle>, Without<Creature>)>,
+) {
+    let Some(state) = background.latest.lock().unwrap().take() else {
+        return;
+    };
+    step.0 = state.step;
+
+    let mut creatures: Vec<_> = creature_query.iter().collect();
+    creatures.sort_by_key(|(entity, ..)| *entity);
+    let mut particles = state.particles.iter().peekable();
+    for (_, creature_transform, children) in creatures {
+        let Some(creature) = particles.peek().map(|particle| particle.creature) else {
+            break;
+        };
+        let mut children = particle_query.iter_many_mut(children);
+        while let Some(particle) = particles.next_if(|particle| particle.creature == creature) {
+            if let Some((mut transform, mut fields)) = children.fetch_next() {
+                transform.translation = particle.position - creature_transform.translation;
+                *fields = particle.fields;
+            }
+        }
+    }
+}
+
+fn stop_on_exit(background: Res<BackgroundSim>, mut exit: EventReader<AppExit>) {
+    if exit.iter().next().is_some() {
+        background.running.store(false, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,239 @@
+use bevy::prelude::*;
+
+use crate::{repulsion_field, Fields, KernelKind, Parameters};
+
+// Nodes with at most this many particles aren't split further, and neither
+// are nodes this deep, which only happens for nearly coincident particles.
+const LEAF_SIZE: usize = 8;
+const MAX_DEPTH: u32 = 24;
+
+/// Sums each creature's own pair fields with a Barnes-Hut quadtree instead of
+/// over every pair. A node of the tree seen from a particle at distance `d`
+/// from its centroid, with a side shorter than `theta * d`, adds its particle
+/// count times the kernel at its centroid, in place of the kernel of each of
+/// its particles. Nodes that reach into the kernel's ring are always opened,
+/// since a node straddling the ring isn't summed up well by its centroid, so
+/// repulsion and the ring stay exact and only the tails are approximated.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct BarnesHut {
+    pub theta: f32,
+}
+
+struct Node {
+    min: Vec2,
+    size: f32,
+    count: f32,
+    centroid: Vec3,
+    // Index of the first of four consecutive children, if split.
+    children: Option<usize>,
+    // Range of `QuadTree::order` holding the node's particles.
+    start: usize,
+    end: usize,
+}
+
+/// What a walk of the tree sums at some node.
+pub enum Source {
+    /// A single particle, by its index in the positions the tree was built from
+    Particle(usize),
+    /// A node far enough away to stand in for its particles
+    Cluster { count: f32, centroid: Vec3 },
+}
+
+/// Quadtree over a set of positions in the xy plane, with the particle count
+/// and centroid of every node.
+pub struct QuadTree {
+    nodes: Vec<Node>,
+    order: Vec<usize>,
+}
+
+impl QuadTree {
+    pub fn build(positions: &[Vec3]) -> Self {
+        let (min, max) = positions.iter().fold((Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)), |(min, max), x| {
+            (min.min(x.truncate()), max.max(x.truncate()))
+        });
+        let size = (max - min).max_element().max(f32::EPSILON);
+
+        let mut tree = QuadTree { nodes: Vec::new(), order: (0..positions.len()).collect() };
+        if !positions.is_empty() {
+            tree.nodes.push(tree.node(positions, min, size, 0, positions.len()));
+            tree.split(positions, 0, 0);
+        }
+        tree
+    }
+
+    fn node(&self, positions: &[Vec3], min: Vec2, size: f32, start: usize, end: usize) -> Node {
+        let count = (end - start) as f32;
+        let centroid = self.order[start..end].iter().map(|i| positions[*i]).sum::<Vec3>() / count.max(1.0);
+        Node { min, size, count, centroid, children: None, start, end }
+    }
+
+    fn split(&mut self, positions: &[Vec3], index: usize, depth: u32) {
+        let Node { min, size, start, end, .. } = self.nodes[index];
+        if end - start <= LEAF_SIZE || depth >= MAX_DEPTH {
+            return;
+        }
+
+        let half = 0.5 * size;
+        let middle = min + half;
+        // A particle exactly on a dividing line goes to the quadrant above or
+        // right of it. The comparison is exact, with no epsilon, so the same
+        // positions always split the same way.
+        let quadrant = |i: &usize| {
+            let x = positions[*i];
+            (x.x >= middle.x) as usize + 2 * (x.y >= middle.y) as usize
+        };
+        self.order[start..end].sort_unstable_by_key(quadrant);
+
+        let first = self.nodes.len();
+        let mut at = start;
+        for q in 0..4 {
+            let length = self.order[at..end].iter().take_while(|i| quadrant(i) == q).count();
+            let corner = min + half * Vec2::new((q % 2) as f32, (q / 2) as f32);
+            let child = self.node(positions, corner, half, at, at + length);
+            self.nodes.push(child);
+            at += length;
+        }
+        self.nodes[index].children = Some(first);
+        for child in first..first + 4 {
+            self.split(positions, child, depth + 1);
+        }
+    }
+
+    /// Walks the tree from a particle at `x`, passing `f` every particle that
+    /// has to be summed exactly and every node far enough away to stand in
+    /// for its particles. Nodes that come within `reach` of `x` are always
+    /// opened.
+    pub fn visit(&self, x: Vec3, theta: f32, reach: f32, mut f: impl FnMut(Source)) {
+        let mut stack = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if node.count == 0.0 {
+                continue;
+            }
+            let Some(first) = node.children else {
+                self.order[node.start..node.end].iter().for_each(|i| f(Source::Particle(*i)));
+                continue;
+            };
+
+            let nearest = x.truncate().clamp(node.min, node.min + node.size);
+            let far = x.truncate().distance(nearest) > reach && node.size < theta * x.distance(node.centroid);
+            if far {
+                f(Source::Cluster { count: node.count, centroid: node.centroid });
+            } else {
+                stack.extend(first..first + 4);
+            }
+        }
+    }
+}
+
+// Distance out to which pairs are summed exactly: three sigma past the ring,
+// where the kernel has fallen below 1e-4 of its peak, or the last control
+// point of a spline, and at least as far as repulsion reaches.
+fn exact_reach(parameters: &Parameters) -> f32 {
+    let reach = match &parameters.kernel {
+        KernelKind::Spline(points) => points.last().map_or(0.0, |(r, _)| *r),
+        _ => parameters.mu_k + 3.0 * parameters.sigma_k,
+    };
+    reach.max(1.0)
+}
+
+/// Pair fields each of the particles at `positions` gets from the others, the
+/// smallest distance between two particles that were summed exactly, and the
+/// distances of the pairs inside the repulsion radius, each counted once.
+pub(crate) fn sum_fields(parameters: &Parameters, positions: &[Vec3], theta: f32) -> (Vec<Fields>, f32, Vec<f32>) {
+    let tree = QuadTree::build(positions);
+    let reach = exact_reach(parameters);
+    let mut min_pair_distance = f32::INFINITY;
+    let mut close = Vec::new();
+    let sums = positions.iter().enumerate()
+        .map(|(i, x_i)| {
+            let mut fields = Fields::default();
+            tree.visit(*x_i, theta, reach, |source| match source {
+                Source::Particle(j) if j == i => {}
+                Source::Particle(j) => {
+                    let d = *x_i - positions[j];
+                    let r = d.length();
+                    min_pair_distance = min_pair_distance.min(r);
+                    if r < 1.0 {
+                        if j > i {
+                            close.push(r);
+                        }
+                        let (R, dR) = repulsion_field(r, parameters.c_rep);
+                        fields.R_val += R;
+                        fields.R_grad += d / r * dR;
+                    }
+                    let (K, dK) = parameters.kernel_field(r);
+                    fields.U_val += K;
+                    fields.U_grad += d / r * dK;
+                }
+                Source::Cluster { count, centroid } => {
+                    let d = *x_i - centroid;
+                    let r = d.length();
+                    let (K, dK) = parameters.kernel_field(r);
+                    fields.U_val += count * K;
+                    fields.U_grad += count * d / r * dK;
+                }
+            });
+            fields
+        })
+        .collect();
+    (sums, min_pair_distance, close)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A 17 x 17 lattice from 0 to 16, so the root splits on x = 8 and y = 8
+    // and every split below it on lattice lines too.
+    fn lattice() -> Vec<Vec3> {
+        (0..17 * 17).map(|i| Vec3::new((i % 17) as f32, (i / 17) as f32, 0.0)).collect()
+    }
+
+    #[test]
+    fn particles_on_dividing_lines_go_above_and_right() {
+        let positions = lattice();
+        let tree = QuadTree::build(&positions);
+
+        let mut on_lines = 0;
+        for node in tree.nodes.iter() {
+            let Some(first) = node.children else {
+                continue;
+            };
+            let middle = node.min + 0.5 * node.size;
+            for q in 0..4 {
+                let child = &tree.nodes[first + q];
+                for i in tree.order[child.start..child.end].iter() {
+                    let x = positions[*i];
+                    assert_eq!(x.x >= middle.x, q % 2 == 1, "{} in quadrant {} of the node split at {}", x, q, middle);
+                    assert_eq!(x.y >= middle.y, q / 2 == 1, "{} in quadrant {} of the node split at {}", x, q, middle);
+                    on_lines += (x.x == middle.x || x.y == middle.y) as usize;
+                }
+            }
+        }
+        assert!(on_lines > 0);
+
+        // The corner of the root's four quadrants is in the upper right one.
+        let root = &tree.nodes[0];
+        let upper_right = &tree.nodes[root.children.unwrap() + 3];
+        let center = positions.iter().position(|x| *x == Vec3::new(8.0, 8.0, 0.0)).unwrap();
+        assert!(tree.order[upper_right.start..upper_right.end].contains(&center));
+    }
+
+    #[test]
+    fn sums_are_identical_from_run_to_run() {
+        let positions = lattice();
+        let parameters = Parameters::default();
+        let (first, ..) = sum_fields(&parameters, &positions, 0.5);
+        let (second, ..) = sum_fields(&parameters, &positions, 0.5);
+        for (a, b) in first.iter().zip(second.iter()) {
+            assert_eq!(a.U_val.to_bits(), b.U_val.to_bits());
+            assert_eq!(a.R_val.to_bits(), b.R_val.to_bits());
+            assert_eq!(a.U_grad.to_array().map(f32::to_bits), b.U_grad.to_array().map(f32::to_bits));
+            assert_eq!(a.R_grad.to_array().map(f32::to_bits), b.R_grad.to_array().map(f32::to_bits));
+        }
+    }
+}
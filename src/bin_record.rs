@@ -0,0 +1,251 @@
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use bevy::{app::AppExit, prelude::*};
+
+use crate::{advance_step, update_position, Creature, Particle, SimStep};
+
+// A recording is a header, then one frame per step, every number
+// little-endian:
+//
+//   b"PLNB", version: u32, seed: u64, creatures: u32, particles: [u32; creatures]
+//   step: u64, count: u32, positions: [[f32; 3]; count]
+//
+// Creatures are in spawn order and each one's particles in child order, as
+// `Simulation::state` lists them, and a frame's positions are in world space.
+const MAGIC: &[u8; 4] = b"PLNB";
+const VERSION: u32 = 1;
+
+/// What a recording was made from: the seed, so a playback can spawn the same
+/// scene, and each creature's particle count when recording started.
+pub struct RecordingHeader {
+    pub seed: u64,
+    pub particle_counts: Vec<u32>,
+}
+
+impl RecordingHeader {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Self::read(&mut BufReader::new(File::open(path)?))
+    }
+
+    fn read(reader: &mut impl Read) -> io::Result<Self> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not a binary recording"));
+        }
+        let version = read_u32(reader)?;
+        if version != VERSION {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("unsupported recording version {}", version)));
+        }
+        let seed = read_u64(reader)?;
+        let creatures = read_u32(reader)?;
+        let particle_counts = (0..creatures).map(|_| read_u32(reader)).collect::<io::Result<_>>()?;
+        Ok(Self { seed, particle_counts })
+    }
+
+    fn write(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&self.seed.to_le_bytes())?;
+        writer.write_all(&(self.particle_counts.len() as u32).to_le_bytes())?;
+        for count in &self.particle_counts {
+            writer.write_all(&count.to_le_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn read_f32(reader: &mut impl Read) -> io::Result<f32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(f32::from_le_bytes(bytes))
+}
+
+// The next frame's step, with its positions in `positions`, or none at the
+// end of the recording.
+fn read_frame(reader: &mut impl Read, positions: &mut Vec<Vec3>) -> io::Result<Option<u64>> {
+    let mut bytes = [0; 8];
+    match reader.read_exact(&mut bytes) {
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        result => result?,
+    }
+    let step = u64::from_le_bytes(bytes);
+    let count = read_u32(reader)?;
+    positions.clear();
+    for _ in 0..count {
+        positions.push(Vec3::new(read_f32(reader)?, read_f32(reader)?, read_f32(reader)?));
+    }
+    Ok(Some(step))
+}
+
+/// Writes every particle's position at every step to a compact binary file,
+/// for playing back with [`BinPlaybackPlugin`]. At 12 bytes a particle a
+/// step, it's a fraction of the size of a RON state or a CSV trajectory.
+pub struct BinRecordPlugin {
+    pub file: File,
+    pub seed: u64,
+}
+
+#[derive(Resource)]
+struct BinRecorder {
+    writer: Option<BufWriter<File>>,
+    seed: u64,
+    header_written: bool,
+}
+
+impl Plugin for BinRecordPlugin {
+    fn build(&self, app: &mut App) {
+        let file = self.file.try_clone().expect("failed to duplicate the recording file handle");
+        app.insert_resource(BinRecorder { writer: Some(BufWriter::new(file)), seed: self.seed, header_written: false })
+            .add_systems(Update, record_frame.after(update_position).before(advance_step))
+            .add_systems(Last, flush_recording);
+    }
+}
+
+fn record_frame(
+    step: Res<SimStep>,
+    mut recorder: ResMut<BinRecorder>,
+    creature_query: Query<(Entity, &Transform, &Children), With<Creature>>,
+    particle_query: Query<&Transform, With<Particle>>,
+) {
+    let mut creatures: Vec<_> = creature_query.iter().collect();
+    creatures.sort_by_key(|(entity, ..)| *entity);
+
+    // The header waits for the first frame, since nothing's spawned when the
+    // plugin is built.
+    let mut bytes = Vec::new();
+    if !recorder.header_written {
+        let header = RecordingHeader {
+            seed: recorder.seed,
+            particle_counts: creatures.iter().map(|(.., children)| particle_query.iter_many(*children).count() as u32).collect(),
+        };
+        header.write(&mut bytes).expect("writing to a Vec doesn't fail");
+        recorder.header_written = true;
+    }
+
+    let positions: Vec<Vec3> = creatures.iter()
+        .flat_map(|(_, creature_transform, children)| {
+            particle_query.iter_many(*children).map(|transform| creature_transform.translation + transform.translation)
+        })
+        .collect();
+    bytes.extend_from_slice(&step.0.to_le_bytes());
+    bytes.extend_from_slice(&(positions.len() as u32).to_le_bytes());
+    for position in positions {
+        for coordinate in position.to_array() {
+            bytes.extend_from_slice(&coordinate.to_le_bytes());
+        }
+    }
+
+    let Some(writer) = recorder.writer.as_mut() else {
+        return;
+    };
+    if let Err(err) = writer.write_all(&bytes) {
+        error!("failed to write the recording, no longer recording: {}", err);
+        recorder.writer = None;
+    }
+}
+
+fn flush_recording(mut recorder: ResMut<BinRecorder>, mut exit: EventReader<AppExit>) {
+    if exit.iter().next().is_none() {
+        return;
+    }
+    if let Some(writer) = recorder.writer.as_mut() {
+        if let Err(err) = writer.flush() {
+            error!("failed to write the recording: {}", err);
+        }
+    }
+}
+
+/// Plays back a recording made with [`BinRecordPlugin`] in place of the
+/// simulation, moving the particles of the spawned scene to one recorded
+/// frame an update. The scene has to come from the same options the
+/// recording did; its seed is taken from the recording unless one is given.
+pub struct BinPlaybackPlugin {
+    pub path: PathBuf,
+}
+
+#[derive(Resource)]
+struct BinPlayback {
+    reader: Option<BufReader<File>>,
+    header: RecordingHeader,
+    positions: Vec<Vec3>,
+    checked: bool,
+}
+
+impl Plugin for BinPlaybackPlugin {
+    fn build(&self, app: &mut App) {
+        let mut reader = BufReader::new(File::open(&self.path).expect("failed to open the recording"));
+        let header = RecordingHeader::read(&mut reader).expect("failed to read the recording's header");
+        app.insert_resource(BinPlayback { reader: Some(reader), header, positions: Vec::new(), checked: false })
+            .add_systems(PreUpdate, play_frame);
+    }
+}
+
+// Creatures match up by spawn order and particles by child order, as
+// they were recorded.
+#[allow(clippy::type_complexity)]
+fn play_frame(
+    mut playback: ResMut<BinPlayback>,
+    mut step: ResMut<SimStep>,
+    creature_query: Query<(Entity, &Transform, &Children), With<Creature>>,
+    mut particle_query: Query<&mut Transform, (With<Particle>, Without<Creature>)>,
+) {
+    let mut creatures: Vec<_> = creature_query.iter().collect();
+    creatures.sort_by_key(|(entity, ..)| *entity);
+    if !playback.checked {
+        playback.checked = true;
+        let counts: Vec<u32> = creatures.iter().map(|(.., children)| particle_query.iter_many(*children).count() as u32).collect();
+        if counts != playback.header.particle_counts {
+            warn!(
+                recorded = ?playback.header.particle_counts,
+                spawned = ?counts,
+                "the scene's particle counts differ from the recording's; play it back with the --demo, --stress or --load-state it was recorded with",
+            );
+        }
+    }
+
+    let playback = &mut *playback;
+    let Some(reader) = playback.reader.as_mut() else {
+        return;
+    };
+    match read_frame(reader, &mut playback.positions) {
+        Ok(Some(recorded_step)) => step.0 = recorded_step,
+        Ok(None) => {
+            info!("playback finished at step {}", step.0);
+            playback.reader = None;
+            return;
+        }
+        Err(err) => {
+            error!("failed to read the recording, stopping playback: {}", err);
+            playback.reader = None;
+            return;
+        }
+    }
+
+    let mut positions = playback.positions.iter();
+    for (_, creature_transform, children) in creatures {
+        let mut children = particle_query.iter_many_mut(children);
+        while let Some(mut transform) = children.fetch_next() {
+            let Some(position) = positions.next() else {
+                return;
+            };
+            transform.translation = *position - creature_transform.translation;
+        }
+    }
+}
@@ -0,0 +1,271 @@
+use bevy::{prelude::*, sprite::Mesh2dHandle};
+use clap::ValueEnum;
+
+use crate::{
+    advance_step, calculate_fields, clamp_forces, culling::ViewBounds, substeps::Substep, update_position,
+    Creature, Fields, ForceTerms, Parameters, Particle, Velocity,
+};
+
+// Seconds the camera's view has to stay put before the bounds follow it, so
+// dragging the window edge doesn't refit them every frame.
+const FIT_DEBOUNCE: f32 = 0.25;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BoundaryMode {
+    /// Particles bounce off the edges
+    Reflect,
+    /// Particles leaving one edge re-enter at the opposite one. Fields are
+    /// not wrapped, so particles on opposite edges don't feel each other.
+    Periodic,
+}
+
+/// World-space extents particles are confined to, once known.
+#[derive(Resource)]
+pub struct Bounds {
+    pub mode: BoundaryMode,
+    pub rect: Option<Rect>,
+}
+
+pub struct BoundaryPlugin {
+    pub mode: BoundaryMode,
+    pub rect: Option<Rect>,
+    /// Keep the bounds matched to the camera's view
+    pub fit_to_camera: bool,
+}
+
+#[derive(Resource, Default)]
+struct PendingFit(Option<(Rect, f32)>);
+
+impl Plugin for BoundaryPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Bounds {
+                mode: self.mode,
+                rect: self.rect,
+            })
+            .add_systems(Update, apply_boundary.after(update_position).before(advance_step));
+
+        if self.fit_to_camera {
+            app.init_resource::<PendingFit>()
+                .add_systems(Update, fit_bounds_to_camera.before(apply_boundary));
+        }
+    }
+}
+
+fn fit_bounds_to_camera(
+    time: Res<Time>,
+    view_bounds: ViewBounds,
+    mut pending: ResMut<PendingFit>,
+    mut bounds: ResMut<Bounds>,
+) {
+    let Some(view) = view_bounds.view_rect() else {
+        return;
+    };
+    if bounds.rect.is_none() {
+        bounds.rect = Some(view);
+        return;
+    }
+
+    let now = time.elapsed_seconds();
+    match pending.0 {
+        Some((rect, since)) if rect == view => {
+            if now - since >= FIT_DEBOUNCE {
+                bounds.rect = Some(rect);
+                pending.0 = None;
+            }
+        }
+        _ if bounds.rect != Some(view) => pending.0 = Some((view, now)),
+        _ => pending.0 = None,
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn apply_boundary(
+    bounds: Res<Bounds>,
+    creature_query: Query<(&Transform, &Children), With<Creature>>,
+    mut particle_query: Query<(&mut Transform, &mut Velocity), (With<Particle>, Without<Creature>)>,
+) {
+    let Some(rect) = bounds.rect else {
+        return;
+    };
+
+    for (creature_transform, children) in creature_query.iter() {
+        let offset = creature_transform.translation.truncate();
+        for child in children.iter() {
+            let Ok((mut transform, mut velocity)) = particle_query.get_mut(*child) else {
+                continue;
+            };
+            let mut x = transform.translation.truncate() + offset;
+            if rect.contains(x) {
+                continue;
+            }
+
+            for axis in 0..2 {
+                let (min, max) = (rect.min[axis], rect.max[axis]);
+                match bounds.mode {
+                    BoundaryMode::Reflect => {
+                        if x[axis] < min {
+                            x[axis] = (2.0 * min - x[axis]).min(max);
+                            velocity.0[axis] = velocity.0[axis].abs();
+                        } else if x[axis] > max {
+                            x[axis] = (2.0 * max - x[axis]).max(min);
+                            velocity.0[axis] = -velocity.0[axis].abs();
+                        }
+                    }
+                    BoundaryMode::Periodic => {
+                        x[axis] = min + (x[axis] - min).rem_euclid(max - min);
+                    }
+                }
+            }
+
+            transform.translation = (x - offset).extend(transform.translation.z);
+        }
+    }
+}
+
+/// W toggles drawing copies of the particles near each periodic edge past
+/// the opposite one, out to the kernel radius `mu_k + 2 sigma_k`, so a
+/// creature straddling an edge looks whole on either side of it. The copies
+/// share their particle's mesh and material but aren't particles, so nothing
+/// simulates, picks or exports them.
+pub struct WrapGhostsPlugin {
+    pub enabled: bool,
+}
+
+#[derive(Resource)]
+struct WrapGhosts(bool);
+
+#[derive(Component)]
+struct Ghost;
+
+impl Plugin for WrapGhostsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(WrapGhosts(self.enabled))
+            .add_systems(Update, (toggle_wrap_ghosts, update_wrap_ghosts.after(apply_boundary)).chain());
+    }
+}
+
+fn toggle_wrap_ghosts(keys: Res<Input<KeyCode>>, mut ghosts: ResMut<WrapGhosts>) {
+    if keys.just_pressed(KeyCode::W) {
+        ghosts.0 = !ghosts.0;
+    }
+}
+
+// Ghosts are kept from frame to frame and hidden when there are more than
+// needed, rather than spawned afresh each frame.
+#[allow(clippy::type_complexity)]
+fn update_wrap_ghosts(
+    mut commands: Commands,
+    ghosts: Res<WrapGhosts>,
+    bounds: Res<Bounds>,
+    creature_query: Query<(&Transform, &Parameters, &Children), With<Creature>>,
+    particle_query: Query<(&Transform, &Mesh2dHandle, &Handle<ColorMaterial>), (With<Particle>, Without<Creature>)>,
+    mut ghost_query: Query<(&mut Transform, &mut Mesh2dHandle, &mut Handle<ColorMaterial>, &mut Visibility), (With<Ghost>, Without<Particle>, Without<Creature>)>,
+) {
+    let mut placements = Vec::new();
+    if let Some(rect) = bounds.rect.filter(|_| ghosts.0 && bounds.mode == BoundaryMode::Periodic) {
+        let size = rect.size();
+        for (creature_transform, parameters, children) in creature_query.iter() {
+            let margin = parameters.mu_k + 2.0 * parameters.sigma_k;
+            for (transform, mesh, material) in particle_query.iter_many(children) {
+                let x = creature_transform.translation + transform.translation;
+                // Shift toward the far side along each axis the particle is near an edge of.
+                let shift = |axis: usize| {
+                    if x[axis] < rect.min[axis] + margin {
+                        size[axis]
+                    } else if x[axis] > rect.max[axis] - margin {
+                        -size[axis]
+                    } else {
+                        0.0
+                    }
+                };
+                let (dx, dy) = (shift(0), shift(1));
+                // Near a corner it shows up past the other three.
+                let offsets = match (dx != 0.0, dy != 0.0) {
+                    (false, false) => continue,
+                    (true, false) => vec![Vec2::new(dx, 0.0)],
+                    (false, true) => vec![Vec2::new(0.0, dy)],
+                    (true, true) => vec![Vec2::new(dx, 0.0), Vec2::new(0.0, dy), Vec2::new(dx, dy)],
+                };
+                for offset in offsets {
+                    placements.push((x + offset.extend(0.0), mesh.clone(), material.clone()));
+                }
+            }
+        }
+    }
+
+    let mut placements = placements.into_iter();
+    for (mut transform, mut mesh, mut material, mut visibility) in ghost_query.iter_mut() {
+        match placements.next() {
+            Some((translation, new_mesh, new_material)) => {
+                transform.translation = translation;
+                *mesh = new_mesh;
+                *material = new_material;
+                *visibility = Visibility::Inherited;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+    for (translation, mesh, material) in placements {
+        commands.spawn((
+            ColorMesh2dBundle {
+                mesh,
+                material,
+                transform: Transform::from_translation(translation),
+                ..default()
+            },
+            Ghost,
+        ));
+    }
+}
+
+pub fn draw_bounds(mut gizmos: Gizmos, bounds: Res<Bounds>) {
+    if let Some(rect) = bounds.rect {
+        gizmos.rect_2d(rect.center(), 0.0, rect.size(), Color::GRAY);
+    }
+}
+
+/// Quadratic potential `k/2 (|x| - radius)^2` outside a circle about the
+/// origin, pulling particles back in more the further out they are, instead
+/// of bouncing them off a wall.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct SoftBoundary {
+    pub radius: f32,
+    pub stiffness: f32,
+}
+
+pub struct SoftBoundaryPlugin {
+    pub boundary: SoftBoundary,
+}
+
+impl Plugin for SoftBoundaryPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.boundary)
+            .add_systems(Update, confine_softly.after(calculate_fields).before(clamp_forces))
+            .add_systems(Substep, confine_softly.after(calculate_fields).before(clamp_forces));
+    }
+}
+
+fn confine_softly(
+    force_terms: Res<ForceTerms>,
+    boundary: Res<SoftBoundary>,
+    creature_query: Query<(&Transform, &Children), With<Creature>>,
+    mut particle_query: Query<(&Transform, &mut Fields), With<Particle>>,
+) {
+    if !force_terms.external {
+        return;
+    }
+    for (creature_transform, children) in creature_query.iter() {
+        let mut particles = particle_query.iter_many_mut(children);
+        while let Some((transform, mut fields)) = particles.fetch_next() {
+            let x = creature_transform.translation + transform.translation;
+            let overshoot = x.length() - boundary.radius;
+            if overshoot > 0.0 {
+                fields.E_grad += boundary.stiffness * overshoot * x.normalize();
+            }
+        }
+    }
+}
+
+pub fn draw_soft_boundary(mut gizmos: Gizmos, boundary: Res<SoftBoundary>) {
+    gizmos.circle_2d(Vec2::ZERO, boundary.radius, Color::GRAY);
+}
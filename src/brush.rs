@@ -0,0 +1,111 @@
+use bevy::prelude::*;
+
+use crate::{picking::Cursor, Creature, GrowthOverride, Parameters, Particle};
+
+const MU_G_STEP: f32 = 0.02;
+
+/// Growth parameters painted onto particles under the cursor with the middle mouse button.
+#[derive(Resource)]
+pub struct ParamBrush {
+    pub radius: f32,
+    pub mu_g: f32,
+    pub sigma_g: f32,
+}
+
+impl Default for ParamBrush {
+    fn default() -> Self {
+        let parameters = Parameters::default();
+        ParamBrush {
+            radius: 3.0,
+            mu_g: parameters.mu_g + MU_G_STEP * 5.0,
+            sigma_g: parameters.sigma_g,
+        }
+    }
+}
+
+pub struct BrushPlugin;
+
+impl Plugin for BrushPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ParamBrush>()
+            .add_systems(Update, (adjust_brush, paint_growth, draw_brush).chain());
+    }
+}
+
+fn adjust_brush(keys: Res<Input<KeyCode>>, mut brush: ResMut<ParamBrush>) {
+    let delta = if keys.just_pressed(KeyCode::Up) {
+        MU_G_STEP
+    } else if keys.just_pressed(KeyCode::Down) {
+        -MU_G_STEP
+    } else {
+        return;
+    };
+
+    brush.mu_g = (brush.mu_g + delta).max(0.0);
+    info!("brush mu_g: {:.2}", brush.mu_g);
+}
+
+// Middle-drag paints the brush's growth parameters onto particles; with Shift
+// held it erases them, returning the particles to their creature's defaults.
+#[allow(clippy::too_many_arguments)]
+fn paint_growth(
+    mut commands: Commands,
+    buttons: Res<Input<MouseButton>>,
+    keys: Res<Input<KeyCode>>,
+    cursor: Cursor,
+    brush: Res<ParamBrush>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    creature_query: Query<(&Transform, &Parameters, &Children), With<Creature>>,
+    particle_query: Query<(&Transform, Option<&Handle<ColorMaterial>>), With<Particle>>,
+) {
+    if !buttons.pressed(MouseButton::Middle) {
+        return;
+    }
+    let Some(point) = cursor.world_position() else {
+        return;
+    };
+    let erase = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+
+    for (creature_transform, parameters, children) in creature_query.iter() {
+        for child in children.iter() {
+            let Ok((transform, material)) = particle_query.get(*child) else {
+                continue;
+            };
+            if (creature_transform.translation + transform.translation).truncate().distance(point) > brush.radius {
+                continue;
+            }
+
+            let color = if erase {
+                commands.entity(*child).remove::<GrowthOverride>();
+                Color::WHITE
+            } else {
+                commands.entity(*child).insert(GrowthOverride {
+                    mu_g: brush.mu_g,
+                    sigma_g: brush.sigma_g,
+                });
+                override_color(brush.mu_g - parameters.mu_g)
+            };
+
+            if let Some(material) = material.and_then(|handle| materials.get_mut(handle)) {
+                material.color = color;
+            }
+        }
+    }
+}
+
+// Tints painted particles red when they grow at a higher density than their
+// creature and blue when lower.
+fn override_color(delta_mu_g: f32) -> Color {
+    let t = (delta_mu_g / (MU_G_STEP * 10.0)).clamp(-1.0, 1.0);
+    if t >= 0.0 {
+        Color::rgb(1.0, 1.0 - t, 1.0 - t)
+    } else {
+        Color::rgb(1.0 + t, 1.0 + t, 1.0)
+    }
+}
+
+fn draw_brush(mut gizmos: Gizmos, cursor: Cursor, brush: Res<ParamBrush>) {
+    if let Some(point) = cursor.world_position() {
+        gizmos.circle_2d(point, brush.radius, override_color(brush.mu_g - Parameters::default().mu_g));
+    }
+}
@@ -0,0 +1,50 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{minimap::MinimapCamera, Creature, Particle};
+
+/// Once the scene has spawned, moves and zooms the camera so that every
+/// particle is in view, with `margin` of the view's width and height left
+/// clear on each side, instead of starting at the scene's fixed zoom.
+pub struct CameraFitPlugin {
+    pub margin: f32,
+}
+
+#[derive(Resource)]
+struct FitMargin(f32);
+
+impl Plugin for CameraFitPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(FitMargin(self.margin))
+            // After Startup, whose commands spawn the particles and the camera.
+            .add_systems(PostStartup, fit_camera);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn fit_camera(
+    margin: Res<FitMargin>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut camera_query: Query<(&mut Transform, &mut OrthographicProjection), (With<Camera>, Without<MinimapCamera>, Without<Creature>, Without<Particle>)>,
+    creature_query: Query<(&Transform, &Children), With<Creature>>,
+    particle_query: Query<&Transform, (With<Particle>, Without<Creature>)>,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let bounds = creature_query.iter()
+        .flat_map(|(creature_transform, children)| {
+            particle_query.iter_many(children).map(|transform| (creature_transform.translation + transform.translation).truncate())
+        })
+        .fold(None, |bounds: Option<Rect>, point| Some(bounds.map_or(Rect::from_center_size(point, Vec2::ZERO), |bounds| bounds.union_point(point))));
+    let Some(bounds) = bounds else {
+        return;
+    };
+
+    // A particle's width past the outermost centers, so their disks fit too.
+    let size = bounds.size() + 2.0;
+    let view = Vec2::new(window.width(), window.height()) * (1.0 - 2.0 * margin.0);
+    for (mut transform, mut projection) in camera_query.iter_mut() {
+        transform.translation = bounds.center().extend(transform.translation.z);
+        projection.scale = (size / view).max_element();
+    }
+}
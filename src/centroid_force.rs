@@ -0,0 +1,91 @@
+use bevy::prelude::*;
+use clap::ValueEnum;
+
+use crate::{
+    attractor::FalloffKind, calculate_fields, clamp_forces, stats::{update_creature_stats, CreatureStats},
+    substeps::Substep, Creature, Fields, ForceTerms, Particle,
+};
+
+/// How the pull between two creatures' centroids weakens with distance, the
+/// attractors' falloffs with `range` as their length scale.
+#[derive(ValueEnum, Clone, Copy, Debug, Default)]
+pub enum CentroidFalloff {
+    Constant,
+    Linear,
+    InverseSquare,
+    #[default]
+    Gaussian,
+}
+
+impl CentroidFalloff {
+    pub fn kind(self, range: f32) -> FalloffKind {
+        match self {
+            CentroidFalloff::Constant => FalloffKind::Constant,
+            CentroidFalloff::Linear => FalloffKind::Linear { range },
+            CentroidFalloff::InverseSquare => FalloffKind::InverseSquare { softening: range },
+            CentroidFalloff::Gaussian => FalloffKind::Gaussian { sigma: range },
+        }
+    }
+}
+
+/// Pull between whole creatures: each one is drawn toward every other
+/// creature's centroid by `strength` weighted by `falloff` of their distance,
+/// or pushed away if `strength` is negative. The force is the same on each of
+/// its particles, so it moves the creature without deforming it, at a cost
+/// in the number of creatures rather than particles.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct CentroidForce {
+    pub strength: f32,
+    pub falloff: FalloffKind,
+}
+
+pub struct CentroidForcePlugin {
+    pub force: CentroidForce,
+}
+
+impl Plugin for CentroidForcePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.force)
+            .add_systems(Update, apply_centroid_force.after(calculate_fields).after(update_creature_stats).before(clamp_forces))
+            .add_systems(Substep, apply_centroid_force.after(calculate_fields).before(clamp_forces));
+    }
+}
+
+// Substeps use the centroids from the start of the step, which move little
+// within one.
+fn apply_centroid_force(
+    force_terms: Res<ForceTerms>,
+    force: Res<CentroidForce>,
+    creature_query: Query<(Entity, &CreatureStats, &Children), With<Creature>>,
+    mut particle_query: Query<&mut Fields, With<Particle>>,
+) {
+    if !force_terms.external {
+        return;
+    }
+    let centroids: Vec<(Entity, Vec3)> = creature_query.iter()
+        .map(|(creature, stats, _)| (creature, stats.centroid))
+        .filter(|(_, centroid)| centroid.is_finite())
+        .collect();
+    if centroids.len() < 2 {
+        return;
+    }
+
+    for (creature, stats, children) in creature_query.iter() {
+        if !stats.centroid.is_finite() {
+            continue;
+        }
+        let mut pull = Vec3::ZERO;
+        for (other, centroid) in centroids.iter() {
+            let d = *centroid - stats.centroid;
+            let r = d.length();
+            if *other != creature && r > f32::EPSILON {
+                pull += (d / r) * force.strength * force.falloff.weight(r);
+            }
+        }
+
+        let mut particles = particle_query.iter_many_mut(children);
+        while let Some(mut fields) = particles.fetch_next() {
+            fields.E_grad -= pull;
+        }
+    }
+}
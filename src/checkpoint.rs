@@ -0,0 +1,88 @@
+use std::{
+    collections::VecDeque,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+
+use crate::{state::StateCapture, SimStep};
+
+/// Every `every` steps, saves the state to `checkpoint-<step>.ron` in `dir`,
+/// removing the oldest so only the last `keep` remain, so a crashed run loses
+/// at most `every` steps. `--resume` picks the run back up from one, RNG and
+/// all. Each file is written in full before it replaces anything.
+pub struct CheckpointPlugin {
+    pub dir: PathBuf,
+    pub every: u64,
+    pub keep: usize,
+}
+
+#[derive(Resource)]
+struct Checkpoints {
+    dir: PathBuf,
+    every: u64,
+    keep: usize,
+    // Checkpoints in the directory, oldest first.
+    written: VecDeque<PathBuf>,
+}
+
+impl Plugin for CheckpointPlugin {
+    fn build(&self, app: &mut App) {
+        // A resumed run carries on rotating the checkpoints it was resumed from.
+        let written = existing(&self.dir).unwrap_or_default().into_iter().map(|(_, path)| path).collect();
+        app.insert_resource(Checkpoints { dir: self.dir.clone(), every: self.every.max(1), keep: self.keep.max(1), written })
+            // After Update's commands are applied, so it has the step's
+            // births and deaths.
+            .add_systems(PostUpdate, write_checkpoint.run_if(resource_changed::<SimStep>()));
+    }
+}
+
+/// The newest checkpoint if `path` is a directory of them, `path` otherwise.
+pub fn resolve(path: &Path) -> io::Result<PathBuf> {
+    if !path.is_dir() {
+        return Ok(path.to_path_buf());
+    }
+    existing(path)?.pop().map(|(_, path)| path)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no checkpoint in the directory"))
+}
+
+// Checkpoints in `dir` and their steps, oldest first.
+fn existing(dir: &Path) -> io::Result<Vec<(u64, PathBuf)>> {
+    let mut checkpoints: Vec<_> = fs::read_dir(dir)?
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let step = path.file_name()?.to_str()?.strip_prefix("checkpoint-")?.strip_suffix(".ron")?.parse().ok()?;
+            Some((step, path))
+        })
+        .collect();
+    checkpoints.sort();
+    Ok(checkpoints)
+}
+
+fn write_checkpoint(mut checkpoints: ResMut<Checkpoints>, step: Res<SimStep>, capture: StateCapture) {
+    if step.0 == 0 || !step.0.is_multiple_of(checkpoints.every) {
+        return;
+    }
+
+    let path = checkpoints.dir.join(format!("checkpoint-{:06}.ron", step.0));
+    let partial = path.with_extension("ron.partial");
+    let written = fs::create_dir_all(&checkpoints.dir).map_err(|err| err.to_string())
+        .and_then(|_| capture.capture().save(&partial))
+        .and_then(|_| fs::rename(&partial, &path).map_err(|err| err.to_string()));
+    if let Err(err) = written {
+        error!("step {}: failed to write a checkpoint to {}: {}", step.0, path.display(), err);
+        return;
+    }
+    info!("step {}: wrote checkpoint {}", step.0, path.display());
+
+    if !checkpoints.written.contains(&path) {
+        checkpoints.written.push_back(path);
+    }
+    while checkpoints.written.len() > checkpoints.keep {
+        let oldest = checkpoints.written.pop_front().unwrap();
+        if let Err(err) = fs::remove_file(&oldest) {
+            warn!("failed to remove old checkpoint {}: {}", oldest.display(), err);
+        }
+    }
+}
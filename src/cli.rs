@@ -0,0 +1,842 @@
+use std::{num::NonZeroUsize, path::PathBuf};
+
+use bevy::prelude::Resource;
+use clap::Parser;
+
+use crate::{boundary::BoundaryMode, centroid_force::CentroidFalloff, colormap::ColorMode, measure::ReadoutCorner, outline::OutlineMode, param_map::ParameterAxis, presets::Preset, quality::RenderQuality, scene::{Demo, DISK_RADIUS}, KernelKind, ParticleShape, ZOrderMode};
+
+#[derive(Parser, Resource, Clone, Debug)]
+#[command(about = "Particle Lenia in Bevy")]
+pub struct Args {
+    /// Spawn a named demo scene instead of a single creature
+    #[arg(long, value_enum)]
+    pub demo: Option<Demo>,
+
+    /// Benchmark a grid of identical creatures and log the time per step
+    #[arg(long, num_args = 2, value_names = ["CREATURES", "PARTICLES_EACH"], conflicts_with = "demo", requires = "headless")]
+    pub stress: Option<Vec<usize>>,
+
+    /// RNG seed for the initial particle clouds
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Parameter preset for every spawned creature
+    #[arg(long, value_enum)]
+    pub preset: Option<Preset>,
+
+    /// Split the window between two creatures grown from the same particles
+    /// under presets LEFT and RIGHT
+    #[arg(long, value_enum, num_args = 2, value_names = ["LEFT", "RIGHT"], conflicts_with_all = ["demo", "stress", "preset", "morph_to", "load_state"])]
+    pub compare: Option<Vec<Preset>>,
+
+    /// Preset the creatures' parameters are morphed into after spawning
+    #[arg(long, value_enum)]
+    pub morph_to: Option<Preset>,
+
+    /// Simulation time the morph takes
+    #[arg(long, default_value_t = 100.0)]
+    pub morph_duration: f32,
+
+    /// Parameter to swing sinusoidally about each creature's value, as
+    /// value + amplitude * sin(2 pi frequency t)
+    #[arg(long, value_enum, value_name = "PARAM", requires = "oscillate_amplitude")]
+    pub oscillate: Option<ParameterAxis>,
+
+    /// How far --oscillate swings the parameter either way
+    #[arg(long, value_name = "A", requires = "oscillate")]
+    pub oscillate_amplitude: Option<f32>,
+
+    /// Cycles per unit of simulation time of --oscillate
+    #[arg(long, value_name = "F", default_value_t = 0.01, requires = "oscillate")]
+    pub oscillate_frequency: f32,
+
+    /// Spawn particles on a square lattice instead of sampling a disk
+    #[arg(long)]
+    pub lattice: bool,
+
+    /// Spawn each creature with exact N-fold rotational symmetry about its
+    /// centroid, as a random wedge of particles and N - 1 turned copies of
+    /// it, keeping roughly the same particle count. Creatures spawned from
+    /// explicit positions, such as --template, keep them
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(2..), conflicts_with_all = ["lattice", "total_mass"])]
+    pub symmetry: Option<u32>,
+
+    /// Spawn particles by rejection sampling from this density f(x, y) of the
+    /// offset from the creature's position, such as "exp(-(r - 6)^2)" for a
+    /// ring. It may use x, y, r, theta, pi, e, + - * / ^ and sin, cos, tan,
+    /// asin, acos, atan, sinh, cosh, tanh, exp, ln, log10, sqrt, abs, floor,
+    /// ceil, step, min, max, pow and atan2; negative values count as zero.
+    /// Sampling follows --seed. Creatures spawned from explicit positions,
+    /// such as --template, keep them
+    #[arg(long, value_name = "EXPR", conflicts_with_all = ["lattice", "symmetry", "total_mass"])]
+    pub density: Option<String>,
+
+    /// Radius of the disk --density is sampled within
+    #[arg(long, value_name = "RADIUS", default_value_t = DISK_RADIUS, requires = "density")]
+    pub density_radius: f32,
+
+    /// Uniform noise added to spawn positions [default: 0 on a lattice, 0.01 in a disk]
+    #[arg(long)]
+    pub jitter: Option<f32>,
+
+    /// Relax each new creature for this many steps before it first appears
+    #[arg(long, default_value_t = 0)]
+    pub settle_steps: usize,
+
+    /// Confine particles to the world bounds
+    #[arg(long, value_enum)]
+    pub boundary: Option<BoundaryMode>,
+
+    /// World bounds for --boundary
+    #[arg(long, num_args = 4, value_names = ["X0", "Y0", "X1", "Y1"], allow_negative_numbers = true, requires = "boundary")]
+    pub bounds: Option<Vec<f32>>,
+
+    /// Match the world bounds to the camera's view, following it when it changes
+    #[arg(long, requires = "boundary", conflicts_with_all = ["bounds", "headless"])]
+    pub fit_bounds: bool,
+
+    /// Start out drawing copies of the particles near a periodic edge past the
+    /// opposite one, toggled with W
+    #[arg(long, requires = "boundary", conflicts_with = "headless")]
+    pub wrap_ghosts: bool,
+
+    /// Pull particles further than R_SOFT from the origin back with a
+    /// quadratic penalty instead of a wall
+    #[arg(long, value_name = "R_SOFT")]
+    pub soft_boundary: Option<f32>,
+
+    /// Stiffness k of the --soft-boundary penalty k/2 (|x| - R_SOFT)^2
+    #[arg(long, value_name = "K", default_value_t = 0.1, requires = "soft_boundary")]
+    pub boundary_stiffness: f32,
+
+    /// Let particles of different creatures repel each other
+    #[arg(long)]
+    pub inter_creature: bool,
+
+    /// Drop the growth term so particles only feel repulsion
+    #[arg(long)]
+    pub no_growth: bool,
+
+    /// Drop the repulsion term, leaving the growth term to pull particles
+    /// together unopposed
+    #[arg(long)]
+    pub no_repulsion: bool,
+
+    /// Drop the forces from outside the model: attractors, the soft boundary,
+    /// the centroid force and the target profile
+    #[arg(long)]
+    pub no_external_forces: bool,
+
+    /// Leave each particle's own contribution at r = 0 out of its R and U, so
+    /// an isolated particle's fields are zero instead of c_rep / 2 and K(0).
+    /// Growth then responds to the neighbors alone, and sizes by R_val grow
+    /// for sparse particles, up to an isolated particle's usual size
+    #[arg(long)]
+    pub no_self_field: bool,
+
+    /// Open the panel of force term checkboxes at startup (toggle with F9)
+    #[arg(long, conflicts_with_all = ["headless", "background_sim", "play_bin"])]
+    pub force_panel: bool,
+
+    /// Cycle through phases with their own force terms from a JSON schedule,
+    /// e.g. spreading under repulsion alone before the full dynamics
+    #[arg(long, value_name = "FILE", conflicts_with = "no_growth")]
+    pub anneal: Option<PathBuf>,
+
+    /// Largest disagreement, relative to the largest derivative, between a
+    /// kernel's derivative and a finite difference of its values before a
+    /// warning. Each kernel is checked when a creature first has it
+    #[arg(long, value_name = "TOLERANCE", default_value_t = 1e-2)]
+    pub gradient_tolerance: f32,
+
+    /// Change creatures' parameters at set steps from a JSON timeline, at
+    /// once or morphed over a duration
+    #[arg(long, value_name = "FILE")]
+    pub timeline: Option<PathBuf>,
+
+    /// Shape of the interaction kernel K(r)
+    #[arg(long, value_enum, default_value_t)]
+    pub kernel: KernelKind,
+
+    /// Shape of the growth mapping G(U)
+    #[arg(long, value_enum, default_value_t)]
+    pub growth_kernel: KernelKind,
+
+    /// Use a double-well growth function, the sum of two Gaussians, for every creature
+    #[arg(long, num_args = 4, value_names = ["MU_1", "SIGMA_1", "MU_2", "SIGMA_2"])]
+    pub double_well: Option<Vec<f32>>,
+
+    /// Clamp each particle's |E_grad| to at most this value
+    #[arg(long)]
+    pub max_force: Option<f32>,
+
+    /// Ramp every force up from zero over the first STEPS steps, so creatures
+    /// assemble gently instead of from a full-strength first step
+    #[arg(long, value_name = "STEPS", value_parser = clap::value_parser!(u32).range(1..))]
+    pub interaction_ramp: Option<u32>,
+
+    /// Scale pair separations per axis before taking their length; below one
+    /// stretches creatures along that axis [default: 1 1 1]
+    #[arg(long, num_args = 2..=3, value_names = ["SX", "SY", "SZ"])]
+    pub metric_scale: Option<Vec<f32>>,
+
+    /// JSON matrix of kernel parameters for each pair of species; new
+    /// particles cycle through the species in turn
+    #[arg(long, value_name = "FILE")]
+    pub interaction_matrix: Option<PathBuf>,
+
+    /// Accumulate the pair loop's field sums in f64 before storing them as f32
+    #[arg(long)]
+    pub wide_sums: bool,
+
+    /// Fade the interaction kernel out smoothly between R_ON and R_CUT and
+    /// skip pairs beyond R_CUT, so the force has no jump at the cutoff
+    #[arg(long, num_args = 2, value_names = ["R_ON", "R_CUT"], conflicts_with = "barnes_hut")]
+    pub kernel_cutoff: Option<Vec<f32>>,
+
+    /// Sum each creature's own fields with a Barnes-Hut quadtree of opening
+    /// angle THETA instead of over every pair; smaller is more accurate
+    #[arg(long, value_name = "THETA", conflicts_with_all = ["wide_sums", "metric_scale", "interaction_matrix"])]
+    pub barnes_hut: Option<f32>,
+
+    /// Sum the pair fields in a compute shader instead of on the CPU
+    #[cfg(feature = "gpu")]
+    #[arg(long, conflicts_with_all = ["headless", "wide_sums", "metric_scale", "interaction_matrix", "background_sim", "substeps", "barnes_hut", "kernel_cutoff", "validate_fields", "rdf_csv", "force_bands", "encounters_csv"])]
+    pub gpu: bool,
+
+    /// Debugging: recompute each particle's pair fields on its own, without
+    /// the symmetric pair loop's shortcut, and panic if the two disagree.
+    /// Quadratically slower
+    #[arg(long, conflicts_with_all = ["barnes_hut", "background_sim"])]
+    pub validate_fields: bool,
+
+    /// Difference --validate-fields tolerates, relative to the size of the
+    /// field where that's above one and absolute below it
+    #[arg(long, value_name = "TOLERANCE", default_value_t = 1e-4, requires = "validate_fields")]
+    pub validate_tolerance: f32,
+
+    /// Every this many steps, log how far the --kernel-cutoff or --barnes-hut
+    /// field sums are from a direct sum over every pair, for a sample of
+    /// particles. In the window F6 logs it on demand
+    #[arg(long, value_name = "STEPS", value_parser = clap::value_parser!(u64).range(1..))]
+    pub truncation_report: Option<u64>,
+
+    /// Particles the truncation report sums directly
+    #[arg(long, value_name = "N", default_value_t = 64, value_parser = clap::value_parser!(u32).range(1..))]
+    pub truncation_sample: u32,
+
+    /// Undo and redo a step with half the time step when the next one shows
+    /// non-finite fields, a force spike or a jump in energy
+    #[arg(long)]
+    pub safe_mode: bool,
+
+    /// Most times --safe-mode may halve the time step over the run
+    #[arg(long, default_value_t = 8, requires = "safe_mode")]
+    pub max_dt_reductions: u32,
+
+    /// Replace a scene creature that explodes with a fresh cloud from its
+    /// spawn and current parameters, and report the deaths on exit
+    #[arg(long)]
+    pub auto_respawn: bool,
+
+    /// Also count a creature as dead once its spread passes SPREAD
+    #[arg(long, value_name = "SPREAD", requires = "auto_respawn")]
+    pub death_spread: Option<f32>,
+
+    /// Integrate particles with inertia instead of overdamped motion
+    #[arg(long)]
+    pub momentum: bool,
+
+    /// Moves each step is split into, with the fields recomputed before each
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..), conflicts_with = "safe_mode")]
+    pub substeps: u32,
+
+    /// Velocity damping rate in momentum mode [default: 1.0]
+    #[arg(long)]
+    pub damping: Option<f32>,
+
+    /// Extra damping in proportion to each particle's R_val: in momentum mode
+    /// it adds to the damping rate, in overdamped mode it shortens the step
+    #[arg(long, default_value_t = 0.0)]
+    pub density_damping: f32,
+
+    /// Integrate in a frame rotating counterclockwise about the origin at
+    /// this angular velocity, adding its centrifugal pseudo-force
+    #[arg(long, value_name = "OMEGA", default_value_t = 0.0, allow_negative_numbers = true)]
+    pub frame_omega: f32,
+
+    /// Add the Coriolis pseudo-force of --frame-omega as well
+    #[arg(long)]
+    pub coriolis: bool,
+
+    /// Turn the camera against --frame-omega, so the view shows the
+    /// non-rotating frame instead of the rotating one
+    #[arg(long, conflicts_with = "headless")]
+    pub counter_rotate_camera: bool,
+
+    /// Exact offset Alt-click and --perturb-particle move a particle by
+    #[arg(long, num_args = 2, value_names = ["DX", "DY"], allow_negative_numbers = true, default_values_t = [0.01, 0.0])]
+    pub perturb_offset: Vec<f32>,
+
+    /// Move this particle, counted over all creatures in spawn order, by
+    /// --perturb-offset at --perturb-step
+    #[arg(long, value_name = "INDEX")]
+    pub perturb_particle: Option<usize>,
+
+    /// Step --perturb-particle is moved at
+    #[arg(long, default_value_t = 0, requires = "perturb_particle")]
+    pub perturb_step: u64,
+
+    /// Move a creature's drift onto its transform once its particles are this
+    /// far from its origin, so the field sums keep their precision
+    #[arg(long, value_name = "DISTANCE")]
+    pub recenter_distance: Option<f32>,
+
+    /// Pull each particle's R_val toward a density of CENTER at its creature's
+    /// centroid, changing linearly to EDGE at RADIUS and beyond
+    #[arg(long, num_args = 3, value_names = ["CENTER", "EDGE", "RADIUS"])]
+    pub target_profile: Option<Vec<f32>>,
+
+    /// Stiffness of the pull toward --target-profile
+    #[arg(long, default_value_t = 0.1, requires = "target_profile")]
+    pub target_strength: f32,
+
+    /// Nudge each creature's mu_g every step to hold its spread at SPREAD
+    #[arg(long, value_name = "SPREAD")]
+    pub target_spread: Option<f32>,
+
+    /// Change in mu_g per step per unit of relative spread error under --target-spread
+    #[arg(long, default_value_t = 0.01, requires = "target_spread")]
+    pub size_gain: f32,
+
+    /// Pull every creature as a whole toward the others' centroids with this
+    /// strength, or push it away if negative
+    #[arg(long, value_name = "STRENGTH", allow_negative_numbers = true)]
+    pub centroid_force: Option<f32>,
+
+    /// How --centroid-force weakens with the distance between centroids
+    #[arg(long, value_enum, default_value_t, requires = "centroid_force")]
+    pub centroid_falloff: CentroidFalloff,
+
+    /// Length scale of --centroid-falloff: the range of linear, the softening
+    /// of inverse-square and the width of gaussian
+    #[arg(long, value_name = "DISTANCE", default_value_t = 30.0, requires = "centroid_force")]
+    pub centroid_range: f32,
+
+    /// Initial velocity given to every particle of the spawned creatures
+    #[arg(long, num_args = 2, value_names = ["VX", "VY"], allow_negative_numbers = true)]
+    pub initial_velocity: Option<Vec<f32>>,
+
+    /// Spawn each scene creature as a disk of particles of --particle-mass
+    /// each, as many as make up this total mass. Mass only sizes the drawn
+    /// particles and fills the export's mass column: every particle still
+    /// counts once in the fields, so a lighter --particle-mass makes a denser
+    /// creature with a larger U rather than the same one more finely divided
+    #[arg(long, value_name = "MASS", conflicts_with = "lattice")]
+    pub total_mass: Option<f32>,
+
+    /// Mass of each particle of a --total-mass creature, which doesn't weight
+    /// its part in the fields
+    #[arg(long, value_name = "MASS", default_value_t = 1.0, requires = "total_mass")]
+    pub particle_mass: f32,
+
+    /// Remove the least dense particles when spawning would exceed this many in total
+    #[arg(long)]
+    pub particle_budget: Option<usize>,
+
+    /// Pull of each attractor right-click places in the window, or its push
+    /// if negative
+    #[arg(long, value_name = "STRENGTH", default_value_t = 0.5, allow_negative_numbers = true, conflicts_with = "headless")]
+    pub attractor_strength: f32,
+
+    /// Distance at which an attractor's linear falloff reaches zero
+    #[arg(long, value_name = "DISTANCE", default_value_t = 20.0, conflicts_with = "headless")]
+    pub attractor_range: f32,
+
+    /// Softening S of an attractor's inverse-square falloff, 1 / (r^2 + S^2)
+    #[arg(long, value_name = "DISTANCE", default_value_t = 2.0, conflicts_with = "headless")]
+    pub attractor_softening: f32,
+
+    /// Width of an attractor's gaussian falloff
+    #[arg(long, value_name = "DISTANCE", default_value_t = 8.0, conflicts_with = "headless")]
+    pub attractor_sigma: f32,
+
+    /// Place an emitter here that keeps injecting particles into a creature of
+    /// its own. In the window Shift-right-click places more
+    #[arg(long, num_args = 2, value_names = ["X", "Y"], allow_negative_numbers = true)]
+    pub emitter: Option<Vec<f32>>,
+
+    /// Particles an emitter injects per unit of simulation time
+    #[arg(long, value_name = "RATE", default_value_t = 5.0)]
+    pub emitter_rate: f32,
+
+    /// Velocity emitted particles start with, kept under --momentum
+    #[arg(long, num_args = 2, value_names = ["VX", "VY"], allow_negative_numbers = true, default_values_t = [0.0, 0.0])]
+    pub emitter_velocity: Vec<f32>,
+
+    /// Particles an emitter's creature can have before the emitter pauses
+    #[arg(long, value_name = "N", default_value_t = 200)]
+    pub emitter_max: usize,
+
+    /// Bud a new particle next to any whose U exceeds BIRTH, and remove those
+    /// whose U stays below DEATH, holding the total at --particle-budget or
+    /// the starting particle count
+    #[arg(long, num_args = 2, value_names = ["BIRTH", "DEATH"])]
+    pub lifecycle: Option<Vec<f32>>,
+
+    /// Consecutive steps below the death threshold before a particle is removed
+    #[arg(long, default_value_t = 30, requires = "lifecycle")]
+    pub starve_steps: u32,
+
+    /// Replace each particle with a new one near its creature's centroid once
+    /// it's this many steps old, keeping the count steady
+    #[arg(long, value_name = "STEPS", value_parser = clap::value_parser!(u32).range(1..), conflicts_with = "background_sim")]
+    pub max_lifespan: Option<u32>,
+
+    /// Mesh used to draw each particle
+    #[arg(long, value_enum, default_value_t, requires_if("sprite", "particle_texture"))]
+    pub particle_shape: ParticleShape,
+
+    /// Antialiasing, circle smoothness and streamline refresh rate, cycled with Q
+    #[arg(long, value_enum, default_value_t)]
+    pub quality: RenderQuality,
+
+    /// Field deciding which overlapping particles are drawn on top
+    #[arg(long, value_enum, default_value_t)]
+    pub z_order: ZOrderMode,
+
+    /// Longest edge drawn between neighboring particles when E shows them
+    /// [default: each creature's kernel radius, mu_k + 2 sigma_k]
+    #[arg(long, conflicts_with = "headless")]
+    pub edge_distance: Option<f32>,
+
+    /// Outline drawn around each creature, cycled with B
+    #[arg(long, value_enum, default_value_t, conflicts_with = "headless")]
+    pub outline: OutlineMode,
+
+    /// Radius of the circle rolled around a creature for --outline alpha;
+    /// smaller values follow concave edges more closely
+    #[arg(long, default_value_t = 1.5, conflicts_with = "headless")]
+    pub outline_alpha: f32,
+
+    /// Frames between recomputing the outlines
+    #[arg(long, value_name = "K", default_value_t = 1, conflicts_with = "headless")]
+    pub outline_every: u32,
+
+    /// Start out drawing a faint ring around each particle at its kernel's
+    /// reach, toggled with 0
+    #[arg(long, conflicts_with = "headless")]
+    pub halos: bool,
+
+    /// Kernel widths past mu_k the --halos rings are drawn at
+    #[arg(long, value_name = "K", default_value_t = 2.0)]
+    pub halo_sigmas: f32,
+
+    /// Opacity of the --halos rings
+    #[arg(long, value_name = "ALPHA", default_value_t = 0.08)]
+    pub halo_alpha: f32,
+
+    /// Ghost snapshots of the particles, captured with F4, kept at once
+    #[arg(long, value_name = "N", default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..))]
+    pub ghost_count: u32,
+
+    /// Opacity of the newest ghost snapshot; older ones fade from it
+    #[arg(long, value_name = "ALPHA", default_value_t = 0.3)]
+    pub ghost_alpha: f32,
+
+    /// Start out drawing a motion-blur streak behind moving particles,
+    /// toggled with Z
+    #[arg(long, conflicts_with = "headless")]
+    pub motion_blur: bool,
+
+    /// Steps of travel each motion-blur streak covers
+    #[arg(long, value_name = "STEPS", default_value_t = 8.0, conflicts_with = "headless")]
+    pub motion_blur_intensity: f32,
+
+    /// Points the fields are sampled at along the probe line, toggled with F5
+    #[arg(long, value_name = "N", default_value_t = 200, value_parser = clap::value_parser!(u32).range(2..))]
+    pub probe_line_samples: u32,
+
+    /// Zoom and move the camera at startup so every spawned particle is in
+    /// view, instead of starting at the scene's fixed zoom
+    #[arg(long, conflicts_with_all = ["headless", "compare"])]
+    pub fit_camera: bool,
+
+    /// Fraction of the view's width and height --fit-camera leaves clear on
+    /// each side
+    #[arg(long, value_name = "FRACTION", default_value_t = 0.1, requires = "fit_camera")]
+    pub fit_margin: f32,
+
+    /// Start out showing a minimap of every creature's centroid in the
+    /// bottom left corner, toggled with Y; clicking it moves the view there
+    #[arg(long, conflicts_with_all = ["headless", "compare"])]
+    pub minimap: bool,
+
+    /// Draw each creature as a single disk, as wide as its spread and colored
+    /// by the mean of the color mode's field, instead of particle by particle
+    /// while zoomed out past this projection scale. The simulation is
+    /// unaffected
+    #[arg(long, value_name = "SCALE", conflicts_with = "headless")]
+    pub lod_scale: Option<f32>,
+
+    /// F10 holds every other creature still and steps the selected ones until
+    /// the largest |E_grad| among their particles is below this
+    #[arg(long, value_name = "TOLERANCE", default_value_t = 1e-3, conflicts_with_all = ["headless", "background_sim", "play_bin"])]
+    pub relax_tolerance: f32,
+
+    /// Steps F10 gives up relaxing after if it hasn't converged
+    #[arg(long, value_name = "STEPS", default_value_t = 5000, conflicts_with_all = ["headless", "background_sim", "play_bin"])]
+    pub relax_max_steps: u64,
+
+    /// Corner the cursor's world coordinates are shown in. A toggles
+    /// measuring, where two clicks show the distance between them
+    #[arg(long, value_enum, value_name = "CORNER", default_value_t, conflicts_with = "headless")]
+    pub coordinate_readout: ReadoutCorner,
+
+    /// Decimal places in the coordinate readout and measurements
+    #[arg(long, value_name = "DIGITS", default_value_t = 3)]
+    pub readout_precision: usize,
+
+    /// Start out showing the inspected particle's force by neighbor
+    /// distance, toggled with X
+    #[arg(long, conflicts_with_all = ["headless", "barnes_hut"])]
+    pub force_bands: bool,
+
+    /// Width of each distance band in the force chart
+    #[arg(long, value_name = "R", default_value_t = 1.0, conflicts_with = "headless")]
+    pub force_band_width: f32,
+
+    /// Distance bands in the force chart, the last taking every farther neighbor
+    #[arg(long, value_name = "N", default_value_t = 8, value_parser = clap::value_parser!(u32).range(1..), conflicts_with = "headless")]
+    pub force_band_count: u32,
+
+    /// Start out drawing a grid of arrows showing the flow field over the
+    /// view, toggled with F2
+    #[arg(long, conflicts_with = "headless")]
+    pub flow_arrows: bool,
+
+    /// Arrows along the longer side of the view in the flow arrow grid
+    #[arg(long, value_name = "N", default_value_t = 32, conflicts_with = "headless")]
+    pub flow_arrows_resolution: u32,
+
+    /// Frames between samples of the flow arrow grid
+    #[arg(long, value_name = "K", default_value_t = 5, conflicts_with = "headless")]
+    pub flow_arrows_every: u32,
+
+    /// Start out showing the U-field heatmap behind the particles, toggled with U
+    #[arg(long, conflicts_with = "headless")]
+    pub heatmap: bool,
+
+    /// Texels along the longer side of the view in the heatmap
+    #[arg(long, value_name = "TEXELS", default_value_t = 128, conflicts_with = "headless")]
+    pub heatmap_resolution: u32,
+
+    /// Frames between heatmap samples, each taken off the main thread
+    #[arg(long, value_name = "K", default_value_t = 10, conflicts_with = "headless")]
+    pub heatmap_every: u32,
+
+    /// Field particles are colored by
+    #[arg(long, value_enum, default_value_t)]
+    pub color_mode: ColorMode,
+
+    /// Distance within which --color-mode by-neighbor-count counts a particle
+    /// of the same creature as a neighbor [default: mu_k + 2 sigma_k]
+    #[arg(long, value_name = "R", conflicts_with = "headless")]
+    pub neighbor_radius: Option<f32>,
+
+    /// Colormap used by --color-mode: viridis, magma, turbo or one from --colormaps
+    #[arg(long, value_name = "NAME")]
+    pub colormap: Option<String>,
+
+    /// JSON file of extra named colormaps, each a list of [position, "#rrggbb"] stops
+    #[arg(long, value_name = "FILE")]
+    pub colormaps: Option<PathBuf>,
+
+    /// Draw particles at their raw radius each frame instead of easing toward it
+    #[arg(long)]
+    pub raw_radius: bool,
+
+    /// Never draw a particle smaller than this radius, in logical pixels,
+    /// however small its size mode makes it
+    #[arg(long, value_name = "PIXELS", default_value_t = 1.5)]
+    pub min_render_radius: f32,
+
+    /// Start out showing the parameter map, toggled with F1
+    #[arg(long, conflicts_with = "headless")]
+    pub param_map: bool,
+
+    /// Parameters along the parameter map's x and y axes
+    #[arg(long, value_enum, num_args = 2, value_names = ["X", "Y"], default_values_t = [ParameterAxis::MuG, ParameterAxis::SigmaG], conflicts_with = "headless")]
+    pub param_map_axes: Vec<ParameterAxis>,
+
+    /// Ranges the parameter map's axes span [default: around each parameter's
+    /// default]
+    #[arg(long, num_args = 4, value_names = ["X_MIN", "X_MAX", "Y_MIN", "Y_MAX"], allow_negative_numbers = true, conflicts_with = "headless")]
+    pub param_map_range: Option<Vec<f32>>,
+
+    /// CSV of a sweep's classes to color the parameter map by, with a column
+    /// named after each axis's parameter, such as mu_g, and a class column
+    #[arg(long, value_name = "FILE", conflicts_with = "headless")]
+    pub param_map_classes: Option<PathBuf>,
+
+    /// Image under assets/ used for sprite particles
+    #[arg(long)]
+    pub particle_texture: Option<String>,
+
+    /// Record keyboard, mouse and cursor input to this file as JSON lines
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["headless", "replay"])]
+    pub record: Option<PathBuf>,
+
+    /// Image shown behind the simulation to compare creatures to, under
+    /// assets/ unless the path is absolute
+    #[arg(long, value_name = "IMAGE", conflicts_with = "headless")]
+    pub reference: Option<String>,
+
+    /// World position the --reference image is centered on
+    #[arg(long, num_args = 2, value_names = ["X", "Y"], default_values_t = [0.0, 0.0], allow_negative_numbers = true, requires = "reference")]
+    pub reference_center: Vec<f32>,
+
+    /// Width of the --reference image in world units, its height following
+    /// from its aspect ratio
+    #[arg(long, value_name = "WIDTH", default_value_t = 20.0, requires = "reference")]
+    pub reference_width: f32,
+
+    /// Opacity of the --reference image, from 0 to 1
+    #[arg(long, value_name = "ALPHA", default_value_t = 0.5, requires = "reference")]
+    pub reference_opacity: f32,
+
+    /// Record the window to this file as a looping GIF, from startup until
+    /// --gif-frames frames are in
+    #[arg(long, value_name = "FILE", conflicts_with = "headless")]
+    pub record_gif: Option<PathBuf>,
+
+    /// Frames in the --record-gif loop
+    #[arg(long, value_name = "N", default_value_t = 90, value_parser = clap::value_parser!(u32).range(1..), requires = "record_gif")]
+    pub gif_frames: u32,
+
+    /// Frames a second captured for --record-gif, and played back
+    #[arg(long, value_name = "FPS", default_value_t = 15.0, requires = "record_gif")]
+    pub gif_fps: f32,
+
+    /// Shrink each --record-gif frame by this factor on both sides
+    #[arg(long, value_name = "K", default_value_t = 2, value_parser = clap::value_parser!(u32).range(1..), requires = "record_gif")]
+    pub gif_downscale: u32,
+
+    /// Play back input recorded with --record; use the same --seed and --demo
+    #[arg(long, value_name = "FILE", conflicts_with = "headless")]
+    pub replay: Option<PathBuf>,
+
+    /// Total worker threads for Bevy's task pools [default: one per core]
+    ///
+    /// The systems run on the compute pool, so this caps how many of them run
+    /// in parallel. Headless runs use the same pools and the same limit. With
+    /// several headless sweeps on one machine, keep the sum of their limits at or
+    /// below the core count.
+    #[arg(long)]
+    pub threads: Option<NonZeroUsize>,
+
+    /// Run every system one at a time on the main thread, in one fixed order
+    ///
+    /// By default Bevy runs systems that don't conflict in parallel, and
+    /// systems that conflict but aren't explicitly ordered, such as two forces
+    /// adding into the same gradient, in whichever order it gets to them, so
+    /// floating-point sums can differ in their last bits from run to run. This
+    /// makes runs with the same options bit-for-bit reproducible, at the cost
+    /// of that parallelism. The pair sums, most of a step, run on one thread
+    /// either way, so what's lost is the overlap of everything else: little
+    /// on a few cores, more with many plugins active on many cores
+    #[arg(long)]
+    pub deterministic: bool,
+
+    /// Write every particle's final position to this file as JSON
+    #[arg(long, value_name = "FILE", requires = "headless", conflicts_with = "check_snapshot")]
+    pub write_snapshot: Option<PathBuf>,
+
+    /// Exit with an error unless the final positions match a --write-snapshot file
+    #[arg(long, value_name = "FILE", requires = "headless")]
+    pub check_snapshot: Option<PathBuf>,
+
+    /// Largest distance a particle may be from its stored position for --check-snapshot
+    #[arg(long, default_value_t = 1e-3, requires = "check_snapshot")]
+    pub snapshot_tolerance: f32,
+
+    /// Start from step 0 of a JSON file of {"dt", "steps"} exported from a
+    /// reference implementation, "steps" holding every particle's [x, y] at
+    /// each step, and exit with an error unless each step's positions stay
+    /// within --trajectory-tolerance of it. The parameters are this run's, so
+    /// give the ones it was made with; --deterministic rules out the last bits
+    /// of the sums changing from run to run
+    #[arg(long, value_name = "FILE", requires = "headless", conflicts_with_all = ["demo", "stress", "compare", "load_state", "resume", "template", "lattice", "symmetry", "density", "total_mass", "settle_steps"])]
+    pub check_trajectory: Option<PathBuf>,
+
+    /// Largest distance a particle may be from the reference for --check-trajectory
+    #[arg(long, default_value_t = 1e-3, requires = "check_trajectory")]
+    pub trajectory_tolerance: f32,
+
+    /// Start from a state.ron written by the P export instead of a fresh scene
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["demo", "stress", "preset", "morph_to"])]
+    pub load_state: Option<PathBuf>,
+
+    /// Spawn a creature from a template written by Shift-P, in place of the
+    /// single random creature or alongside a --demo's creatures
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["load_state", "resume", "compare"])]
+    pub template: Option<PathBuf>,
+
+    /// World position the --template creature's centroid is spawned at
+    #[arg(long, num_args = 2, value_names = ["X", "Y"], default_values_t = [0.0, 0.0], allow_negative_numbers = true, requires = "template")]
+    pub template_at: Vec<f32>,
+
+    /// Compute the fields of a loaded or resumed state once it's spawned,
+    /// rather than leaving them for the first step
+    #[arg(long, conflicts_with = "background_sim")]
+    pub recompute_fields_on_load: bool,
+
+    /// Continue a run from a checkpoint, or from the newest one in a
+    /// directory of them, at its step and with its RNG
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["demo", "stress", "preset", "morph_to", "load_state", "compare"])]
+    pub resume: Option<PathBuf>,
+
+    /// Save the state to --checkpoint-dir every this many steps
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    pub checkpoint_interval: Option<u64>,
+
+    /// Directory checkpoints are written to
+    #[arg(long, value_name = "DIR", default_value = "checkpoints", requires = "checkpoint_interval")]
+    pub checkpoint_dir: PathBuf,
+
+    /// Checkpoints kept, the oldest being removed as new ones are written
+    #[arg(long, value_name = "K", default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..), requires = "checkpoint_interval")]
+    pub checkpoint_keep: u32,
+
+    /// Directory P and V export the current frame to
+    #[arg(long, value_name = "DIR", default_value = "exports", conflicts_with = "headless")]
+    pub export_dir: PathBuf,
+
+    /// Name of each P export directory and V export file, from {step}, {seed},
+    /// {timestamp} and {creature}. Paths given to --record, --trajectory-csv
+    /// and --write-snapshot may use the same placeholders
+    #[arg(long, value_name = "TEMPLATE", default_value = "step-{step}", conflicts_with = "headless")]
+    pub name_template: String,
+
+    /// Step the simulation on its own thread and draw the latest finished
+    /// step, so a slow step doesn't hold up the window
+    #[arg(long, conflicts_with_all = ["headless", "record", "replay", "lifecycle", "fit_bounds"])]
+    pub background_sim: bool,
+
+    /// Run the simulation without a window
+    #[arg(long)]
+    pub headless: bool,
+
+    /// Number of steps to run in headless mode
+    #[arg(long, default_value_t = 1000)]
+    pub steps: u64,
+
+    /// Take at most this many steps per second of wall-clock time, sleeping
+    /// out the rest of any frame that finishes early, e.g. so a recording's
+    /// motion plays at a known pace. Unlike a creature's time scale, dt is
+    /// unchanged
+    #[arg(long, value_name = "HZ")]
+    pub realtime_cap: Option<f32>,
+
+    /// Log the minimum pair distance and maximum force every step
+    #[arg(long)]
+    pub log_diagnostics: bool,
+
+    /// Log the time taken by each of the main simulation systems
+    #[arg(long)]
+    pub log_timings: bool,
+
+    /// Log filter directives used unless RUST_LOG is set, e.g.
+    /// particle_lenia=debug for creature events or particle_lenia=trace for a
+    /// summary of every step [default: info,wgpu=error,naga=warn]
+    #[arg(long, value_name = "DIRECTIVES")]
+    pub log_filter: Option<String>,
+
+    /// Stream per-creature statistics to stdout as JSON lines
+    #[arg(long, requires = "headless")]
+    pub metrics_jsonl: bool,
+
+    /// Emit a metrics line every this many steps
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+    pub metrics_every: u64,
+
+    /// Add each creature's radius of gyration, aspect ratio and asphericity
+    /// to the metrics lines
+    #[arg(long, requires = "metrics_jsonl")]
+    pub shape_metrics: bool,
+
+    /// Keep a live summary of each creature on the terminal, on stderr
+    #[arg(long, requires = "headless")]
+    pub tui: bool,
+
+    /// Instead of running, search this many single-creature candidates,
+    /// perturbed at random from the first creature of the scene, for ones
+    /// that move steadily without breaking up, and write the best out
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub find_gliders: Option<u32>,
+
+    /// Steps each candidate settles for before its motion is measured, at
+    /// least one so there's a settled spread to catch explosions against
+    #[arg(long, value_name = "STEPS", default_value_t = 300, value_parser = clap::value_parser!(u64).range(1..), requires = "find_gliders")]
+    pub glider_settle: u64,
+
+    /// Steps each candidate's motion is measured over
+    #[arg(long, value_name = "STEPS", default_value_t = 600, value_parser = clap::value_parser!(u64).range(1..), requires = "find_gliders")]
+    pub glider_steps: u64,
+
+    /// Best candidates printed and written to --glider-dir
+    #[arg(long, value_name = "N", default_value_t = 3, requires = "find_gliders")]
+    pub glider_top: usize,
+
+    /// Directory the best candidates' parameters and trajectory plots go in
+    #[arg(long, value_name = "DIR", default_value = "gliders", requires = "find_gliders")]
+    pub glider_dir: PathBuf,
+
+    /// Write particle positions to this file as CSV
+    #[arg(long, value_name = "FILE")]
+    pub trajectory_csv: Option<PathBuf>,
+
+    /// Write positions to --trajectory-csv every this many steps
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..), requires = "trajectory_csv")]
+    pub log_step_stride: u64,
+
+    /// Fraction of the particles written to --trajectory-csv, picked from the seed
+    #[arg(long, default_value_t = 1.0, requires = "trajectory_csv")]
+    pub log_particle_fraction: f32,
+
+    /// Record every particle's position at every step to this file in a
+    /// compact binary format, for --play-bin
+    #[arg(long, value_name = "FILE")]
+    pub record_bin: Option<PathBuf>,
+
+    /// Play back a --record-bin recording in the window instead of simulating;
+    /// use the same --demo, --stress or --load-state. The seed is the
+    /// recording's unless --seed is given
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["headless", "background_sim", "record_bin", "resume"])]
+    pub play_bin: Option<PathBuf>,
+
+    /// Record the radial distribution g(r) of each creature's pairs from the
+    /// start and write it to this file as CSV on exit. In the window, D
+    /// toggles recording and its plot, and Shift-D writes it out
+    #[arg(long, value_name = "FILE", conflicts_with = "barnes_hut")]
+    pub rdf_csv: Option<PathBuf>,
+
+    /// Largest pair distance in g(r)
+    #[arg(long, value_name = "R", default_value_t = 10.0)]
+    pub rdf_max: f32,
+
+    /// Bins of g(r) out to --rdf-max
+    #[arg(long, value_name = "N", default_value_t = 100, value_parser = clap::value_parser!(u32).range(1..))]
+    pub rdf_bins: u32,
+
+    /// Histogram the distances of close encounters, pairs inside the
+    /// repulsion radius, from the start and write it to this file as CSV on
+    /// exit. In the window, F3 resets the counts and Shift-F3 writes them out.
+    /// They're counted in the CPU pair sums, so not with --gpu
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["background_sim", "play_bin"])]
+    pub encounters_csv: Option<PathBuf>,
+
+    /// Bins of the close encounter histogram over distances 0 .. 1
+    #[arg(long, value_name = "N", default_value_t = 20, value_parser = clap::value_parser!(u32).range(1..))]
+    pub encounter_bins: u32,
+}
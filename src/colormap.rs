@@ -0,0 +1,203 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use bevy::prelude::*;
+use clap::ValueEnum;
+
+use crate::{DirectPairSums, Fields, GrowthOverride, NeighborCount, Particle};
+
+/// Maps a value in `[0, 1]` to a color by interpolating between stops.
+#[derive(Clone, Debug)]
+pub struct Colormap {
+    pub name: String,
+    /// Positions in `[0, 1]`, ascending, with their colors
+    pub stops: Vec<(f32, Color)>,
+}
+
+impl Colormap {
+    fn from_hex(name: &str, stops: &[&str]) -> Self {
+        let last = (stops.len() - 1).max(1) as f32;
+        Colormap {
+            name: name.to_string(),
+            stops: stops.iter().enumerate().map(|(i, hex)| (i as f32 / last, Color::hex(hex).unwrap())).collect(),
+        }
+    }
+
+    pub fn sample(&self, t: f32) -> Color {
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+        let Some(upper) = self.stops.iter().position(|(position, _)| *position >= t) else {
+            return self.stops.last().map_or(Color::WHITE, |(_, color)| *color);
+        };
+        if upper == 0 {
+            return self.stops[0].1;
+        }
+
+        let (a, color_a) = self.stops[upper - 1];
+        let (b, color_b) = self.stops[upper];
+        let s = if b > a { (t - a) / (b - a) } else { 1.0 };
+        let [r_a, g_a, b_a, alpha_a] = color_a.as_rgba_f32();
+        let [r_b, g_b, b_b, alpha_b] = color_b.as_rgba_f32();
+        Color::rgba(r_a + s * (r_b - r_a), g_a + s * (g_b - g_a), b_a + s * (b_b - b_a), alpha_a + s * (alpha_b - alpha_a))
+    }
+}
+
+/// Every available colormap, the built-in ones first, and the one in use.
+#[derive(Resource, Clone)]
+pub struct Colormaps {
+    pub maps: Vec<Colormap>,
+    pub selected: usize,
+}
+
+impl Default for Colormaps {
+    fn default() -> Self {
+        // Evenly spaced samples of matplotlib's viridis and magma and Google's turbo.
+        Colormaps {
+            maps: vec![
+                Colormap::from_hex("viridis", &["440154", "482878", "3e4989", "31688e", "26828e", "1f9e89", "35b779", "6ece58", "b5de2b", "fde725"]),
+                Colormap::from_hex("magma", &["000004", "180f3d", "440f76", "721f81", "9e2f7f", "cd4071", "f1605d", "fd9668", "feca8d", "fcfdbf"]),
+                Colormap::from_hex("turbo", &["30123b", "4662d7", "36aaf9", "1ae4b6", "72fe5e", "c8ef34", "faba39", "f66b19", "ca2a04", "7a0403"]),
+            ],
+            selected: 0,
+        }
+    }
+}
+
+impl Colormaps {
+    /// Adds the maps in a JSON file of named stop lists, e.g.
+    /// `{"ice": [[0.0, "#000000"], [1.0, "#a0e0ff"]]}`. A map named like a
+    /// built-in one replaces it.
+    pub fn load(&mut self, path: &Path) -> Result<(), String> {
+        let json = fs::read_to_string(path).map_err(|err| err.to_string())?;
+        let maps: BTreeMap<String, Vec<(f32, String)>> = serde_json::from_str(&json).map_err(|err| err.to_string())?;
+
+        for (name, stops) in maps {
+            let mut stops = stops.into_iter()
+                .map(|(position, hex)| Color::hex(&hex).map(|color| (position, color)).map_err(|err| format!("{}: {}", name, err)))
+                .collect::<Result<Vec<_>, _>>()?;
+            if stops.is_empty() {
+                return Err(format!("{}: no stops", name));
+            }
+            stops.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+            let map = Colormap { name, stops };
+            match self.maps.iter_mut().find(|existing| existing.name == map.name) {
+                Some(existing) => *existing = map,
+                None => self.maps.push(map),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn select(&mut self, name: &str) -> Result<(), String> {
+        self.selected = self.maps.iter().position(|map| map.name == name).ok_or_else(|| {
+            let names: Vec<_> = self.maps.iter().map(|map| map.name.as_str()).collect();
+            format!("no colormap named {}, expected one of {}", name, names.join(", "))
+        })?;
+        Ok(())
+    }
+
+    pub fn current(&self) -> &Colormap {
+        &self.maps[self.selected]
+    }
+}
+
+/// Field particles are colored by, normalized to its range over all particles.
+#[derive(ValueEnum, Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorMode {
+    /// Plain white particles
+    #[default]
+    Off,
+    /// Color by density R_val
+    RVal,
+    /// Color by growth potential U_val
+    UVal,
+    /// Color by how many particles of the same creature are within
+    /// --neighbor-radius, lowest on the surface. Not counted with
+    /// --barnes-hut, --gpu or --background-sim
+    ByNeighborCount,
+}
+
+impl ColorMode {
+    pub fn value(self, fields: &Fields, neighbors: Option<&NeighborCount>) -> Option<f32> {
+        match self {
+            ColorMode::Off => None,
+            ColorMode::RVal => Some(fields.R_val),
+            ColorMode::UVal => Some(fields.U_val),
+            ColorMode::ByNeighborCount => neighbors.map(|neighbors| neighbors.0 as f32),
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            ColorMode::Off => ColorMode::RVal,
+            ColorMode::RVal => ColorMode::UVal,
+            ColorMode::UVal => ColorMode::ByNeighborCount,
+            ColorMode::ByNeighborCount => ColorMode::Off,
+        }
+    }
+}
+
+pub struct ColormapPlugin {
+    pub mode: ColorMode,
+    pub colormaps: Colormaps,
+}
+
+impl Plugin for ColormapPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.mode)
+            .insert_resource(self.colormaps.clone())
+            .add_systems(Update, (cycle_colors, color_particles).chain());
+    }
+}
+
+// O cycles the color mode, K the colormap.
+fn cycle_colors(
+    keys: Res<Input<KeyCode>>,
+    direct: Res<DirectPairSums>,
+    mut mode: ResMut<ColorMode>,
+    mut colormaps: ResMut<Colormaps>,
+) {
+    if keys.just_pressed(KeyCode::O) {
+        *mode = mode.next();
+        info!("color mode: {:?}", *mode);
+        if *mode == ColorMode::ByNeighborCount {
+            direct.warn_unless("the neighbor count");
+        }
+    }
+    if keys.just_pressed(KeyCode::K) {
+        colormaps.selected = (colormaps.selected + 1) % colormaps.maps.len();
+        info!("colormap: {}", colormaps.current().name);
+    }
+}
+
+// Painted particles keep the brush's tint.
+#[allow(clippy::type_complexity)]
+fn color_particles(
+    mode: Res<ColorMode>,
+    colormaps: Res<Colormaps>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    particle_query: Query<(&Fields, Option<&NeighborCount>, &Handle<ColorMaterial>), (With<Particle>, Without<GrowthOverride>)>,
+) {
+    if mode.is_changed() && *mode == ColorMode::Off {
+        for (.., handle) in particle_query.iter() {
+            if let Some(material) = materials.get_mut(handle) {
+                material.color = Color::WHITE;
+            }
+        }
+    }
+    if *mode == ColorMode::Off {
+        return;
+    }
+
+    let (min, max) = particle_query.iter()
+        .filter_map(|(fields, neighbors, _)| mode.value(fields, neighbors))
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), value| (min.min(value), max.max(value)));
+    let range = (max - min).max(f32::EPSILON);
+
+    let colormap = colormaps.current();
+    for (fields, neighbors, handle) in particle_query.iter() {
+        let (Some(value), Some(material)) = (mode.value(fields, neighbors), materials.get_mut(handle)) else {
+            continue;
+        };
+        material.color = colormap.sample((value - min) / range);
+    }
+}
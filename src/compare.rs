@@ -0,0 +1,60 @@
+use bevy::{
+    core_pipeline::clear_color::ClearColorConfig,
+    prelude::*,
+    render::camera::Viewport,
+    window::{PrimaryWindow, WindowResized},
+};
+
+use crate::scene::{Scene, COMPARE_OFFSET};
+
+/// Splits the window in two, each half with its own camera following one of
+/// the two `--compare` creatures.
+pub struct ComparePlugin;
+
+// Which half of the window a camera draws to, 0 for the left.
+#[derive(Component)]
+struct CompareView(u32);
+
+impl Plugin for ComparePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_compare_cameras)
+            .add_systems(Update, fit_viewports);
+    }
+}
+
+fn spawn_compare_cameras(mut commands: Commands, scene: Res<Scene>) {
+    for (half, x) in [(0, -COMPARE_OFFSET), (1, COMPARE_OFFSET)] {
+        let mut camera = Camera2dBundle::default();
+        camera.projection.scale = scene.camera_scale;
+        camera.transform.translation.x = x;
+        camera.camera.order = half as isize;
+        // Clearing would wipe the whole window, including the other half.
+        if half > 0 {
+            camera.camera_2d.clear_color = ClearColorConfig::None;
+        }
+        commands.spawn((camera, CompareView(half)));
+    }
+}
+
+fn fit_viewports(
+    mut resized: EventReader<WindowResized>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut camera_query: Query<(&mut Camera, &CompareView)>,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let unset = camera_query.iter().any(|(camera, _)| camera.viewport.is_none());
+    if resized.iter().last().is_none() && !unset {
+        return;
+    }
+
+    let half_width = window.physical_width() / 2;
+    for (mut camera, view) in camera_query.iter_mut() {
+        camera.viewport = Some(Viewport {
+            physical_position: UVec2::new(view.0 * half_width, 0),
+            physical_size: UVec2::new(half_width.max(1), window.physical_height().max(1)),
+            ..default()
+        });
+    }
+}
@@ -0,0 +1,84 @@
+use bevy::prelude::*;
+
+use crate::{probe::creature_positions, Creature, Parameters, Particle};
+
+// Change in the edge distance threshold per key press.
+const THRESHOLD_STEP: f32 = 0.5;
+
+/// Edges drawn between particles of the same creature that lie within
+/// `max_distance` of each other, or when it's unset, within the kernel's
+/// effective radius mu_k + 2 sigma_k.
+#[derive(Resource)]
+pub struct Connectivity {
+    pub enabled: bool,
+    pub max_distance: Option<f32>,
+}
+
+pub struct ConnectivityPlugin {
+    pub max_distance: Option<f32>,
+}
+
+impl Plugin for ConnectivityPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Connectivity {
+                enabled: false,
+                max_distance: self.max_distance,
+            })
+            .add_systems(Update, (adjust_connectivity, draw_connectivity).chain());
+    }
+}
+
+// E toggles the edges, comma and period shrink and grow the distance threshold.
+fn adjust_connectivity(
+    keys: Res<Input<KeyCode>>,
+    mut connectivity: ResMut<Connectivity>,
+    parameters_query: Query<&Parameters, With<Creature>>,
+) {
+    if keys.just_pressed(KeyCode::E) {
+        connectivity.enabled = !connectivity.enabled;
+    }
+
+    let step = if keys.just_pressed(KeyCode::Comma) {
+        -THRESHOLD_STEP
+    } else if keys.just_pressed(KeyCode::Period) {
+        THRESHOLD_STEP
+    } else {
+        return;
+    };
+    // Start from the widest kernel radius when no threshold was set yet.
+    let current = connectivity.max_distance.unwrap_or_else(|| {
+        parameters_query.iter().map(effective_radius).fold(0.0, f32::max)
+    });
+    connectivity.max_distance = Some((current + step).max(THRESHOLD_STEP));
+    info!("edge distance threshold: {:?}", connectivity.max_distance);
+}
+
+fn effective_radius(parameters: &Parameters) -> f32 {
+    parameters.mu_k + 2.0 * parameters.sigma_k
+}
+
+fn draw_connectivity(
+    mut gizmos: Gizmos,
+    connectivity: Res<Connectivity>,
+    creature_query: Query<(&Transform, &Parameters, &Children), With<Creature>>,
+    particle_query: Query<&Transform, With<Particle>>,
+) {
+    if !connectivity.enabled {
+        return;
+    }
+
+    for (parameters, positions) in creature_positions(&creature_query, &particle_query) {
+        let max_distance = connectivity.max_distance.unwrap_or_else(|| effective_radius(&parameters));
+        for (i, a) in positions.iter().enumerate() {
+            for b in positions[i + 1..].iter() {
+                let distance = a.distance(*b);
+                if distance > max_distance {
+                    continue;
+                }
+                // Shorter edges are brighter.
+                let alpha = 0.6 * (1.0 - distance / max_distance) + 0.05;
+                gizmos.line_2d(a.truncate(), b.truncate(), Color::rgba(1.0, 0.8, 0.3, alpha));
+            }
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::minimap::MinimapCamera;
+
+// Largest distance a particle's mesh is expected to extend past its centre.
+const CULL_MARGIN: f32 = 2.0;
+
+#[derive(SystemParam)]
+pub struct ViewBounds<'w, 's> {
+    camera_query: Query<'w, 's, (&'static Camera, &'static OrthographicProjection, &'static GlobalTransform), Without<MinimapCamera>>,
+}
+
+impl ViewBounds<'_, '_> {
+    /// World-space rectangles currently visible through each active camera,
+    /// grown by the cull margin.
+    pub fn rects(&self) -> Vec<Rect> {
+        self.view_rects().map(|rect| rect.inset(CULL_MARGIN)).collect()
+    }
+
+    /// The world-space rectangle exactly visible through the first active
+    /// camera, or the smallest one around its view if the camera is turned.
+    pub fn view_rect(&self) -> Option<Rect> {
+        self.view_rects().next()
+    }
+
+    fn view_rects(&self) -> impl Iterator<Item = Rect> + '_ {
+        self.camera_query.iter()
+            .filter(|(camera, _, _)| camera.is_active)
+            .map(|(_, projection, transform)| {
+                let area = projection.area;
+                let corners = [area.min, Vec2::new(area.max.x, area.min.y), area.max, Vec2::new(area.min.x, area.max.y)];
+                corners.into_iter()
+                    .map(|corner| transform.transform_point(corner.extend(0.0)).truncate())
+                    .fold(Rect { min: Vec2::splat(f32::INFINITY), max: Vec2::splat(f32::NEG_INFINITY) }, |rect, point| rect.union_point(point))
+            })
+    }
+}
+
+pub fn is_visible(rects: &[Rect], point: Vec2) -> bool {
+    rects.iter().any(|rect| rect.contains(point))
+}
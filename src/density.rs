@@ -0,0 +1,390 @@
+use std::{f32::consts::{E, PI, TAU}, fmt};
+
+use bevy::prelude::*;
+use rand::Rng;
+
+// Points per side of the grid the peak of a density is searched on before
+// sampling it.
+const PEAK_GRID: usize = 128;
+
+/// A density `f(x, y)` over a creature's spawn offsets, parsed from an
+/// expression such as `exp(-(r - 6)^2) * (1 + cos(theta))`.
+///
+/// Expressions combine numbers, `+ - * / ^`, parentheses, the variables `x`,
+/// `y`, `r` and `theta` (the polar angle from the x axis), the constants `pi`
+/// and `e`, the functions `sin cos tan asin acos atan sinh cosh tanh exp ln
+/// log10 sqrt abs floor ceil step`, where `step(v)` is 1 for positive `v` and
+/// 0 otherwise, and the two-argument `min max pow atan2`. Negative values
+/// count as zero.
+#[derive(Clone)]
+pub struct Density {
+    source: String,
+    expr: Expr,
+}
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Number(f32),
+    Variable(Variable),
+    Negate(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(Function, Vec<Expr>),
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Variable {
+    X,
+    Y,
+    R,
+    Theta,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum BinaryOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Power,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Function {
+    Sin,
+    Cos,
+    Tan,
+    Asin,
+    Acos,
+    Atan,
+    Sinh,
+    Cosh,
+    Tanh,
+    Exp,
+    Ln,
+    Log10,
+    Sqrt,
+    Abs,
+    Floor,
+    Ceil,
+    Step,
+    Min,
+    Max,
+    Pow,
+    Atan2,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Function> {
+        Some(match name {
+            "sin" => Function::Sin,
+            "cos" => Function::Cos,
+            "tan" => Function::Tan,
+            "asin" => Function::Asin,
+            "acos" => Function::Acos,
+            "atan" => Function::Atan,
+            "sinh" => Function::Sinh,
+            "cosh" => Function::Cosh,
+            "tanh" => Function::Tanh,
+            "exp" => Function::Exp,
+            "ln" => Function::Ln,
+            "log10" => Function::Log10,
+            "sqrt" => Function::Sqrt,
+            "abs" => Function::Abs,
+            "floor" => Function::Floor,
+            "ceil" => Function::Ceil,
+            "step" => Function::Step,
+            "min" => Function::Min,
+            "max" => Function::Max,
+            "pow" => Function::Pow,
+            "atan2" => Function::Atan2,
+            _ => return None,
+        })
+    }
+
+    fn arity(self) -> usize {
+        match self {
+            Function::Min | Function::Max | Function::Pow | Function::Atan2 => 2,
+            _ => 1,
+        }
+    }
+
+    fn apply(self, args: &[f32]) -> f32 {
+        let a = args[0];
+        match self {
+            Function::Sin => a.sin(),
+            Function::Cos => a.cos(),
+            Function::Tan => a.tan(),
+            Function::Asin => a.asin(),
+            Function::Acos => a.acos(),
+            Function::Atan => a.atan(),
+            Function::Sinh => a.sinh(),
+            Function::Cosh => a.cosh(),
+            Function::Tanh => a.tanh(),
+            Function::Exp => a.exp(),
+            Function::Ln => a.ln(),
+            Function::Log10 => a.log10(),
+            Function::Sqrt => a.sqrt(),
+            Function::Abs => a.abs(),
+            Function::Floor => a.floor(),
+            Function::Ceil => a.ceil(),
+            Function::Step => if a > 0.0 { 1.0 } else { 0.0 },
+            Function::Min => a.min(args[1]),
+            Function::Max => a.max(args[1]),
+            Function::Pow => a.powf(args[1]),
+            Function::Atan2 => a.atan2(args[1]),
+        }
+    }
+}
+
+impl Expr {
+    fn eval(&self, x: f32, y: f32) -> f32 {
+        match self {
+            Expr::Number(value) => *value,
+            Expr::Variable(Variable::X) => x,
+            Expr::Variable(Variable::Y) => y,
+            Expr::Variable(Variable::R) => x.hypot(y),
+            Expr::Variable(Variable::Theta) => y.atan2(x),
+            Expr::Negate(expr) => -expr.eval(x, y),
+            Expr::Binary(op, lhs, rhs) => {
+                let (lhs, rhs) = (lhs.eval(x, y), rhs.eval(x, y));
+                match op {
+                    BinaryOp::Add => lhs + rhs,
+                    BinaryOp::Subtract => lhs - rhs,
+                    BinaryOp::Multiply => lhs * rhs,
+                    BinaryOp::Divide => lhs / rhs,
+                    BinaryOp::Power => lhs.powf(rhs),
+                }
+            }
+            Expr::Call(function, args) => {
+                let args: Vec<f32> = args.iter().map(|arg| arg.eval(x, y)).collect();
+                function.apply(&args)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f32),
+    Ident(String),
+    Op(char),
+    Open,
+    Close,
+    Comma,
+}
+
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut end = start;
+            while let Some(&(i, c)) = chars.peek() {
+                // An exponent's sign belongs to the number, as in 1e-3.
+                let exponent_sign = (c == '-' || c == '+') && matches!(source[..i].chars().last(), Some('e' | 'E'));
+                if !(c.is_ascii_digit() || c == '.' || c == 'e' || c == 'E' || exponent_sign) {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            let text = &source[start..end];
+            let value = text.parse().map_err(|_| format!("bad number '{}' at {}", text, start))?;
+            tokens.push((start, Token::Number(value)));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut end = start;
+            while let Some(&(i, c)) = chars.peek() {
+                if !(c.is_alphanumeric() || c == '_') {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            tokens.push((start, Token::Ident(source[start..end].to_string())));
+        } else {
+            let token = match c {
+                '+' | '-' | '*' | '/' | '^' => Token::Op(c),
+                '(' => Token::Open,
+                ')' => Token::Close,
+                ',' => Token::Comma,
+                _ => return Err(format!("unexpected '{}' at {}", c, start)),
+            };
+            tokens.push((start, token));
+            chars.next();
+        }
+    }
+    Ok(tokens)
+}
+
+// Recursive descent over the usual precedence: sums of products of signed
+// powers, with `^` binding tighter than a leading minus and to the right.
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    next: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next).map(|(_, token)| token)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens.get(self.next).map_or(self.end, |(position, _)| *position)
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.next).map(|(_, token)| token.clone());
+        self.next += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token, what: &str) -> Result<(), String> {
+        let position = self.position();
+        match self.advance() {
+            Some(token) if token == expected => Ok(()),
+            _ => Err(format!("expected {} at {}", what, position)),
+        }
+    }
+
+    fn sum(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.product()?;
+        while let Some(Token::Op(op @ ('+' | '-'))) = self.peek() {
+            let op = if *op == '+' { BinaryOp::Add } else { BinaryOp::Subtract };
+            self.advance();
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.product()?));
+        }
+        Ok(lhs)
+    }
+
+    fn product(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.signed()?;
+        while let Some(Token::Op(op @ ('*' | '/'))) = self.peek() {
+            let op = if *op == '*' { BinaryOp::Multiply } else { BinaryOp::Divide };
+            self.advance();
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.signed()?));
+        }
+        Ok(lhs)
+    }
+
+    fn signed(&mut self) -> Result<Expr, String> {
+        match self.peek() {
+            Some(Token::Op('-')) => {
+                self.advance();
+                Ok(Expr::Negate(Box::new(self.signed()?)))
+            }
+            Some(Token::Op('+')) => {
+                self.advance();
+                self.signed()
+            }
+            _ => self.power(),
+        }
+    }
+
+    fn power(&mut self) -> Result<Expr, String> {
+        let base = self.atom()?;
+        if let Some(Token::Op('^')) = self.peek() {
+            self.advance();
+            return Ok(Expr::Binary(BinaryOp::Power, Box::new(base), Box::new(self.signed()?)));
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<Expr, String> {
+        let position = self.position();
+        match self.advance() {
+            Some(Token::Number(value)) => Ok(Expr::Number(value)),
+            Some(Token::Open) => {
+                let expr = self.sum()?;
+                self.expect(Token::Close, "')'")?;
+                Ok(expr)
+            }
+            Some(Token::Ident(name)) if self.peek() == Some(&Token::Open) => {
+                let function = Function::from_name(&name).ok_or_else(|| format!("unknown function '{}' at {}", name, position))?;
+                self.advance();
+                let mut args = vec![self.sum()?];
+                while self.peek() == Some(&Token::Comma) {
+                    self.advance();
+                    args.push(self.sum()?);
+                }
+                self.expect(Token::Close, "')'")?;
+                if args.len() != function.arity() {
+                    return Err(format!("{} takes {} argument{} at {}", name, function.arity(), if function.arity() == 1 { "" } else { "s" }, position));
+                }
+                Ok(Expr::Call(function, args))
+            }
+            Some(Token::Ident(name)) => match name.as_str() {
+                "x" => Ok(Expr::Variable(Variable::X)),
+                "y" => Ok(Expr::Variable(Variable::Y)),
+                "r" => Ok(Expr::Variable(Variable::R)),
+                "theta" => Ok(Expr::Variable(Variable::Theta)),
+                "pi" => Ok(Expr::Number(PI)),
+                "e" => Ok(Expr::Number(E)),
+                _ => Err(format!("unknown variable '{}' at {}", name, position)),
+            },
+            Some(_) => Err(format!("unexpected token at {}", position)),
+            None => Err(format!("unexpected end of expression at {}", position)),
+        }
+    }
+}
+
+impl Density {
+    pub fn parse(source: &str) -> Result<Density, String> {
+        let mut parser = Parser { tokens: tokenize(source)?, next: 0, end: source.len() };
+        let expr = parser.sum()?;
+        if parser.peek().is_some() {
+            return Err(format!("unexpected token at {}", parser.position()));
+        }
+        Ok(Density { source: source.to_string(), expr })
+    }
+
+    /// The density at `(x, y)`, with negative and NaN values taken as zero.
+    pub fn eval(&self, x: f32, y: f32) -> f32 {
+        self.expr.eval(x, y).max(0.0)
+    }
+
+    /// The largest finite value of the density on a grid over the disk of
+    /// `radius`, zero if there's none.
+    pub fn peak(&self, radius: f32) -> f32 {
+        let step = 2.0 * radius / (PEAK_GRID - 1) as f32;
+        (0..PEAK_GRID * PEAK_GRID)
+            .map(|i| Vec2::new((i % PEAK_GRID) as f32, (i / PEAK_GRID) as f32) * step - radius)
+            .filter(|point| point.length() <= radius)
+            .map(|point| self.eval(point.x, point.y))
+            .filter(|value| value.is_finite())
+            .fold(0.0, f32::max)
+    }
+
+    /// Rejection-samples `count` offsets within the disk of `radius` with
+    /// probability proportional to the density, against the peak found on a
+    /// grid, so anything the grid misses above it is clipped. The density
+    /// must be positive somewhere on that grid.
+    pub fn sample(&self, rng: &mut impl Rng, count: usize, radius: f32) -> Vec<Vec3> {
+        let peak = self.peak(radius);
+        assert!(peak > 0.0, "density {} is nowhere positive within radius {}", self, radius);
+        let mut positions = Vec::with_capacity(count);
+        while positions.len() < count {
+            let r = radius * rng.gen::<f32>().sqrt();
+            let theta = rng.gen::<f32>() * TAU;
+            let (x, y) = (r * theta.cos(), r * theta.sin());
+            if rng.gen::<f32>() * peak < self.eval(x, y) {
+                positions.push(Vec3::new(x, y, 0.0));
+            }
+        }
+        positions
+    }
+}
+
+impl fmt::Display for Density {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl fmt::Debug for Density {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Density({:?})", self.source)
+    }
+}
@@ -0,0 +1,178 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    advance_step, picking::Cursor, scene::{CreatureSpawn, SpawnPattern}, spawn_creature, spawner::ParticleBudget,
+    state::SavedState, update_position, Creature, Fields, Integrator, Mass, Parameters, Particle, ParticleBundle,
+    SimRng, Velocity,
+};
+
+// Radius around the emitter new particles are scattered in, so that two
+// emitted in the same step don't land on top of each other.
+const EMIT_SPREAD: f32 = 0.25;
+const EMITTER_COLOR: Color = Color::LIME_GREEN;
+
+/// A point that injects `rate` particles per unit of simulation time into a
+/// creature of its own, each starting at `initial_velocity`, while that
+/// creature has fewer than `max_particles`. The velocity only carries into
+/// the motion under momentum dynamics.
+#[derive(Component, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Emitter {
+    pub position: Vec3,
+    pub rate: f32,
+    pub initial_velocity: Vec3,
+    pub max_particles: usize,
+}
+
+/// The creature an emitter feeds, spawned with its first particle, and the
+/// fraction of a particle it owes from earlier steps.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct Emission {
+    pub creature: Option<Entity>,
+    pub owed: f32,
+}
+
+/// Spawns `emitters`, whose creatures take `parameters`, unless a saved state
+/// brought its own emitters back. In the window
+/// Shift-right-click places another like `template` at the cursor, and
+/// Backspace removes them all along with the attractors.
+pub struct EmitterPlugin {
+    pub emitters: Vec<Emitter>,
+    pub template: Emitter,
+    pub parameters: Parameters,
+    pub interactive: bool,
+}
+
+#[derive(Resource)]
+struct EmitterSettings {
+    initial: Vec<Emitter>,
+    template: Emitter,
+    parameters: Parameters,
+}
+
+impl Plugin for EmitterPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(EmitterSettings { initial: self.emitters.clone(), template: self.template, parameters: self.parameters.clone() })
+            .add_systems(Startup, spawn_emitters)
+            // Alongside births, so a step's new particles are in place for the next.
+            .add_systems(Update, emit_particles.after(update_position).before(advance_step));
+        if self.interactive {
+            app.add_systems(Update, (place_emitters, draw_emitters));
+        }
+    }
+}
+
+fn spawn_emitters(mut commands: Commands, settings: Res<EmitterSettings>, state: Option<Res<SavedState>>) {
+    if state.is_some_and(|state| state.has_emitters()) {
+        return;
+    }
+    for emitter in settings.initial.iter() {
+        commands.spawn((*emitter, Emission::default()));
+    }
+}
+
+fn emit_particles(
+    mut commands: Commands,
+    settings: Res<EmitterSettings>,
+    budget: Res<ParticleBudget>,
+    integrator: Res<Integrator>,
+    mut rng: ResMut<SimRng>,
+    mut emitter_query: Query<(&Emitter, &mut Emission)>,
+    creature_query: Query<Option<&Children>, With<Creature>>,
+) {
+    let mut total: usize = creature_query.iter().flatten().map(|children| children.len()).sum();
+    for (emitter, mut emission) in emitter_query.iter_mut() {
+        emission.owed += emitter.rate * integrator.dt;
+        let due = emission.owed.floor();
+        emission.owed -= due;
+
+        // The creature may have died, or been merged into another.
+        let creature = emission.creature.filter(|creature| creature_query.contains(*creature));
+        let alive = creature.and_then(|creature| creature_query.get(creature).ok()).flatten().map_or(0, |children| children.len());
+        let room = emitter.max_particles.saturating_sub(alive).min(budget.0.map_or(usize::MAX, |budget| budget.saturating_sub(total)));
+        let count = (due as usize).min(room);
+        if count == 0 {
+            continue;
+        }
+        total += count;
+
+        let offsets: Vec<Vec3> = (0..count)
+            .map(|_| {
+                let r = EMIT_SPREAD * rng.0.gen::<f32>().sqrt();
+                let theta = rng.0.gen::<f32>() * TAU;
+                Vec3::new(r * theta.cos(), r * theta.sin(), 0.0)
+            })
+            .collect();
+        match creature {
+            Some(creature) => {
+                commands.entity(creature).with_children(|parent| {
+                    for offset in offsets {
+                        parent.spawn(ParticleBundle {
+                            spatial: SpatialBundle {
+                                transform: Transform::from_translation(offset),
+                                ..default()
+                            },
+                            fields: Fields::default(),
+                            mass: Mass::default(),
+                            velocity: Velocity(emitter.initial_velocity),
+                            particle: Particle,
+                        });
+                    }
+                });
+            }
+            None => {
+                let spawn = CreatureSpawn {
+                    position: emitter.position,
+                    parameters: settings.parameters.clone(),
+                    pattern: SpawnPattern::Explicit(offsets),
+                    jitter: Some(0.0),
+                    initial_velocity: emitter.initial_velocity,
+                    ..default()
+                };
+                emission.creature = Some(spawn_creature(&mut commands, &mut rng.0, &spawn, integrator.dt));
+            }
+        }
+    }
+}
+
+fn place_emitters(
+    mut commands: Commands,
+    buttons: Res<Input<MouseButton>>,
+    keys: Res<Input<KeyCode>>,
+    settings: Res<EmitterSettings>,
+    cursor: Cursor,
+    emitter_query: Query<Entity, With<Emitter>>,
+) {
+    if keys.just_pressed(KeyCode::Back) {
+        for entity in emitter_query.iter() {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    if !buttons.just_pressed(MouseButton::Right) || !emitter_modifier_pressed(&keys) {
+        return;
+    }
+    if let Some(position) = cursor.world_position() {
+        let emitter = Emitter { position: position.extend(0.0), ..settings.template };
+        info!("emitter at {:.2}, {} per unit time up to {}", position, emitter.rate, emitter.max_particles);
+        commands.spawn((emitter, Emission::default()));
+    }
+}
+
+/// Whether a right-click places an emitter instead of an attractor.
+pub fn emitter_modifier_pressed(keys: &Input<KeyCode>) -> bool {
+    keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight])
+}
+
+fn draw_emitters(mut gizmos: Gizmos, emitter_query: Query<&Emitter>) {
+    for emitter in emitter_query.iter() {
+        let position = emitter.position.truncate();
+        gizmos.circle_2d(position, EMIT_SPREAD * 2.0, EMITTER_COLOR);
+        if emitter.initial_velocity != Vec3::ZERO {
+            gizmos.line_2d(position, position + emitter.initial_velocity.truncate(), EMITTER_COLOR);
+        }
+    }
+}
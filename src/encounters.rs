@@ -0,0 +1,136 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+};
+
+use bevy::{app::AppExit, prelude::*};
+
+use crate::{
+    headless::exit_after_steps,
+    output::{OutputConfig, OutputName},
+    DirectPairSums, Scene, SimStep,
+};
+
+/// Close encounters, pairs of particles inside each other's repulsion radius
+/// of 1, counted by the pair loop: how many there have been since the last
+/// reset and a histogram of how close they came. The current step's count is
+/// in the step diagnostics. None are counted with `--gpu`, or in a window
+/// that leaves the stepping to `--background-sim` or `--play-bin`.
+#[derive(Resource)]
+pub struct CloseEncounters {
+    counts: Vec<u64>,
+    steps: u64,
+}
+
+impl CloseEncounters {
+    pub fn new(bins: usize) -> Self {
+        CloseEncounters { counts: vec![0; bins.max(1)], steps: 0 }
+    }
+
+    fn bin_width(&self) -> f32 {
+        1.0 / self.counts.len() as f32
+    }
+
+    pub(crate) fn start_step(&mut self) {
+        self.steps += 1;
+    }
+
+    pub(crate) fn record(&mut self, r: f32) {
+        let last = self.counts.len() - 1;
+        let bin = ((r / self.bin_width()) as usize).min(last);
+        self.counts[bin] += 1;
+    }
+
+    fn clear(&mut self) {
+        self.counts.iter_mut().for_each(|count| *count = 0);
+        self.steps = 0;
+    }
+
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Mean encounters per step since the last reset.
+    pub fn per_step(&self) -> f32 {
+        self.total() as f32 / self.steps.max(1) as f32
+    }
+
+    /// Writes `r,count,per_step` rows, one per bin, with `r` its center.
+    pub fn write_csv(&self, file: File) -> io::Result<()> {
+        let mut writer = BufWriter::new(file);
+        writeln!(writer, "r,count,per_step")?;
+        let width = self.bin_width();
+        for (bin, count) in self.counts.iter().enumerate() {
+            writeln!(writer, "{},{},{}", (bin as f32 + 0.5) * width, count, *count as f32 / self.steps.max(1) as f32)?;
+        }
+        writer.flush()
+    }
+}
+
+/// With `interactive`, F3 resets the counts and Shift-F3 writes the histogram
+/// to a CSV named by the output template. With `csv`, it's written there on
+/// exit.
+pub struct CloseEncountersPlugin {
+    pub bins: usize,
+    pub interactive: bool,
+    pub csv: Option<File>,
+}
+
+#[derive(Resource)]
+struct ExitCsv(Option<File>);
+
+impl Plugin for CloseEncountersPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(CloseEncounters::new(self.bins));
+
+        if let Some(file) = &self.csv {
+            let file = file.try_clone().expect("failed to open the close encounters file");
+            app.insert_resource(ExitCsv(Some(file)))
+                .add_systems(Last, write_on_exit.after(exit_after_steps));
+        }
+        if self.interactive {
+            app.add_systems(Update, (reset_encounters, export_encounters));
+        }
+    }
+}
+
+fn write_on_exit(encounters: Res<CloseEncounters>, mut csv: ResMut<ExitCsv>, mut exit: EventReader<AppExit>) {
+    if exit.iter().next().is_none() {
+        return;
+    }
+    if let Some(file) = csv.0.take() {
+        match encounters.write_csv(file) {
+            Ok(()) => info!("wrote {} close encounters over {} steps", encounters.total(), encounters.steps),
+            Err(err) => error!("failed to write the close encounters: {}", err),
+        }
+    }
+}
+
+fn reset_encounters(keys: Res<Input<KeyCode>>, direct: Res<DirectPairSums>, mut encounters: ResMut<CloseEncounters>) {
+    if keys.just_pressed(KeyCode::F3) && !keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        encounters.clear();
+        info!("close encounter counts reset");
+        direct.warn_unless_cpu("close encounters");
+    }
+}
+
+fn export_encounters(
+    keys: Res<Input<KeyCode>>,
+    direct: Res<DirectPairSums>,
+    encounters: Res<CloseEncounters>,
+    output: Res<OutputConfig>,
+    scene: Res<Scene>,
+    step: Res<SimStep>,
+) {
+    if !(keys.just_pressed(KeyCode::F3) && keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight])) {
+        return;
+    }
+    direct.warn_unless_cpu("close encounters");
+    let name = OutputName { step: step.0, seed: scene.seed, creature: None };
+    let written = output.path(&name, Some("encounters.csv"))
+        .and_then(|path| File::create(&path).and_then(|file| encounters.write_csv(file)).map(|_| path));
+    match written {
+        Ok(path) => info!("wrote {} close encounters over {} steps to {}", encounters.total(), encounters.steps, path.display()),
+        Err(err) => error!("failed to write the close encounters under {}: {}", output.dir.display(), err),
+    }
+}
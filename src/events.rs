@@ -0,0 +1,76 @@
+use bevy::prelude::*;
+
+use crate::{Creature, SimStep};
+
+/// A creature entered the world, from the scene, a saved state or a spawn request.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct CreatureSpawned {
+    pub creature: Entity,
+}
+
+/// A creature's particles split into more disconnected clusters than before.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct CreatureFragmented {
+    pub creature: Entity,
+    pub clusters: usize,
+}
+
+/// A creature's positions or fields stopped being finite.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct CreatureExploded {
+    pub creature: Entity,
+}
+
+/// The particles of `from` were moved into `into`, and `from` despawned.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct CreatureMerged {
+    pub into: Entity,
+    pub from: Entity,
+}
+
+/// Registers the creature lifecycle events and logs each one as it happens:
+/// spawns, fragments and merges at debug level and explosions as warnings,
+/// with the step and creatures as fields.
+pub struct CreatureEventsPlugin;
+
+impl Plugin for CreatureEventsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CreatureSpawned>()
+            .add_event::<CreatureFragmented>()
+            .add_event::<CreatureExploded>()
+            .add_event::<CreatureMerged>()
+            .add_systems(PreUpdate, announce_spawns)
+            .add_systems(Last, log_creature_events);
+    }
+}
+
+// Every spawn path ends in a new Creature, so they're all caught here rather
+// than in each of them.
+fn announce_spawns(
+    creature_query: Query<Entity, Added<Creature>>,
+    mut spawned: EventWriter<CreatureSpawned>,
+) {
+    spawned.send_batch(creature_query.iter().map(|creature| CreatureSpawned { creature }));
+}
+
+fn log_creature_events(
+    step: Res<SimStep>,
+    mut spawned: EventReader<CreatureSpawned>,
+    mut fragmented: EventReader<CreatureFragmented>,
+    mut exploded: EventReader<CreatureExploded>,
+    mut merged: EventReader<CreatureMerged>,
+) {
+    let step = step.0;
+    for event in spawned.iter() {
+        debug!(step, creature = ?event.creature, "creature spawned");
+    }
+    for event in fragmented.iter() {
+        debug!(step, creature = ?event.creature, clusters = event.clusters, "creature fragmented");
+    }
+    for event in exploded.iter() {
+        warn!(step, creature = ?event.creature, "creature exploded: its positions or fields aren't finite");
+    }
+    for event in merged.iter() {
+        debug!(step, from = ?event.from, into = ?event.into, "creatures merged");
+    }
+}
@@ -0,0 +1,165 @@
+use std::{fs, process::Command};
+
+use bevy::{prelude::*, render::view::screenshot::ScreenshotManager, window::PrimaryWindow};
+use serde::Serialize;
+
+use crate::{
+    advance_step,
+    culling::ViewBounds,
+    output::{OutputConfig, OutputName},
+    selection::Selection,
+    state::StateCapture,
+    svg::{export_svg, SvgCircle},
+    template::CreatureTemplate,
+    Creature, DisplayRadius, Fields, Mass, Parameters, Particle, Scene, SimStep, SizeMode,
+};
+
+/// P writes the current frame to a new directory named by the output
+/// template: a screenshot, the state to reload it with `--load-state`, and
+/// the provenance of the run. V writes it as an SVG with one circle per
+/// particle, named by the same template, and Shift-P writes the selected
+/// creature as a `--template` to spawn it again in its current shape.
+pub struct ExportPlugin {
+    pub output: OutputConfig,
+}
+
+#[derive(Serialize)]
+struct Metadata<'a> {
+    seed: u64,
+    step: u64,
+    git_hash: Option<String>,
+    command_line: Vec<String>,
+    parameters: Vec<&'a Parameters>,
+}
+
+impl Plugin for ExportPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.output.clone())
+            // Between steps, so the state, the screenshot of this frame and
+            // the step count all agree.
+            .add_systems(Update, (export_frame, export_frame_svg, export_template).after(advance_step));
+    }
+}
+
+fn export_frame(
+    keys: Res<Input<KeyCode>>,
+    output: Res<OutputConfig>,
+    capture: StateCapture,
+    mut screenshots: ResMut<ScreenshotManager>,
+    window_query: Query<Entity, With<PrimaryWindow>>,
+) {
+    if !keys.just_pressed(KeyCode::P) || keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        return;
+    }
+
+    let state = capture.capture();
+    let name = OutputName { step: state.step, seed: state.seed, creature: None };
+    let dir = match output.path(&name, None).and_then(|dir| fs::create_dir(&dir).map(|_| dir)) {
+        Ok(dir) => dir,
+        Err(err) => {
+            error!("failed to create the export directory under {}: {}", output.dir.display(), err);
+            return;
+        }
+    };
+
+    if let Ok(window) = window_query.get_single() {
+        if let Err(err) = screenshots.save_screenshot_to_disk(window, dir.join("screenshot.png")) {
+            error!("failed to take a screenshot: {}", err);
+        }
+    }
+
+    if let Err(err) = state.save(&dir.join("state.ron")) {
+        error!("failed to save the state: {}", err);
+    }
+
+    let metadata = Metadata {
+        seed: state.seed,
+        step: state.step,
+        git_hash: git_hash(),
+        command_line: std::env::args().collect(),
+        parameters: state.parameters().collect(),
+    };
+    let json = serde_json::to_string_pretty(&metadata).unwrap();
+    if let Err(err) = fs::write(dir.join("metadata.json"), json + "\n") {
+        error!("failed to write the metadata: {}", err);
+    }
+
+    info!("exported step {} to {}", state.step, dir.display());
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn export_frame_svg(
+    keys: Res<Input<KeyCode>>,
+    output: Res<OutputConfig>,
+    scene: Res<Scene>,
+    step: Res<SimStep>,
+    size_mode: Res<SizeMode>,
+    clear_color: Res<ClearColor>,
+    materials: Res<Assets<ColorMaterial>>,
+    view_bounds: ViewBounds,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    creature_query: Query<(&Parameters, &Children), With<Creature>>,
+    particle_query: Query<(&GlobalTransform, &Fields, &Mass, Option<&DisplayRadius>, Option<&Handle<ColorMaterial>>), With<Particle>>,
+) {
+    if !keys.just_pressed(KeyCode::V) {
+        return;
+    }
+    let (Some(view), Ok(window)) = (view_bounds.view_rect(), window_query.get_single()) else {
+        return;
+    };
+
+    // Same radius and color the particle is drawn with, in the same depth order.
+    let mut circles = Vec::new();
+    for (parameters, children) in creature_query.iter() {
+        for (transform, fields, mass, display_radius, material) in particle_query.iter_many(children) {
+            let translation = transform.translation();
+            let radius = display_radius.and_then(|radius| radius.0).unwrap_or_else(|| size_mode.radius(parameters, fields, mass));
+            let color = material.and_then(|material| materials.get(material)).map_or(Color::WHITE, |material| material.color);
+            circles.push((translation.z, SvgCircle { center: translation.truncate(), radius, color }));
+        }
+    }
+    circles.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+    let circles: Vec<_> = circles.into_iter().map(|(_, circle)| circle).collect();
+
+    let name = OutputName { step: step.0, seed: scene.seed, creature: None };
+    let size = Vec2::new(window.width(), window.height());
+    match output.path(&name, Some("svg")).and_then(|path| export_svg(&path, view, size, clear_color.0, &circles).map(|_| path)) {
+        Ok(path) => info!("exported step {} to {}", step.0, path.display()),
+        Err(err) => error!("failed to write an SVG under {}: {}", output.dir.display(), err),
+    }
+}
+
+fn export_template(
+    keys: Res<Input<KeyCode>>,
+    output: Res<OutputConfig>,
+    scene: Res<Scene>,
+    step: Res<SimStep>,
+    selection: Res<Selection>,
+    creature_query: Query<(Entity, &Parameters, &Children), With<Creature>>,
+    particle_query: Query<&Transform, With<Particle>>,
+) {
+    if !(keys.just_pressed(KeyCode::P) && keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight])) {
+        return;
+    }
+    let Some((creature, parameters, children)) = selection.0.first().and_then(|entity| creature_query.get(*entity).ok()) else {
+        info!("select a creature to export as a template");
+        return;
+    };
+    let positions: Vec<Vec3> = particle_query.iter_many(children).map(|transform| transform.translation).collect();
+    if positions.is_empty() {
+        return;
+    }
+
+    let template = CreatureTemplate::from_positions(parameters, &positions);
+    let name = OutputName { step: step.0, seed: scene.seed, creature: Some(creature) };
+    match output.path(&name, Some("template.ron")).map_err(|err| err.to_string()).and_then(|path| template.save(&path).map(|_| path)) {
+        Ok(path) => info!("exported {:?} at step {} to {}, spawn it with --template", creature, step.0, path.display()),
+        Err(err) => error!("failed to write a template under {}: {}", output.dir.display(), err),
+    }
+}
+
+// Commit of the working directory, with `-dirty` when it has uncommitted changes.
+fn git_hash() -> Option<String> {
+    let output = Command::new("git").args(["describe", "--always", "--dirty", "--abbrev=40"]).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
@@ -0,0 +1,125 @@
+use bevy::prelude::*;
+
+use crate::{
+    culling::ViewBounds,
+    probe::{creature_positions, probe_energy_gradient},
+    Creature, Parameters, Particle,
+};
+
+// Arrows are scaled so that this quantile of the magnitudes fills a cell,
+// and longer ones are capped there, so the repulsion right next to a particle
+// doesn't shrink every other arrow to nothing.
+const SCALE_QUANTILE: f32 = 0.9;
+// Fraction of a cell the longest arrows span, and of an arrow its head takes.
+const ARROW_FILL: f32 = 0.9;
+const HEAD_FRACTION: f32 = 0.3;
+// Arrows shorter than this fraction of a full one aren't drawn.
+const MIN_FRACTION: f32 = 0.02;
+
+/// F2 toggles a grid of arrows over the camera's view showing the flow
+/// `-E_grad` a test particle would feel at each point, the field the
+/// streamlines trace, evaluated directly rather than from the particles'
+/// own forces.
+pub struct FlowArrowsPlugin {
+    pub enabled: bool,
+    /// Arrows along the longer side of the view
+    pub resolution: u32,
+    /// Frames between samples of the field
+    pub every: u32,
+}
+
+#[derive(Resource)]
+struct FlowArrows {
+    enabled: bool,
+    resolution: u32,
+    every: u32,
+    frames_since_sample: u32,
+    spacing: f32,
+    // Each grid point and the flow there.
+    arrows: Vec<(Vec2, Vec2)>,
+}
+
+impl Plugin for FlowArrowsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(FlowArrows {
+                enabled: self.enabled,
+                resolution: self.resolution.max(2),
+                every: self.every.max(1),
+                frames_since_sample: self.every,
+                spacing: 0.0,
+                arrows: Vec::new(),
+            })
+            .add_systems(Update, (toggle_flow_arrows, sample_flow, draw_flow_arrows).chain());
+    }
+}
+
+fn toggle_flow_arrows(keys: Res<Input<KeyCode>>, mut flow: ResMut<FlowArrows>) {
+    if keys.just_pressed(KeyCode::F2) {
+        flow.enabled = !flow.enabled;
+        flow.frames_since_sample = flow.every;
+        flow.arrows.clear();
+        info!("flow arrows {}", if flow.enabled { "enabled" } else { "disabled" });
+    }
+}
+
+fn sample_flow(
+    mut flow: ResMut<FlowArrows>,
+    view_bounds: ViewBounds,
+    creature_query: Query<(&Transform, &Parameters, &Children), With<Creature>>,
+    particle_query: Query<&Transform, With<Particle>>,
+) {
+    if !flow.enabled {
+        return;
+    }
+    if flow.frames_since_sample + 1 < flow.every {
+        flow.frames_since_sample += 1;
+        return;
+    }
+    flow.frames_since_sample = 0;
+
+    let Some(view) = view_bounds.view_rect().filter(|view| view.size().min_element() > 0.0) else {
+        flow.arrows.clear();
+        return;
+    };
+    let creatures = creature_positions(&creature_query, &particle_query);
+    let spacing = view.size().max_element() / flow.resolution as f32;
+    let counts = (view.size() / spacing).ceil().as_uvec2();
+    // Centered in the view, so the grid is symmetric about it.
+    let origin = view.center() - 0.5 * (counts.as_vec2() - 1.0) * spacing;
+
+    flow.spacing = spacing;
+    flow.arrows = (0..counts.x)
+        .flat_map(|i| (0..counts.y).map(move |j| origin + UVec2::new(i, j).as_vec2() * spacing))
+        .map(|point| (point, -probe_energy_gradient(point.extend(0.0), &creatures).truncate()))
+        .filter(|(_, vector)| vector.is_finite())
+        .collect();
+}
+
+fn draw_flow_arrows(mut gizmos: Gizmos, flow: Res<FlowArrows>) {
+    if !flow.enabled || flow.arrows.is_empty() {
+        return;
+    }
+    let mut magnitudes: Vec<f32> = flow.arrows.iter().map(|(_, vector)| vector.length()).collect();
+    let index = ((magnitudes.len() - 1) as f32 * SCALE_QUANTILE) as usize;
+    let scale = *magnitudes.select_nth_unstable_by(index, f32::total_cmp).1;
+    if scale <= 0.0 {
+        return;
+    }
+
+    for (point, vector) in flow.arrows.iter() {
+        let fraction = (vector.length() / scale).min(1.0);
+        if fraction < MIN_FRACTION {
+            continue;
+        }
+        let length = fraction * ARROW_FILL * flow.spacing;
+        let direction = vector.normalize();
+        let tail = *point - 0.5 * length * direction;
+        let tip = *point + 0.5 * length * direction;
+        let color = Color::rgba(0.4, 1.0, 0.6, 0.25 + 0.65 * fraction);
+        gizmos.line_2d(tail, tip, color);
+        let head = HEAD_FRACTION * length;
+        for angle in [2.6, -2.6] {
+            gizmos.line_2d(tip, tip + head * Vec2::from_angle(angle).rotate(direction), color);
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use std::fmt::Write;
+
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{inspector::Inspected, picking::ScreenSpace, DirectPairSums};
+
+// Size of the chart and its gap from the right of the window, in logical pixels.
+const CHART_SIZE: Vec2 = Vec2::new(240.0, 120.0);
+const CHART_MARGIN: f32 = 16.0;
+const REPULSION_COLOR: Color = Color::TOMATO;
+const KERNEL_COLOR: Color = Color::LIME_GREEN;
+
+/// The inspected particle's R_grad and U_grad split by the distance of the
+/// neighbors they came from, in bands `width` wide with the last one open,
+/// summed by the pair loop. Not split with `--barnes-hut` or `--gpu`.
+#[derive(Resource)]
+pub struct ForceBands {
+    enabled: bool,
+    width: f32,
+    pub(crate) particle: Option<Entity>,
+    bands: Vec<Band>,
+}
+
+#[derive(Clone, Copy, Default)]
+struct Band {
+    R_grad: Vec3,
+    U_grad: Vec3,
+}
+
+impl ForceBands {
+    fn band(&mut self, r: f32) -> &mut Band {
+        let last = self.bands.len() - 1;
+        &mut self.bands[((r / self.width) as usize).min(last)]
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.bands.iter_mut().for_each(|band| *band = Band::default());
+    }
+
+    /// Adds a pair's repulsion gradients, one for each of `pair`, if the
+    /// inspected particle is in it.
+    pub(crate) fn add_repulsion(&mut self, pair: [Entity; 2], r: f32, grads: [Vec3; 2]) {
+        if let Some(side) = pair.iter().position(|entity| Some(*entity) == self.particle) {
+            self.band(r).R_grad += grads[side];
+        }
+    }
+
+    /// Adds a pair's kernel gradients, as [`ForceBands::add_repulsion`].
+    pub(crate) fn add_kernel(&mut self, pair: [Entity; 2], r: f32, grads: [Vec3; 2]) {
+        if let Some(side) = pair.iter().position(|entity| Some(*entity) == self.particle) {
+            self.band(r).U_grad += grads[side];
+        }
+    }
+}
+
+/// X shows a bar chart of [`ForceBands`] for the particle picked with I,
+/// the length of each band's R_grad beside its U_grad, on the right of the
+/// window.
+pub struct ForceBandsPlugin {
+    pub enabled: bool,
+    pub width: f32,
+    pub count: usize,
+}
+
+#[derive(Component)]
+struct ForceBandsText;
+
+impl Plugin for ForceBandsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ForceBands {
+                enabled: self.enabled,
+                width: self.width,
+                particle: None,
+                bands: vec![Band::default(); self.count.max(1)],
+            })
+            .add_systems(Startup, spawn_force_bands_text)
+            .add_systems(Update, (toggle_force_bands, follow_inspected, draw_force_bands, update_force_bands_text).chain());
+    }
+}
+
+fn spawn_force_bands_text(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section("", TextStyle {
+            font_size: 14.0,
+            color: Color::WHITE,
+            ..default()
+        })
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(50.0),
+            right: Val::Px(CHART_MARGIN),
+            margin: UiRect::top(Val::Px(CHART_SIZE.y / 2.0 + 4.0)),
+            ..default()
+        }),
+        ForceBandsText,
+    ));
+}
+
+fn toggle_force_bands(keys: Res<Input<KeyCode>>, direct: Res<DirectPairSums>, mut bands: ResMut<ForceBands>) {
+    if keys.just_pressed(KeyCode::X) {
+        bands.enabled = !bands.enabled;
+        if bands.enabled {
+            direct.warn_unless("the force by neighbor distance");
+        }
+    }
+}
+
+// The bands are summed for the inspected particle from the next step on.
+fn follow_inspected(inspected: Res<Inspected>, mut bands: ResMut<ForceBands>) {
+    let particle = inspected.0.filter(|_| bands.enabled);
+    if bands.particle != particle {
+        bands.particle = particle;
+        bands.clear();
+    }
+}
+
+fn chart_rect(window: &Window) -> Rect {
+    let min = Vec2::new(window.width() - CHART_MARGIN - CHART_SIZE.x, (window.height() - CHART_SIZE.y) / 2.0);
+    Rect::from_corners(min, min + CHART_SIZE)
+}
+
+fn outline(gizmos: &mut Gizmos, screen: &ScreenSpace, rect: Rect, color: Color) {
+    let corners = [rect.min, Vec2::new(rect.max.x, rect.min.y), rect.max, Vec2::new(rect.min.x, rect.max.y), rect.min];
+    gizmos.linestrip_2d(corners.into_iter().filter_map(|point| screen.world_point(point)), color);
+}
+
+fn draw_force_bands(
+    bands: Res<ForceBands>,
+    screen: ScreenSpace,
+    mut gizmos: Gizmos,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    if bands.particle.is_none() {
+        return;
+    }
+
+    let chart = chart_rect(window);
+    outline(&mut gizmos, &screen, chart, Color::GRAY);
+    let largest = bands.bands.iter()
+        .flat_map(|band| [band.R_grad.length(), band.U_grad.length()])
+        .fold(f32::EPSILON, f32::max);
+    let slot = chart.width() / bands.bands.len() as f32;
+    for (index, band) in bands.bands.iter().enumerate() {
+        let left = chart.min.x + index as f32 * slot;
+        for (offset, length, color) in [(0.15, band.R_grad.length(), REPULSION_COLOR), (0.5, band.U_grad.length(), KERNEL_COLOR)] {
+            let height = length / largest * chart.height();
+            let x = left + offset * slot;
+            outline(&mut gizmos, &screen, Rect::new(x, chart.max.y - height, x + 0.35 * slot, chart.max.y), color);
+        }
+    }
+}
+
+fn update_force_bands_text(
+    bands: Res<ForceBands>,
+    mut text_query: Query<(&mut Text, &mut Visibility), With<ForceBandsText>>,
+) {
+    let mut text = String::new();
+    if bands.particle.is_some() {
+        let _ = writeln!(text, "|R_grad| red, |U_grad| green, by neighbor distance");
+        let last = bands.bands.len() - 1;
+        for (index, band) in bands.bands.iter().enumerate() {
+            let start = index as f32 * bands.width;
+            let range = if index == last { format!("[{}, inf)", start) } else { format!("[{}, {})", start, start + bands.width) };
+            let _ = writeln!(text, "r {:<10} R {:.4}  U {:.4}", range, band.R_grad.length(), band.U_grad.length());
+        }
+    }
+
+    for (mut panel, mut visibility) in text_query.iter_mut() {
+        panel.sections[0].value.clone_from(&text);
+        *visibility = if text.is_empty() { Visibility::Hidden } else { Visibility::Inherited };
+    }
+}
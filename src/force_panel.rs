@@ -0,0 +1,165 @@
+use bevy::prelude::*;
+
+use crate::ForceTerms;
+
+// Distance of the panel from the bottom of the window, in logical pixels. It
+// starts halfway across, clear of the minimap and the coordinate readout.
+const PANEL_MARGIN: f32 = 16.0;
+const CHECKED: Color = Color::WHITE;
+const UNCHECKED: Color = Color::GRAY;
+
+/// A term of E_grad the panel can switch off.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+enum ForceTerm {
+    Repulsion,
+    Growth,
+    External,
+}
+
+impl ForceTerm {
+    const ALL: [ForceTerm; 3] = [ForceTerm::Repulsion, ForceTerm::Growth, ForceTerm::External];
+
+    fn label(self) -> &'static str {
+        match self {
+            ForceTerm::Repulsion => "repulsion  R_grad",
+            ForceTerm::Growth => "growth  -dG * U_grad",
+            ForceTerm::External => "external  attractors, boundary, centroid, target",
+        }
+    }
+
+    fn enabled(self, terms: &ForceTerms) -> bool {
+        match self {
+            ForceTerm::Repulsion => terms.repulsion,
+            ForceTerm::Growth => terms.growth,
+            ForceTerm::External => terms.external,
+        }
+    }
+
+    fn toggle(self, terms: &mut ForceTerms) {
+        match self {
+            ForceTerm::Repulsion => terms.repulsion = !terms.repulsion,
+            ForceTerm::Growth => terms.growth = !terms.growth,
+            ForceTerm::External => terms.external = !terms.external,
+        }
+    }
+}
+
+/// F9 opens a panel along the bottom of the window with a checkbox for each term
+/// of E_grad, to watch what a creature does without it.
+pub struct ForcePanelPlugin {
+    pub open: bool,
+}
+
+#[derive(Resource)]
+pub struct ForcePanel {
+    open: bool,
+    hovered: bool,
+}
+
+impl ForcePanel {
+    /// Whether the cursor is over one of the panel's checkboxes.
+    pub fn hovered(&self) -> bool {
+        self.open && self.hovered
+    }
+}
+
+#[derive(Component)]
+struct PanelRoot;
+
+impl Plugin for ForcePanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ForcePanel { open: self.open, hovered: false })
+            .add_systems(Startup, spawn_panel)
+            .add_systems(Update, (toggle_panel, click_checkboxes, update_checkboxes).chain());
+    }
+}
+
+fn spawn_panel(mut commands: Commands, panel: Res<ForcePanel>) {
+    let root = NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            left: Val::Percent(50.0),
+            bottom: Val::Px(PANEL_MARGIN),
+            flex_direction: FlexDirection::Column,
+            padding: UiRect::all(Val::Px(4.0)),
+            ..default()
+        },
+        background_color: Color::rgba(0.1, 0.1, 0.1, 0.85).into(),
+        visibility: if panel.open { Visibility::Inherited } else { Visibility::Hidden },
+        ..default()
+    };
+
+    commands.spawn((root, PanelRoot)).with_children(|root| {
+        for term in ForceTerm::ALL {
+            root.spawn((
+                ButtonBundle {
+                    style: Style {
+                        padding: UiRect::all(Val::Px(2.0)),
+                        ..default()
+                    },
+                    background_color: Color::NONE.into(),
+                    ..default()
+                },
+                term,
+            ))
+            .with_children(|button| {
+                button.spawn(TextBundle::from_section("", TextStyle {
+                    font_size: 14.0,
+                    color: CHECKED,
+                    ..default()
+                }));
+            });
+        }
+    });
+}
+
+fn toggle_panel(
+    keys: Res<Input<KeyCode>>,
+    mut panel: ResMut<ForcePanel>,
+    mut root_query: Query<&mut Visibility, With<PanelRoot>>,
+) {
+    if !keys.just_pressed(KeyCode::F9) {
+        return;
+    }
+    panel.open = !panel.open;
+    for mut visibility in root_query.iter_mut() {
+        *visibility = if panel.open { Visibility::Inherited } else { Visibility::Hidden };
+    }
+}
+
+fn click_checkboxes(
+    mut panel: ResMut<ForcePanel>,
+    mut force_terms: ResMut<ForceTerms>,
+    button_query: Query<(&Interaction, &ForceTerm)>,
+    changed_query: Query<(&Interaction, &ForceTerm), Changed<Interaction>>,
+) {
+    panel.hovered = button_query.iter().any(|(interaction, _)| *interaction != Interaction::None);
+    if !panel.open {
+        return;
+    }
+    for (interaction, term) in changed_query.iter() {
+        if *interaction == Interaction::Pressed {
+            term.toggle(&mut force_terms);
+            info!("{:?} term {}", term, if term.enabled(&force_terms) { "enabled" } else { "disabled" });
+        }
+    }
+}
+
+// Also after G or a phase of the schedule changes the terms.
+fn update_checkboxes(
+    force_terms: Res<ForceTerms>,
+    button_query: Query<(&ForceTerm, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !force_terms.is_changed() {
+        return;
+    }
+    for (term, children) in button_query.iter() {
+        let enabled = term.enabled(&force_terms);
+        let mut texts = text_query.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.sections[0].value = format!("[{}] {}", if enabled { "x" } else { " " }, term.label());
+            text.sections[0].style.color = if enabled { CHECKED } else { UNCHECKED };
+        }
+    }
+}
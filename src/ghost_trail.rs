@@ -0,0 +1,103 @@
+use bevy::{prelude::*, sprite::Mesh2dHandle};
+
+use crate::{update_position, update_size, Creature, Particle, SimStep};
+
+// Depth of the ghosts, under every live particle however the z order spreads
+// them.
+const GHOST_DEPTH: f32 = -1.0;
+const GHOST_COLOR: Color = Color::rgb(0.6, 0.8, 1.0);
+
+/// F4 freezes a translucent copy of every particle, as drawn at that moment,
+/// under the live ones, so drift, rotation and deformation since then show
+/// against it, and Shift-F4 clears them. Up to `count` are kept, the newest at
+/// `alpha` and each older one fainter, and capturing another past that drops
+/// the oldest.
+pub struct GhostTrailPlugin {
+    pub count: usize,
+    pub alpha: f32,
+}
+
+#[derive(Resource)]
+struct GhostTrail {
+    count: usize,
+    alpha: f32,
+    captured: u64,
+}
+
+// One captured snapshot, numbered in capture order, with the material all
+// its particles share.
+#[derive(Component)]
+struct GhostSnapshot {
+    index: u64,
+    material: Handle<ColorMaterial>,
+}
+
+impl Plugin for GhostTrailPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(GhostTrail { count: self.count.max(1), alpha: self.alpha.clamp(0.0, 1.0), captured: 0 })
+            .add_systems(Update, capture_ghost.after(update_position).after(update_size));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn capture_ghost(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    mut trail: ResMut<GhostTrail>,
+    step: Res<SimStep>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    ghost_query: Query<(Entity, &GhostSnapshot)>,
+    creature_query: Query<(&Transform, &Children), With<Creature>>,
+    particle_query: Query<(&Transform, &Mesh2dHandle), With<Particle>>,
+) {
+    if !keys.just_pressed(KeyCode::F4) {
+        return;
+    }
+    let mut ghosts: Vec<_> = ghost_query.iter().collect();
+    // Newest first.
+    ghosts.sort_by_key(|(_, ghost)| std::cmp::Reverse(ghost.index));
+
+    if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        for (entity, _) in ghosts {
+            commands.entity(entity).despawn_recursive();
+        }
+        info!("ghosts cleared");
+        return;
+    }
+
+    let material = materials.add(ColorMaterial::from(GHOST_COLOR.with_a(trail.alpha)));
+    commands.spawn((
+        SpatialBundle::from_transform(Transform::from_xyz(0.0, 0.0, GHOST_DEPTH)),
+        GhostSnapshot { index: trail.captured, material: material.clone() },
+    ))
+    .with_children(|parent| {
+        for (creature_transform, children) in creature_query.iter() {
+            for (transform, mesh) in particle_query.iter_many(children) {
+                // A copy, since the particle's own mesh is resized every frame.
+                let Some(mesh) = meshes.get(&mesh.0).cloned() else {
+                    continue;
+                };
+                let center = (creature_transform.translation + transform.translation).truncate();
+                parent.spawn(ColorMesh2dBundle {
+                    mesh: Mesh2dHandle(meshes.add(mesh)),
+                    material: material.clone(),
+                    transform: Transform::from_translation(center.extend(0.0)),
+                    ..default()
+                });
+            }
+        }
+    });
+
+    trail.captured += 1;
+
+    // The new ghost takes the place of the newest, and the rest fade a step.
+    for (age, (entity, ghost)) in ghosts.into_iter().enumerate().map(|(index, ghost)| (index + 1, ghost)) {
+        if age >= trail.count {
+            commands.entity(entity).despawn_recursive();
+        } else if let Some(material) = materials.get_mut(&ghost.material) {
+            material.color.set_a(trail.alpha * (1.0 - age as f32 / trail.count as f32));
+        }
+    }
+    info!(step = step.0, "ghost captured");
+}
@@ -0,0 +1,140 @@
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+};
+
+use color_quant::NeuQuant;
+
+// Pixels the palette is learned from at most, spread evenly over the frames,
+// and how finely NeuQuant samples them, 1 being every pixel.
+const PALETTE_SAMPLE: usize = 1 << 20;
+const QUANTIZER_SAMPLING: i32 = 10;
+const MAX_CODES: u16 = 4096;
+
+/// Writes `frames`, each `width` by `height` RGBA pixels, as a looping GIF
+/// showing each for `delay` hundredths of a second. The frames share one
+/// 256-color palette learned from all of them, so colors don't flicker from
+/// frame to frame.
+pub fn write_gif(mut writer: impl Write, width: u16, height: u16, frames: &[Vec<u8>], delay: u16) -> io::Result<()> {
+    let quantizer = learn_palette(frames);
+    let palette = quantizer.color_map_rgb();
+
+    writer.write_all(b"GIF89a")?;
+    writer.write_all(&width.to_le_bytes())?;
+    writer.write_all(&height.to_le_bytes())?;
+    // A global table of 2^8 colors, 8 bits per primary.
+    writer.write_all(&[0xf7, 0, 0])?;
+    writer.write_all(&palette)?;
+    writer.write_all(&vec![0; 3 * 256 - palette.len()])?;
+    // Loop forever.
+    writer.write_all(b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00")?;
+
+    for frame in frames {
+        // Graphic control: keep the frame up for `delay`, no transparency.
+        writer.write_all(&[0x21, 0xf9, 0x04, 0x04])?;
+        writer.write_all(&delay.to_le_bytes())?;
+        writer.write_all(&[0, 0])?;
+        // Image descriptor covering the whole screen, with no local table.
+        writer.write_all(&[0x2c, 0, 0, 0, 0])?;
+        writer.write_all(&width.to_le_bytes())?;
+        writer.write_all(&height.to_le_bytes())?;
+        writer.write_all(&[0])?;
+
+        let indices: Vec<u8> = frame.chunks_exact(4).map(|pixel| quantizer.index_of(pixel) as u8).collect();
+        writer.write_all(&[8])?;
+        for block in lzw(&indices).chunks(255) {
+            writer.write_all(&[block.len() as u8])?;
+            writer.write_all(block)?;
+        }
+        writer.write_all(&[0])?;
+    }
+    writer.write_all(&[0x3b])?;
+    writer.flush()
+}
+
+fn learn_palette(frames: &[Vec<u8>]) -> NeuQuant {
+    let pixels: usize = frames.iter().map(|frame| frame.len() / 4).sum();
+    let stride = pixels.div_ceil(PALETTE_SAMPLE).max(1);
+    let sample: Vec<u8> = frames.iter()
+        .flat_map(|frame| frame.chunks_exact(4))
+        .step_by(stride)
+        .flatten()
+        .copied()
+        .collect();
+    NeuQuant::new(QUANTIZER_SAMPLING, 256, &sample)
+}
+
+// GIF's variable-width LZW over 8-bit indices, packed least significant bit
+// first. The code table is cleared and started over once it's full.
+fn lzw(indices: &[u8]) -> Vec<u8> {
+    const CLEAR: u16 = 256;
+    const END: u16 = 257;
+
+    let mut bits = BitWriter::default();
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut width = 9;
+    let mut next = END + 1;
+    bits.write(CLEAR, width);
+
+    let Some((&first, rest)) = indices.split_first() else {
+        bits.write(END, width);
+        return bits.finish();
+    };
+    let mut prefix = first as u16;
+    for &index in rest {
+        if let Some(&code) = table.get(&(prefix, index)) {
+            prefix = code;
+            continue;
+        }
+        bits.write(prefix, width);
+        if next < MAX_CODES {
+            table.insert((prefix, index), next);
+            next += 1;
+            // The decoder adds each code a step behind, so it widens once
+            // the code after the first that no longer fits is assigned.
+            if next > 1 << width {
+                width += 1;
+            }
+        } else {
+            bits.write(CLEAR, width);
+            table.clear();
+            width = 9;
+            next = END + 1;
+        }
+        prefix = index as u16;
+    }
+    bits.write(prefix, width);
+    // Reading that last code, the decoder adds the code from the one before
+    // it and may widen for the end code.
+    if next == 1 << width && width < 12 {
+        width += 1;
+    }
+    bits.write(END, width);
+    bits.finish()
+}
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    count: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, width: u32) {
+        self.buffer |= (code as u32) << self.count;
+        self.count += width;
+        while self.count >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
@@ -0,0 +1,136 @@
+use std::{
+    fs::File,
+    io::BufWriter,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use bevy::{prelude::*, render::view::screenshot::ScreenshotManager, tasks::AsyncComputeTaskPool, window::PrimaryWindow};
+
+use crate::gif::write_gif;
+
+/// Captures the window `fps` times a second from startup until it has
+/// `frames` frames, each shrunk by `downscale` on both sides, and encodes
+/// them to `file` as a looping GIF off the main thread.
+pub struct GifRecordPlugin {
+    pub file: File,
+    pub path: PathBuf,
+    pub frames: usize,
+    pub fps: f32,
+    pub downscale: u32,
+}
+
+// A captured frame: its size and RGBA pixels.
+struct Frame {
+    size: UVec2,
+    pixels: Vec<u8>,
+}
+
+#[derive(Resource)]
+struct GifRecording {
+    file: Option<File>,
+    path: PathBuf,
+    frames: usize,
+    interval: f32,
+    downscale: u32,
+    since_capture: f32,
+    requested: usize,
+    // Filled in by the screenshot callbacks, which run on the render side.
+    captured: Arc<Mutex<Vec<Frame>>>,
+}
+
+impl Plugin for GifRecordPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(GifRecording {
+                file: Some(self.file.try_clone().expect("failed to open the GIF file")),
+                path: self.path.clone(),
+                frames: self.frames.max(1),
+                interval: 1.0 / self.fps,
+                downscale: self.downscale.max(1),
+                since_capture: f32::INFINITY,
+                requested: 0,
+                captured: Arc::new(Mutex::new(Vec::new())),
+            })
+            .add_systems(Update, (capture_frame, encode_gif));
+    }
+}
+
+fn capture_frame(
+    time: Res<Time>,
+    mut recording: ResMut<GifRecording>,
+    mut screenshots: ResMut<ScreenshotManager>,
+    window_query: Query<Entity, With<PrimaryWindow>>,
+) {
+    recording.since_capture += time.delta_seconds();
+    if recording.requested >= recording.frames || recording.since_capture < recording.interval {
+        return;
+    }
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+
+    let captured = Arc::clone(&recording.captured);
+    let downscale = recording.downscale;
+    let requested = screenshots.take_screenshot(window, move |image| {
+        let Ok(image) = image.try_into_dynamic() else {
+            return;
+        };
+        let image = image.into_rgba8();
+        let size = UVec2::new(image.width(), image.height());
+        captured.lock().unwrap().push(shrink(size, &image.into_raw(), downscale));
+    });
+    // Taken by an export this frame; try again on the next.
+    if requested.is_ok() {
+        recording.requested += 1;
+        recording.since_capture = 0.0;
+    }
+}
+
+// Averages each `factor` by `factor` block of pixels into one.
+fn shrink(size: UVec2, pixels: &[u8], factor: u32) -> Frame {
+    let shrunk = (size / factor).max(UVec2::ONE);
+    let mut out = Vec::with_capacity((shrunk.x * shrunk.y * 4) as usize);
+    for y in 0..shrunk.y {
+        for x in 0..shrunk.x {
+            let mut sum = [0u32; 4];
+            let mut count = 0;
+            for sy in y * factor..((y + 1) * factor).min(size.y) {
+                for sx in x * factor..((x + 1) * factor).min(size.x) {
+                    let at = ((sy * size.x + sx) * 4) as usize;
+                    for (sum, value) in sum.iter_mut().zip(&pixels[at..at + 4]) {
+                        *sum += *value as u32;
+                    }
+                    count += 1;
+                }
+            }
+            out.extend(sum.map(|sum| (sum / count.max(1)) as u8));
+        }
+    }
+    Frame { size: shrunk, pixels: out }
+}
+
+fn encode_gif(mut recording: ResMut<GifRecording>) {
+    if recording.file.is_none() || recording.captured.lock().unwrap().len() < recording.frames {
+        return;
+    }
+    let file = recording.file.take().unwrap();
+    let frames = std::mem::take(&mut *recording.captured.lock().unwrap());
+    let path = recording.path.clone();
+    // Hundredths of a second, the unit GIF delays come in.
+    let delay = (100.0 * recording.interval).round().max(1.0) as u16;
+
+    AsyncComputeTaskPool::get()
+        .spawn(async move {
+            // Frames after the window was resized don't fit the first.
+            let size = frames[0].size;
+            let pixels: Vec<Vec<u8>> = frames.into_iter()
+                .filter(|frame| frame.size == size)
+                .map(|frame| frame.pixels)
+                .collect();
+            match write_gif(BufWriter::new(file), size.x as u16, size.y as u16, &pixels, delay) {
+                Ok(()) => info!("wrote a {}-frame {}x{} GIF to {}", pixels.len(), size.x, size.y, path.display()),
+                Err(err) => error!("failed to write the GIF to {}: {}", path.display(), err),
+            }
+        })
+        .detach();
+}
@@ -0,0 +1,176 @@
+use std::{
+    fmt::Write as _,
+    fs,
+    io,
+    path::Path,
+};
+
+use bevy::prelude::*;
+use rand::{prelude::*, rngs::SmallRng};
+use serde::Serialize;
+
+use crate::{cli::Args, simulation::Simulation, stats::CreatureStats, Creature, Parameters, Scene};
+
+// How far each candidate's parameters stray from the base, as a fraction
+// either way, drawn evenly in log space.
+const PERTURBATION: f32 = 0.3;
+// A creature whose spread grows past this many times its settled spread has
+// exploded.
+const EXPLOSION_GROWTH: f32 = 3.0;
+const PLOT_SIZE: f32 = 400.0;
+const PLOT_MARGIN: f32 = 20.0;
+
+/// How one candidate fared in the search.
+#[derive(Serialize)]
+struct Candidate {
+    index: usize,
+    /// Net centroid displacement per step over the measured steps, scaled
+    /// down by the fraction of them it spent fragmented, or 0 if it exploded
+    fitness: f32,
+    speed: f32,
+    fragmented: f32,
+    exploded: bool,
+    parameters: Parameters,
+    /// The centroid at every step, settling included
+    trajectory: Vec<Vec2>,
+}
+
+/// Runs `--find-gliders` candidates headless, each a single creature with the
+/// base scene's parameters perturbed at random, and ranks them by how far
+/// they move per step once settled. The best are printed and written to
+/// `--glider-dir` with a plot of their trajectories.
+pub fn find_gliders(args: &Args, candidates: usize) {
+    let seed = args.seed.unwrap_or_else(|| thread_rng().gen());
+    let mut rng = SmallRng::seed_from_u64(seed);
+    let steps = args.glider_settle + args.glider_steps;
+    // Logging isn't set up until the first candidate's app is built.
+    eprintln!("searching {} candidates for gliders, {} steps each, seed {}", candidates, steps, seed);
+
+    let mut results = Vec::with_capacity(candidates);
+    for index in 0..candidates {
+        let candidate_args = Args { seed: Some(seed), headless: true, find_gliders: None, ..args.clone() };
+        let mut simulation = Simulation::new(&candidate_args);
+        let mut scene = simulation.world_mut().resource_mut::<Scene>();
+        let base = scene.creatures[0].parameters.clone();
+        // The first candidate is the base itself, to compare the rest with.
+        let parameters = if index == 0 { base } else { perturb(&base, &mut rng) };
+        scene.creatures.truncate(1);
+        scene.creatures[0].parameters = parameters.clone();
+        scene.creatures[0].animation = None;
+
+        let mut trajectory = Vec::with_capacity(steps as usize);
+        let mut settled_spread = None;
+        let mut fragmented_steps = 0;
+        let mut exploded = false;
+        for step in 0..steps {
+            simulation.step();
+            let stats = creature_stats(&mut simulation);
+            if !stats.centroid.is_finite() || !stats.spread.is_finite() {
+                exploded = true;
+                break;
+            }
+            trajectory.push(stats.centroid.truncate());
+            if step + 1 == args.glider_settle {
+                settled_spread = Some(stats.spread);
+            }
+            if let Some(settled_spread) = settled_spread.filter(|_| step >= args.glider_settle) {
+                if stats.spread > EXPLOSION_GROWTH * settled_spread {
+                    exploded = true;
+                    break;
+                }
+                if stats.clusters > 1 {
+                    fragmented_steps += 1;
+                }
+            }
+        }
+
+        let settle = args.glider_settle as usize;
+        let speed = match (trajectory.get(settle.saturating_sub(1)), trajectory.last()) {
+            (Some(start), Some(end)) if !exploded => start.distance(*end) / args.glider_steps as f32,
+            _ => 0.0,
+        };
+        let fragmented = fragmented_steps as f32 / args.glider_steps as f32;
+        let fitness = if exploded { 0.0 } else { speed * (1.0 - fragmented) };
+        info!(
+            "candidate {}: fitness {:.5}, speed {:.5}, fragmented {:.0}%{}",
+            index, fitness, speed, 100.0 * fragmented, if exploded { ", exploded" } else { "" },
+        );
+        results.push(Candidate { index, fitness, speed, fragmented, exploded, parameters, trajectory });
+    }
+
+    results.sort_by(|a, b| b.fitness.total_cmp(&a.fitness));
+    if let Err(err) = fs::create_dir_all(&args.glider_dir) {
+        error!("failed to create {}: {}", args.glider_dir.display(), err);
+    }
+    for (rank, candidate) in results.iter().take(args.glider_top).enumerate() {
+        println!(
+            "#{} candidate {}: fitness {:.5}, speed {:.5}/step, fragmented {:.0}%, {:?}",
+            rank + 1, candidate.index, candidate.fitness, candidate.speed, 100.0 * candidate.fragmented, candidate.parameters,
+        );
+        let stem = args.glider_dir.join(format!("glider-{}", rank + 1));
+        let written = fs::write(stem.with_extension("json"), serde_json::to_string_pretty(candidate).unwrap() + "\n")
+            .and_then(|_| plot_trajectory(&stem.with_extension("svg"), &candidate.trajectory, args.glider_settle as usize));
+        if let Err(err) = written {
+            error!("failed to write {}: {}", stem.display(), err);
+        }
+    }
+}
+
+fn perturb(base: &Parameters, rng: &mut SmallRng) -> Parameters {
+    let mut scale = |value: f32| value * (1.0 + PERTURBATION).powf(rng.gen_range(-1.0..=1.0));
+    let parameters = Parameters {
+        mu_k: scale(base.mu_k),
+        sigma_k: scale(base.sigma_k),
+        w_k: scale(base.w_k),
+        mu_g: scale(base.mu_g),
+        sigma_g: scale(base.sigma_g),
+        c_rep: scale(base.c_rep),
+        ..base.clone()
+    };
+    // Only a spline kernel or growth can fail, and those aren't perturbed.
+    debug_assert!(parameters.validate().is_ok());
+    parameters
+}
+
+fn creature_stats(simulation: &mut Simulation) -> CreatureStats {
+    let world = simulation.world_mut();
+    let mut creature_query = world.query_filtered::<&CreatureStats, With<Creature>>();
+    creature_query.iter(world).next().cloned().unwrap_or_default()
+}
+
+// Plots the centroid's path as an SVG, framed to fit, with the settling steps
+// faint and a dot where it ends.
+fn plot_trajectory(path: &Path, trajectory: &[Vec2], settle: usize) -> io::Result<()> {
+    let (min, max) = trajectory.iter().fold((Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)), |(min, max), point| {
+        (min.min(*point), max.max(*point))
+    });
+    let extent = (max - min).max_element().max(f32::EPSILON);
+    let scale = (PLOT_SIZE - 2.0 * PLOT_MARGIN) / extent;
+    let to_plot = |point: &Vec2| {
+        let point = (*point - min) * scale + PLOT_MARGIN;
+        format!("{:.2},{:.2}", point.x, PLOT_SIZE - point.y)
+    };
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{s}" height="{s}" viewBox="0 0 {s} {s}">"#,
+        s = PLOT_SIZE,
+    );
+    let _ = writeln!(svg, r##"<rect width="100%" height="100%" fill="#000000"/>"##);
+    let settle = settle.min(trajectory.len());
+    // The measured line starts where the settling one ends, so they join.
+    let (settling, measured) = (&trajectory[..settle], &trajectory[settle.saturating_sub(1)..]);
+    for (points, color) in [(settling, "#555555"), (measured, "#ffa500")] {
+        let points: Vec<_> = points.iter().map(to_plot).collect();
+        let _ = writeln!(svg, r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="1.5"/>"#, points.join(" "), color);
+    }
+    if let Some(end) = trajectory.last() {
+        let end = to_plot(end);
+        let (x, y) = end.split_once(',').unwrap();
+        let _ = writeln!(svg, r##"<circle cx="{}" cy="{}" r="4" fill="#ffffff"/>"##, x, y);
+    }
+    let _ = writeln!(svg, r##"<text x="8" y="16" fill="#ffffff" font-size="12">centroid over {} steps, {:.3} across</text>"##, trajectory.len(), extent);
+    svg.push_str("</svg>\n");
+    fs::write(path, svg)
+}
@@ -0,0 +1,250 @@
+use std::borrow::Cow;
+
+use bevy::{
+    diagnostic::Diagnostics,
+    prelude::*,
+    render::{
+        render_resource::{
+            BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor,
+            BindGroupLayoutEntry, BindingType, Buffer, BufferBindingType, BufferDescriptor,
+            BufferInitDescriptor, BufferUsages, CommandEncoderDescriptor, ComputePassDescriptor,
+            ComputePipeline, MapMode, PipelineLayoutDescriptor, RawComputePipelineDescriptor,
+            ShaderModuleDescriptor, ShaderSource, ShaderStages,
+        },
+        renderer::{RenderDevice, RenderQueue},
+    },
+};
+
+use crate::{
+    calculate_fields,
+    timings::{self, SystemTimer},
+    Creature, Fields, InterCreature, KernelKind, Parameters, Particle, SelfField, StepDiagnostics,
+};
+
+const WORKGROUP_SIZE: u32 = 64;
+
+// One `ParticleFields` in the shader: values, R_grad and U_grad as vec4s.
+const FIELDS_SIZE: u64 = 3 * 16;
+
+/// Computes the pair fields in a compute shader in place of `sum_pair_fields`.
+/// Positions are uploaded and the sums read back every step, so the rest of the
+/// simulation is unchanged.
+pub struct GpuFieldsPlugin;
+
+impl Plugin for GpuFieldsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, sum_pair_fields_gpu.before(calculate_fields));
+    }
+
+    // The render device only exists once the render plugin has finished.
+    fn finish(&self, app: &mut App) {
+        app.init_resource::<GpuFields>();
+    }
+}
+
+#[derive(Resource)]
+struct GpuFields {
+    pipeline: ComputePipeline,
+    layout: BindGroupLayout,
+    buffers: Option<GpuBuffers>,
+}
+
+// Storage reused between steps, reallocated when the particle or creature
+// count outgrows it.
+struct GpuBuffers {
+    particles: usize,
+    creatures: usize,
+    positions: Buffer,
+    parameters: Buffer,
+    fields: Buffer,
+    readback: Buffer,
+}
+
+impl FromWorld for GpuFields {
+    fn from_world(world: &mut World) -> Self {
+        let device = world.resource::<RenderDevice>();
+
+        let shader = device.create_shader_module(ShaderModuleDescriptor {
+            label: Some("gpu_fields"),
+            source: ShaderSource::Wgsl(Cow::Borrowed(include_str!("gpu_fields.wgsl"))),
+        });
+
+        let storage = |binding, read_only| BindGroupLayoutEntry {
+            binding,
+            visibility: ShaderStages::COMPUTE,
+            ty: BindingType::Buffer {
+                ty: BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("gpu_fields"),
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::COMPUTE,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                storage(1, true),
+                storage(2, true),
+                storage(3, false),
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("gpu_fields"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_compute_pipeline(&RawComputePipelineDescriptor {
+            label: Some("gpu_fields"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point: "sum_pair_fields",
+        });
+
+        GpuFields {
+            pipeline,
+            layout,
+            buffers: None,
+        }
+    }
+}
+
+impl GpuBuffers {
+    fn new(device: &RenderDevice, particles: usize, creatures: usize) -> Self {
+        let buffer = |label, size, usage| device.create_buffer(&BufferDescriptor {
+            label: Some(label),
+            size,
+            usage,
+            mapped_at_creation: false,
+        });
+        let fields_size = particles as u64 * FIELDS_SIZE;
+
+        GpuBuffers {
+            particles,
+            creatures,
+            positions: buffer("gpu_fields_positions", particles as u64 * 16, BufferUsages::STORAGE | BufferUsages::COPY_DST),
+            parameters: buffer("gpu_fields_parameters", creatures as u64 * 32, BufferUsages::STORAGE | BufferUsages::COPY_DST),
+            fields: buffer("gpu_fields_fields", fields_size, BufferUsages::STORAGE | BufferUsages::COPY_SRC),
+            readback: buffer("gpu_fields_readback", fields_size, BufferUsages::MAP_READ | BufferUsages::COPY_DST),
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn sum_pair_fields_gpu(
+    mut timers: Diagnostics,
+    device: Res<RenderDevice>,
+    queue: Res<RenderQueue>,
+    mut gpu: ResMut<GpuFields>,
+    inter_creature: Res<InterCreature>,
+    self_field: Res<SelfField>,
+    mut diagnostics: ResMut<StepDiagnostics>,
+    creature_query: Query<(&Transform, &Parameters, &Children), With<Creature>>,
+    mut particle_query: Query<(&Transform, &mut Fields), With<Particle>>,
+) {
+    let _timer = SystemTimer::start(&mut timers, timings::SUM_PAIR_FIELDS);
+
+    let mut entities = Vec::new();
+    let mut positions: Vec<[f32; 4]> = Vec::new();
+    let mut parameters: Vec<[f32; 8]> = Vec::new();
+
+    for (index, (creature_transform, creature_parameters, children)) in creature_query.iter().enumerate() {
+        let kernel = match creature_parameters.kernel {
+            KernelKind::Quartic => 1,
+            KernelKind::TwoRings => 2,
+            // app() refuses --gpu when a creature has a spline.
+            KernelKind::Gaussian | KernelKind::Spline(_) => 0,
+        };
+        let kernel = f32::from_bits(kernel);
+        parameters.push([
+            creature_parameters.mu_k, creature_parameters.sigma_k, creature_parameters.w_k, creature_parameters.c_rep,
+            kernel, 0.0, 0.0, 0.0,
+        ]);
+        for child in children.iter() {
+            let Ok((transform, _)) = particle_query.get(*child) else {
+                continue;
+            };
+            let x = creature_transform.translation + transform.translation;
+            entities.push(*child);
+            positions.push([x.x, x.y, x.z, f32::from_bits(index as u32)]);
+        }
+    }
+    if positions.is_empty() {
+        diagnostics.min_pair_distance = f32::INFINITY;
+        return;
+    }
+
+    let gpu = &mut *gpu;
+    if gpu.buffers.as_ref().is_none_or(|buffers| buffers.particles < positions.len() || buffers.creatures < parameters.len()) {
+        gpu.buffers = Some(GpuBuffers::new(&device, positions.len().next_power_of_two(), parameters.len().next_power_of_two()));
+    }
+    let buffers = gpu.buffers.as_ref().unwrap();
+
+    let uniforms = device.create_buffer_with_data(&BufferInitDescriptor {
+        label: Some("gpu_fields_uniforms"),
+        contents: bytemuck::cast_slice(&[positions.len() as u32, inter_creature.enabled as u32, self_field.0 as u32, 0]),
+        usage: BufferUsages::UNIFORM,
+    });
+    queue.write_buffer(&buffers.positions, 0, bytemuck::cast_slice(&positions));
+    queue.write_buffer(&buffers.parameters, 0, bytemuck::cast_slice(&parameters));
+
+    let bind_group = device.create_bind_group(&BindGroupDescriptor {
+        label: Some("gpu_fields"),
+        layout: &gpu.layout,
+        entries: &[
+            BindGroupEntry { binding: 0, resource: uniforms.as_entire_binding() },
+            BindGroupEntry { binding: 1, resource: buffers.positions.as_entire_binding() },
+            BindGroupEntry { binding: 2, resource: buffers.parameters.as_entire_binding() },
+            BindGroupEntry { binding: 3, resource: buffers.fields.as_entire_binding() },
+        ],
+    });
+
+    let fields_size = positions.len() as u64 * FIELDS_SIZE;
+    let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor { label: Some("gpu_fields") });
+    {
+        let mut pass = encoder.begin_compute_pass(&ComputePassDescriptor { label: Some("gpu_fields") });
+        pass.set_pipeline(&gpu.pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.dispatch_workgroups((positions.len() as u32).div_ceil(WORKGROUP_SIZE), 1, 1);
+    }
+    encoder.copy_buffer_to_buffer(&buffers.fields, 0, &buffers.readback, 0, fields_size);
+    queue.submit([encoder.finish()]);
+
+    // Block on the readback; the next systems need the fields this step.
+    let slice = buffers.readback.slice(..fields_size);
+    slice.map_async(MapMode::Read, |result| {
+        if let Err(err) = result {
+            error!("failed to map the GPU fields: {}", err);
+        }
+    });
+    device.poll(wgpu::Maintain::Wait);
+
+    let mut min_pair_distance = f32::INFINITY;
+    {
+        let mapped = slice.get_mapped_range();
+        let values: &[[f32; 4]] = bytemuck::cast_slice(&mapped);
+        for (entity, [sums, R_grad, U_grad]) in entities.iter().zip(values.chunks_exact(3).map(|chunk| [chunk[0], chunk[1], chunk[2]])) {
+            let Ok((_, mut fields)) = particle_query.get_mut(*entity) else {
+                continue;
+            };
+            fields.R_val = sums[0];
+            fields.U_val = sums[1];
+            fields.R_grad = Vec3::new(R_grad[0], R_grad[1], R_grad[2]);
+            fields.U_grad = Vec3::new(U_grad[0], U_grad[1], U_grad[2]);
+            fields.E_grad = Vec3::ZERO;
+            min_pair_distance = min_pair_distance.min(sums[2]);
+        }
+    }
+    buffers.readback.unmap();
+
+    diagnostics.min_pair_distance = min_pair_distance;
+}
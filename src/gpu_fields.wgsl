@@ -0,0 +1,147 @@
+// Pair sums of the repulsion and kernel fields, one invocation per particle.
+// Mirrors `sum_pair_fields` in src/lib.rs, which stays the reference.
+
+struct Uniforms {
+    count: u32,
+    inter_creature: u32,
+    self_field: u32,
+    _padding: u32,
+}
+
+struct CreatureParameters {
+    // (mu_k, sigma_k, w_k, c_rep)
+    values: vec4<f32>,
+    // Kernel kind in x, in the order of `KernelKind`.
+    kernel: vec4<u32>,
+}
+
+struct ParticleFields {
+    // (R_val, U_val, nearest neighbour distance, unused)
+    values: vec4<f32>,
+    R_grad: vec4<f32>,
+    U_grad: vec4<f32>,
+}
+
+@group(0) @binding(0) var<uniform> uniforms: Uniforms;
+// World position in xyz, creature index bit-cast into w.
+@group(0) @binding(1) var<storage, read> positions: array<vec4<f32>>;
+@group(0) @binding(2) var<storage, read> parameters: array<CreatureParameters>;
+@group(0) @binding(3) var<storage, read_write> fields: array<ParticleFields>;
+
+const TILE_SIZE: u32 = 64u;
+
+var<workgroup> tile: array<vec4<f32>, TILE_SIZE>;
+
+fn repulsion_field(r: f32, c_rep: f32) -> vec2<f32> {
+    let t = max(0.0, 1.0 - r);
+    return vec2<f32>(0.5 * c_rep * t * t, -c_rep * t);
+}
+
+fn radial_field(x: f32, mu: f32, sigma: f32, w: f32) -> vec2<f32> {
+    let t = (x - mu) / sigma;
+    let y = w * exp(-t * t);
+    if y == 0.0 {
+        return vec2<f32>(0.0, 0.0);
+    }
+    return vec2<f32>(y, -2.0 * t * y / sigma);
+}
+
+fn kernel_field(kind: u32, x: f32, mu: f32, sigma: f32, w: f32) -> vec2<f32> {
+    switch kind {
+        case 1u: {
+            let t = (x - mu) / sigma;
+            let s = max(0.0, 1.0 - 0.25 * t * t);
+            return vec2<f32>(w * s * s * s * s, -2.0 * w * t * s * s * s / sigma);
+        }
+        case 2u: {
+            return radial_field(x, 0.5 * mu, sigma, w) + radial_field(x, mu, sigma, w);
+        }
+        default: {
+            return radial_field(x, mu, sigma, w);
+        }
+    }
+}
+
+@compute @workgroup_size(64)
+fn sum_pair_fields(
+    @builtin(global_invocation_id) global_id: vec3<u32>,
+    @builtin(local_invocation_id) local_id: vec3<u32>,
+) {
+    let i = global_id.x;
+    let in_range = i < uniforms.count;
+
+    var x_i = vec4<f32>(0.0);
+    var creature_i = 0u;
+    var parameters_i = vec4<f32>(0.0);
+    var kernel_i = 0u;
+    if in_range {
+        x_i = positions[i];
+        creature_i = bitcast<u32>(x_i.w);
+        parameters_i = parameters[creature_i].values;
+        kernel_i = parameters[creature_i].kernel.x;
+    }
+
+    var R_val = 0.0;
+    var U_val = 0.0;
+    if uniforms.self_field != 0u {
+        R_val = repulsion_field(0.0, parameters_i.w).x;
+        U_val = kernel_field(kernel_i, 0.0, parameters_i.x, parameters_i.y, parameters_i.z).x;
+    }
+    var R_grad = vec3<f32>(0.0);
+    var U_grad = vec3<f32>(0.0);
+    var min_r = 3.4e38;
+
+    // Every invocation in the workgroup loads one particle of each tile, so the
+    // inner loop reads positions from workgroup memory instead of storage.
+    for (var base = 0u; base < uniforms.count; base += TILE_SIZE) {
+        let j = base + local_id.x;
+        if j < uniforms.count {
+            tile[local_id.x] = positions[j];
+        }
+        workgroupBarrier();
+
+        if in_range {
+            let n = min(TILE_SIZE, uniforms.count - base);
+            for (var k = 0u; k < n; k++) {
+                if base + k == i {
+                    continue;
+                }
+                let x_j = tile[k];
+                let creature_j = bitcast<u32>(x_j.w);
+                let same_creature = creature_j == creature_i;
+                if !same_creature && uniforms.inter_creature == 0u {
+                    continue;
+                }
+
+                let r = distance(x_i.xyz, x_j.xyz);
+                let r_grad = (x_i.xyz - x_j.xyz) / r;
+                min_r = min(min_r, r);
+
+                if r < 1.0 {
+                    var c_rep = parameters_i.w;
+                    if !same_creature {
+                        c_rep = 0.5 * (parameters_i.w + parameters[creature_j].values.w);
+                    }
+                    let R = repulsion_field(r, c_rep);
+                    R_val += R.x;
+                    R_grad += r_grad * R.y;
+                }
+
+                if same_creature {
+                    let K = kernel_field(kernel_i, r, parameters_i.x, parameters_i.y, parameters_i.z);
+                    U_val += K.x;
+                    U_grad += r_grad * K.y;
+                }
+            }
+        }
+        workgroupBarrier();
+    }
+
+    if in_range {
+        fields[i] = ParticleFields(
+            vec4<f32>(R_val, U_val, min_r, 0.0),
+            vec4<f32>(R_grad, 0.0),
+            vec4<f32>(U_grad, 0.0),
+        );
+    }
+}
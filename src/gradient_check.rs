@@ -0,0 +1,152 @@
+use std::mem;
+
+use bevy::prelude::*;
+
+use crate::{kernel_editor::KernelEditor, Creature, GrowthKind, KernelKind, Parameters};
+
+// Points each curve is checked at, and the central difference's step as a
+// fraction of the range they span.
+const SAMPLES: usize = 256;
+const STEP_FRACTION: f64 = 1e-3;
+// Widths past the bumps' centers the checked ranges reach.
+const REACH: f32 = 4.0;
+
+/// Compares the derivative each kernel returns with a central difference of
+/// the values it returns, and warns where they disagree by more than
+/// `tolerance`, relative to the largest derivative along the curve. Each
+/// choice of interaction kernel, growth kernel and growth mapping is checked
+/// the first time a creature has it, whether it's spawned with it or edited
+/// into it. A spline dragged in the kernel editor is checked once it's let go.
+pub struct GradientCheckPlugin {
+    pub tolerance: f32,
+}
+
+type Kinds = (KernelKind, KernelKind, GrowthKind);
+
+#[derive(Resource)]
+struct GradientCheck {
+    tolerance: f32,
+    // Only the latest spline of each combination of kinds is kept.
+    checked: Vec<Kinds>,
+    // Creatures whose parameters changed since they were last checked.
+    pending: Vec<Entity>,
+}
+
+impl Plugin for GradientCheckPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(GradientCheck { tolerance: self.tolerance, checked: Vec::new(), pending: Vec::new() })
+            .add_systems(Update, check_kernel_gradients);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn check_kernel_gradients(
+    mut check: ResMut<GradientCheck>,
+    editor: Option<Res<KernelEditor>>,
+    changed_query: Query<Entity, (With<Creature>, Changed<Parameters>)>,
+    creature_query: Query<&Parameters, With<Creature>>,
+) {
+    for creature in changed_query.iter() {
+        if !check.pending.contains(&creature) {
+            check.pending.push(creature);
+        }
+    }
+    if editor.is_some_and(|editor| editor.dragging()) {
+        return;
+    }
+
+    for creature in mem::take(&mut check.pending) {
+        let Ok(parameters) = creature_query.get(creature) else {
+            continue;
+        };
+        let key = (parameters.kernel.clone(), parameters.growth_kernel.clone(), parameters.growth);
+        if check.checked.contains(&key) {
+            continue;
+        }
+
+        let curves: [(&str, (f32, f32), &dyn Fn(f32) -> (f32, f32)); 2] = [
+            ("interaction kernel", kernel_range(parameters), &|r| parameters.kernel_field(r)),
+            ("growth mapping", growth_range(parameters), &|U| parameters.growth_field(U)),
+        ];
+        for (name, range, field) in curves {
+            if let Some(mismatch) = worst_mismatch(field, range).filter(|mismatch| mismatch.relative_error > check.tolerance as f64) {
+                warn!(
+                    ?creature,
+                    at = mismatch.x,
+                    analytic = mismatch.analytic,
+                    numerical = mismatch.numerical,
+                    "the {}'s derivative disagrees with a finite difference of its values by {:.1e} of its largest",
+                    name, mismatch.relative_error,
+                );
+            }
+        }
+        if is_spline(&key.0) || is_spline(&key.1) {
+            check.checked.retain(|checked| !same_kinds(checked, &key));
+        }
+        check.checked.push(key);
+    }
+}
+
+fn is_spline(kernel: &KernelKind) -> bool {
+    matches!(kernel, KernelKind::Spline(_))
+}
+
+// Whether both are the same kinds, whatever their splines or wells.
+fn same_kinds(a: &Kinds, b: &Kinds) -> bool {
+    mem::discriminant(&a.0) == mem::discriminant(&b.0)
+        && mem::discriminant(&a.1) == mem::discriminant(&b.1)
+        && mem::discriminant(&a.2) == mem::discriminant(&b.2)
+}
+
+// Distances the kernel is checked over, out to where it's negligible.
+fn kernel_range(parameters: &Parameters) -> (f32, f32) {
+    let reach = match &parameters.kernel {
+        KernelKind::Spline(points) => points.last().map_or(1.0, |(x, _)| *x) * 1.1,
+        _ => parameters.mu_k + REACH * parameters.sigma_k,
+    };
+    (0.0, reach.max(1.0))
+}
+
+// Densities the growth mapping is checked over, around each of its wells.
+fn growth_range(parameters: &Parameters) -> (f32, f32) {
+    let wells = match parameters.growth {
+        GrowthKind::SingleGaussian => vec![(parameters.mu_g, parameters.sigma_g)],
+        GrowthKind::DoubleWell { mu_1, sigma_1, mu_2, sigma_2 } => vec![(mu_1, sigma_1), (mu_2, sigma_2)],
+    };
+    let reach = match &parameters.growth_kernel {
+        KernelKind::Spline(points) => points.last().map_or(1.0, |(x, _)| *x) * 1.1,
+        _ => wells.iter().map(|(mu, sigma)| mu + REACH * sigma).fold(0.0, f32::max),
+    };
+    let low = wells.iter().map(|(mu, sigma)| mu - REACH * sigma).fold(0.0, f32::min);
+    (low, reach.max(low + 1.0))
+}
+
+struct Mismatch {
+    x: f32,
+    analytic: f32,
+    numerical: f64,
+    relative_error: f64,
+}
+
+// The point where `field`'s derivative is furthest from a central difference
+// of its values, against the largest derivative on `range`.
+fn worst_mismatch(field: &dyn Fn(f32) -> (f32, f32), (low, high): (f32, f32)) -> Option<Mismatch> {
+    let h = STEP_FRACTION * (high - low) as f64;
+    let samples: Vec<(f32, f32, f64)> = (0..SAMPLES)
+        .map(|i| {
+            let x = low + (high - low) * (i as f32 + 0.5) / SAMPLES as f32;
+            // Over the step the f32 inputs actually span.
+            let (above, below) = ((x as f64 + h) as f32, (x as f64 - h) as f32);
+            let numerical = (field(above).0 as f64 - field(below).0 as f64) / (above as f64 - below as f64);
+            (x, field(x).1, numerical)
+        })
+        .filter(|(_, analytic, numerical)| analytic.is_finite() && numerical.is_finite())
+        .collect();
+    let scale = samples.iter().map(|(_, analytic, numerical)| (*analytic as f64).abs().max(numerical.abs())).fold(0.0, f64::max);
+    if scale <= 0.0 {
+        return None;
+    }
+    samples.into_iter()
+        .map(|(x, analytic, numerical)| Mismatch { x, analytic, numerical, relative_error: (analytic as f64 - numerical).abs() / scale })
+        .max_by(|a, b| a.relative_error.total_cmp(&b.relative_error))
+}
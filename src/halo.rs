@@ -0,0 +1,62 @@
+use bevy::prelude::*;
+
+use crate::{
+    culling::{is_visible, ViewBounds},
+    effective_radius, update_position, Creature, Parameters, Particle,
+};
+
+/// 0 toggles a faint ring around each visible particle at its creature's
+/// kernel reach, `mu_k + sigmas * sigma_k`, showing how far its influence
+/// extends and where neighbors' overlap.
+pub struct HaloPlugin {
+    pub enabled: bool,
+    pub sigmas: f32,
+    pub alpha: f32,
+}
+
+#[derive(Resource)]
+struct Halos {
+    enabled: bool,
+    sigmas: f32,
+    alpha: f32,
+}
+
+impl Plugin for HaloPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Halos { enabled: self.enabled, sigmas: self.sigmas.max(0.0), alpha: self.alpha.clamp(0.0, 1.0) })
+            .add_systems(Update, (toggle_halos, draw_halos.after(update_position)));
+    }
+}
+
+fn toggle_halos(keys: Res<Input<KeyCode>>, mut halos: ResMut<Halos>) {
+    if keys.just_pressed(KeyCode::Key0) {
+        halos.enabled = !halos.enabled;
+        info!("interaction halos {}", if halos.enabled { "enabled" } else { "disabled" });
+    }
+}
+
+fn draw_halos(
+    halos: Res<Halos>,
+    view_bounds: ViewBounds,
+    mut gizmos: Gizmos,
+    creature_query: Query<(&Transform, &Parameters, &Children), With<Creature>>,
+    particle_query: Query<&Transform, With<Particle>>,
+) {
+    if !halos.enabled {
+        return;
+    }
+    let visible_rects = view_bounds.rects();
+    let color = Color::WHITE.with_a(halos.alpha);
+
+    for (creature_transform, parameters, children) in creature_query.iter() {
+        // The tolerance at which the Gaussian falls off exactly `sigmas` widths
+        // out, so this is mu_k + sigmas * sigma_k unless repulsion reaches further.
+        let radius = effective_radius(parameters, (-halos.sigmas * halos.sigmas).exp());
+        for transform in particle_query.iter_many(children) {
+            let center = (creature_transform.translation + transform.translation).truncate();
+            if is_visible(&visible_rects, center) {
+                gizmos.circle_2d(center, radius, color);
+            }
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use std::time::{Duration, Instant};
+
+use bevy::{app::AppExit, prelude::*};
+
+use crate::SimStep;
+
+pub struct HeadlessPlugin {
+    pub steps: u64,
+}
+
+#[derive(Resource)]
+pub struct StepLimit(u64);
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(StepLimit(self.steps))
+            .add_systems(Last, exit_after_steps);
+    }
+}
+
+pub fn exit_after_steps(
+    step: Res<SimStep>,
+    limit: Res<StepLimit>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if step.0 >= limit.0 {
+        exit.send(AppExit);
+    }
+}
+
+/// Logs percentiles of the wall-clock time per step when the app exits.
+pub struct StepTimingPlugin;
+
+#[derive(Resource, Default)]
+struct StepTimes {
+    last: Option<Instant>,
+    samples: Vec<Duration>,
+}
+
+impl Plugin for StepTimingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<StepTimes>()
+            .add_systems(Last, (time_step, report_step_times.after(exit_after_steps)).chain());
+    }
+}
+
+// Measured between consecutive frames, so the first sample starts after the
+// startup systems have spawned the scene.
+fn time_step(mut times: ResMut<StepTimes>) {
+    let now = Instant::now();
+    if let Some(last) = times.last {
+        times.samples.push(now - last);
+    }
+    times.last = Some(now);
+}
+
+fn report_step_times(mut times: ResMut<StepTimes>, mut exit: EventReader<AppExit>) {
+    if exit.iter().next().is_none() || times.samples.is_empty() {
+        return;
+    }
+
+    let samples = &mut times.samples;
+    samples.sort();
+    let percentile = |p: usize| samples[(samples.len() * p / 100).min(samples.len() - 1)];
+    let mean = samples.iter().sum::<Duration>() / samples.len() as u32;
+    info!(
+        "{} steps: mean {:?}, p50 {:?}, p90 {:?}, p99 {:?}, max {:?}",
+        samples.len(), mean, percentile(50), percentile(90), percentile(99), samples[samples.len() - 1],
+    );
+}
@@ -0,0 +1,181 @@
+use std::sync::{Arc, Mutex};
+
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+    tasks::AsyncComputeTaskPool,
+};
+
+use crate::{
+    colormap::{Colormap, Colormaps},
+    culling::ViewBounds,
+    Creature, Parameters, Particle,
+};
+
+// Depth of the heatmap, behind every particle.
+const HEATMAP_Z: f32 = -10.0;
+
+/// U toggles a heatmap of the U field over the camera's view, each texel
+/// summing the kernel of every particle at its center. It's sampled on the
+/// async compute pool from a copy of the particles, so the main loop never
+/// waits on it. Until a sample finishes, the last one stays up where it was
+/// taken, which can leave it a few frames behind the particles.
+pub struct HeatmapPlugin {
+    pub enabled: bool,
+    /// Texels along the longer side of the view
+    pub resolution: u32,
+    /// Frames between starting samples
+    pub every: u32,
+}
+
+// A finished sample: its texels and the world rectangle they cover.
+struct Sample {
+    rect: Rect,
+    size: UVec2,
+    texels: Vec<u8>,
+}
+
+#[derive(Resource)]
+struct Heatmap {
+    enabled: bool,
+    resolution: u32,
+    every: u32,
+    frames_since_sample: u32,
+    // Set while a sample is being taken, and holding it once it's done.
+    pending: Option<Arc<Mutex<Option<Sample>>>>,
+}
+
+#[derive(Component)]
+struct HeatmapSprite;
+
+impl Plugin for HeatmapPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Heatmap {
+                enabled: self.enabled,
+                resolution: self.resolution.max(2),
+                every: self.every.max(1),
+                frames_since_sample: 0,
+                pending: None,
+            })
+            .add_systems(Startup, spawn_heatmap_sprite)
+            .add_systems(Update, (toggle_heatmap, start_sample, show_sample).chain());
+    }
+}
+
+fn spawn_heatmap_sprite(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    commands.spawn((
+        SpriteBundle {
+            texture: images.add(Image::default()),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        HeatmapSprite,
+    ));
+}
+
+fn toggle_heatmap(
+    keys: Res<Input<KeyCode>>,
+    mut heatmap: ResMut<Heatmap>,
+    mut sprite_query: Query<&mut Visibility, With<HeatmapSprite>>,
+) {
+    if !keys.just_pressed(KeyCode::U) {
+        return;
+    }
+    heatmap.enabled = !heatmap.enabled;
+    heatmap.frames_since_sample = heatmap.every;
+    // Shown again once a fresh sample is in, rather than with a stale one.
+    for mut visibility in sprite_query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+fn start_sample(
+    mut heatmap: ResMut<Heatmap>,
+    colormaps: Res<Colormaps>,
+    view_bounds: ViewBounds,
+    creature_query: Query<(&Transform, &Parameters, &Children), With<Creature>>,
+    particle_query: Query<&Transform, With<Particle>>,
+) {
+    heatmap.frames_since_sample += 1;
+    if !heatmap.enabled || heatmap.pending.is_some() || heatmap.frames_since_sample < heatmap.every {
+        return;
+    }
+    let Some(rect) = view_bounds.view_rect() else {
+        return;
+    };
+    heatmap.frames_since_sample = 0;
+
+    let creatures: Vec<(Parameters, Vec<Vec2>)> = creature_query.iter()
+        .map(|(creature_transform, parameters, children)| {
+            let offset = creature_transform.translation.truncate();
+            (parameters.clone(), particle_query.iter_many(children).map(|transform| offset + transform.translation.truncate()).collect())
+        })
+        .collect();
+    let size = if rect.width() >= rect.height() {
+        UVec2::new(heatmap.resolution, ((heatmap.resolution as f32 * rect.height() / rect.width()).round() as u32).max(1))
+    } else {
+        UVec2::new(((heatmap.resolution as f32 * rect.width() / rect.height()).round() as u32).max(1), heatmap.resolution)
+    };
+    let colormap = colormaps.current().clone();
+
+    let slot = Arc::new(Mutex::new(None));
+    let task_slot = Arc::clone(&slot);
+    AsyncComputeTaskPool::get()
+        .spawn(async move {
+            let texels = sample_field(&creatures, rect, size, &colormap);
+            *task_slot.lock().unwrap() = Some(Sample { rect, size, texels });
+        })
+        .detach();
+    heatmap.pending = Some(slot);
+}
+
+// RGBA texels of U over `rect`, top row first, normalized to the sample's
+// own range.
+fn sample_field(creatures: &[(Parameters, Vec<Vec2>)], rect: Rect, size: UVec2, colormap: &Colormap) -> Vec<u8> {
+    let texel = rect.size() / size.as_vec2();
+    let values: Vec<f32> = (0..size.y)
+        .flat_map(|row| (0..size.x).map(move |column| (column, row)))
+        .map(|(column, row)| {
+            let x = Vec2::new(rect.min.x + (column as f32 + 0.5) * texel.x, rect.max.y - (row as f32 + 0.5) * texel.y);
+            creatures.iter()
+                .map(|(parameters, positions)| positions.iter().map(|p| parameters.kernel_field(x.distance(*p)).0).sum::<f32>())
+                .sum()
+        })
+        .collect();
+
+    let (min, max) = values.iter().fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), value| (min.min(*value), max.max(*value)));
+    let range = (max - min).max(f32::EPSILON);
+    values.into_iter()
+        .flat_map(|value| colormap.sample((value - min) / range).as_rgba_u8())
+        .collect()
+}
+
+fn show_sample(
+    mut heatmap: ResMut<Heatmap>,
+    mut images: ResMut<Assets<Image>>,
+    mut sprite_query: Query<(&Handle<Image>, &mut Sprite, &mut Transform, &mut Visibility), With<HeatmapSprite>>,
+) {
+    let Some(sample) = heatmap.pending.as_ref().and_then(|slot| slot.lock().unwrap().take()) else {
+        return;
+    };
+    heatmap.pending = None;
+    // Toggled off while it was being taken.
+    if !heatmap.enabled {
+        return;
+    }
+
+    for (texture, mut sprite, mut transform, mut visibility) in sprite_query.iter_mut() {
+        let Some(image) = images.get_mut(texture) else {
+            continue;
+        };
+        *image = Image::new(
+            Extent3d { width: sample.size.x, height: sample.size.y, depth_or_array_layers: 1 },
+            TextureDimension::D2,
+            sample.texels.clone(),
+            TextureFormat::Rgba8UnormSrgb,
+        );
+        sprite.custom_size = Some(sample.rect.size());
+        transform.translation = sample.rect.center().extend(HEATMAP_Z);
+        *visibility = Visibility::Inherited;
+    }
+}
@@ -0,0 +1,120 @@
+use std::fmt::Write;
+
+use bevy::prelude::*;
+
+use crate::{
+    picking::{nearest_particle, Cursor},
+    selection::PICK_RADIUS,
+    Creature, Fields, GrowthKind, GrowthOverride, Parameters, Particle, Velocity,
+};
+
+/// Particle whose fields are shown in the inspector panel, if any.
+#[derive(Resource, Default)]
+pub struct Inspected(pub Option<Entity>);
+
+#[derive(Component)]
+struct InspectorText;
+
+pub struct InspectorPlugin;
+
+impl Plugin for InspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Inspected>()
+            .add_systems(Startup, spawn_inspector)
+            .add_systems(Update, (inspect_particle, update_inspector, draw_inspected).chain());
+    }
+}
+
+fn spawn_inspector(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section("", TextStyle {
+            font_size: 16.0,
+            color: Color::WHITE,
+            ..default()
+        })
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.0),
+            right: Val::Px(8.0),
+            ..default()
+        }),
+        InspectorText,
+    ));
+}
+
+// I inspects the particle under the cursor, or stops inspecting over empty space.
+fn inspect_particle(
+    keys: Res<Input<KeyCode>>,
+    cursor: Cursor,
+    mut inspected: ResMut<Inspected>,
+    creature_query: Query<(Entity, &Transform, &Children), With<Creature>>,
+    particle_query: Query<&Transform, With<Particle>>,
+) {
+    if !keys.just_pressed(KeyCode::I) {
+        return;
+    }
+    let Some(point) = cursor.world_position() else {
+        return;
+    };
+
+    inspected.0 = nearest_particle(point, PICK_RADIUS, &creature_query, &particle_query)
+        .map(|(_, particle)| particle);
+}
+
+fn update_inspector(
+    mut inspected: ResMut<Inspected>,
+    creature_query: Query<(&Transform, &Parameters), With<Creature>>,
+    particle_query: Query<(&Parent, &Transform, &Fields, Option<&Velocity>), With<Particle>>,
+    growth_query: Query<&GrowthOverride, With<Particle>>,
+    mut inspector_query: Query<(&mut Text, &mut Visibility), With<InspectorText>>,
+) {
+    let mut text = String::new();
+    if let Some(entity) = inspected.0 {
+        match particle_query.get(entity) {
+            Ok((parent, transform, fields, velocity)) => {
+                let (creature_transform, parameters) = creature_query.get(parent.get())
+                    .map_or((None, None), |(transform, parameters)| (Some(transform), Some(parameters)));
+                let position = creature_transform.map_or(Vec3::ZERO, |transform| transform.translation) + transform.translation;
+
+                let _ = writeln!(text, "particle {:?} of creature {:?}", entity, parent.get());
+                let _ = writeln!(text, "position {:.4}", position.truncate());
+                if let Some(velocity) = velocity {
+                    let _ = writeln!(text, "velocity {:.4}", velocity.0.truncate());
+                }
+                let _ = writeln!(text, "R_val {:.4}  R_grad {:.4}", fields.R_val, fields.R_grad.truncate());
+                let _ = writeln!(text, "U_val {:.4}  U_grad {:.4}", fields.U_val, fields.U_grad.truncate());
+                let _ = writeln!(text, "E_grad {:.4}", fields.E_grad.truncate());
+                if let Some(parameters) = parameters {
+                    let _ = writeln!(text, "mu_k {} sigma_k {} w_k {}", parameters.mu_k, parameters.sigma_k, parameters.w_k);
+                    let _ = writeln!(text, "mu_g {} sigma_g {} c_rep {}", parameters.mu_g, parameters.sigma_g, parameters.c_rep);
+                    if parameters.growth != GrowthKind::SingleGaussian {
+                        let _ = writeln!(text, "growth {:?}", parameters.growth);
+                    }
+                }
+                if let Ok(growth) = growth_query.get(entity) {
+                    let _ = writeln!(text, "painted mu_g {} sigma_g {}", growth.mu_g, growth.sigma_g);
+                }
+            }
+            // The particle was despawned, e.g. to stay within the particle budget.
+            Err(_) => inspected.0 = None,
+        }
+    }
+
+    for (mut inspector, mut visibility) in inspector_query.iter_mut() {
+        inspector.sections[0].value.clone_from(&text);
+        *visibility = if text.is_empty() { Visibility::Hidden } else { Visibility::Inherited };
+    }
+}
+
+fn draw_inspected(
+    mut gizmos: Gizmos,
+    inspected: Res<Inspected>,
+    creature_query: Query<&Transform, With<Creature>>,
+    particle_query: Query<(&Parent, &Transform), With<Particle>>,
+) {
+    let Some((parent, transform)) = inspected.0.and_then(|entity| particle_query.get(entity).ok()) else {
+        return;
+    };
+    let offset = creature_query.get(parent.get()).map_or(Vec3::ZERO, |transform| transform.translation);
+    gizmos.circle_2d((offset + transform.translation).truncate(), 0.75, Color::CYAN);
+}
@@ -0,0 +1,201 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{picking::ScreenSpace, selection::Selection, spline_field, Creature, KernelKind, Parameters};
+
+// Panel size and its distance from the bottom-left corner, in logical pixels.
+const PANEL_SIZE: Vec2 = Vec2::new(320.0, 180.0);
+const PANEL_MARGIN: f32 = 16.0;
+// How close to a control point a click has to be to grab it.
+const GRAB_RADIUS: f32 = 8.0;
+const HANDLE_RADIUS: f32 = 4.0;
+// Control points a Gaussian, quartic or two-ring kernel is sampled at.
+const CONTROL_POINTS: usize = 9;
+// Control points can't be dragged closer together than this along r.
+const MIN_GAP: f32 = 0.05;
+const CURVE_SAMPLES: usize = 128;
+
+/// J opens a plot of the interaction kernel K(r) / w_k in the bottom-left
+/// corner. Dragging its control points turns the kernel of the selected
+/// creatures, or of every creature when none are selected, into a spline
+/// through them, which the next step already uses.
+pub struct KernelEditorPlugin;
+
+#[derive(Resource, Default)]
+pub struct KernelEditor {
+    open: bool,
+    points: Vec<(f32, f32)>,
+    max_r: f32,
+    values: (f32, f32),
+    dragging: Option<usize>,
+    panel: Option<Rect>,
+}
+
+impl KernelEditor {
+    /// Whether `point`, in logical window coordinates, is over the open panel.
+    pub fn covers(&self, point: Vec2) -> bool {
+        self.open && self.panel.is_some_and(|panel| panel.contains(point))
+    }
+
+    /// Whether a control point is being dragged, changing the kernel every frame.
+    pub fn dragging(&self) -> bool {
+        self.dragging.is_some()
+    }
+
+    fn screen_point(&self, panel: Rect, (r, value): (f32, f32)) -> Vec2 {
+        let (min, max) = self.values;
+        Vec2::new(
+            panel.min.x + r / self.max_r * panel.width(),
+            panel.max.y - (value - min) / (max - min) * panel.height(),
+        )
+    }
+
+    fn plot_point(&self, panel: Rect, point: Vec2) -> (f32, f32) {
+        let (min, max) = self.values;
+        (
+            (point.x - panel.min.x) / panel.width() * self.max_r,
+            min + (panel.max.y - point.y) / panel.height() * (max - min),
+        )
+    }
+}
+
+impl Plugin for KernelEditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<KernelEditor>()
+            .add_systems(Update, (toggle_editor, drag_control_points, draw_editor).chain());
+    }
+}
+
+fn panel_rect(window: &Window) -> Rect {
+    let min = Vec2::new(PANEL_MARGIN, window.height() - PANEL_MARGIN - PANEL_SIZE.y);
+    Rect::from_corners(min, min + PANEL_SIZE)
+}
+
+// Control points of the kernel as it is now, with `w` factored out.
+fn control_points(parameters: &Parameters) -> Vec<(f32, f32)> {
+    if let KernelKind::Spline(points) = &parameters.kernel {
+        return points.clone();
+    }
+    let max_r = parameters.mu_k + 3.0 * parameters.sigma_k;
+    (0..CONTROL_POINTS)
+        .map(|i| {
+            let r = max_r * i as f32 / (CONTROL_POINTS - 1) as f32;
+            (r, parameters.kernel.field(r, parameters.mu_k, parameters.sigma_k, 1.0).0)
+        })
+        .collect()
+}
+
+fn toggle_editor(
+    keys: Res<Input<KeyCode>>,
+    selection: Res<Selection>,
+    mut editor: ResMut<KernelEditor>,
+    creature_query: Query<(Entity, &Parameters), With<Creature>>,
+) {
+    if !keys.just_pressed(KeyCode::J) {
+        return;
+    }
+    editor.open = !editor.open;
+    editor.dragging = None;
+    if !editor.open {
+        return;
+    }
+
+    let edited = selection.0.iter().find_map(|entity| creature_query.get(*entity).ok())
+        .or_else(|| creature_query.iter().min_by_key(|(entity, _)| *entity));
+    let Some((_, parameters)) = edited else {
+        editor.open = false;
+        return;
+    };
+
+    // Room past the last point to drag it outwards, and below zero for
+    // repulsive rings.
+    editor.points = control_points(parameters);
+    editor.max_r = editor.points.last().map_or(1.0, |(r, _)| *r).max(1.0) * 1.25;
+    let (min, max) = editor.points.iter()
+        .fold((0.0f32, 1.0f32), |(min, max), (_, value)| (min.min(*value), max.max(*value)));
+    let padding = 0.25 * (max - min);
+    editor.values = (min - padding, max + padding);
+}
+
+fn drag_control_points(
+    buttons: Res<Input<MouseButton>>,
+    selection: Res<Selection>,
+    mut editor: ResMut<KernelEditor>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut creature_query: Query<(Entity, &mut Parameters), With<Creature>>,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let panel = panel_rect(window);
+    editor.panel = Some(panel);
+    if !editor.open {
+        return;
+    }
+    if !buttons.pressed(MouseButton::Left) {
+        editor.dragging = None;
+        return;
+    }
+    let Some(cursor) = window.cursor_position() else {
+        return;
+    };
+
+    if buttons.just_pressed(MouseButton::Left) {
+        editor.dragging = editor.points.iter().enumerate()
+            .map(|(i, point)| (i, editor.screen_point(panel, *point).distance(cursor)))
+            .filter(|(_, distance)| *distance < GRAB_RADIUS)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(i, _)| i);
+    }
+    let Some(i) = editor.dragging else {
+        return;
+    };
+
+    // Points keep their order along r, so the spline stays a function of r.
+    let (r, value) = editor.plot_point(panel, cursor.clamp(panel.min, panel.max));
+    let lower = if i == 0 { 0.0 } else { editor.points[i - 1].0 + MIN_GAP };
+    let upper = editor.points.get(i + 1).map_or(editor.max_r, |(r, _)| r - MIN_GAP);
+    let point = (r.clamp(lower, upper.max(lower)), value);
+    if editor.points[i] == point {
+        return;
+    }
+    editor.points[i] = point;
+
+    let kernel = KernelKind::Spline(editor.points.clone());
+    for (entity, mut parameters) in creature_query.iter_mut() {
+        if !selection.0.is_empty() && !selection.0.contains(&entity) {
+            continue;
+        }
+        let edited = Parameters { kernel: kernel.clone(), ..parameters.clone() };
+        match edited.validate() {
+            Ok(()) => *parameters = edited,
+            Err(err) => warn!("kept the previous kernel of {:?}: {}", entity, err),
+        }
+    }
+}
+
+fn draw_editor(editor: Res<KernelEditor>, screen: ScreenSpace, mut gizmos: Gizmos) {
+    let Some(panel) = editor.panel.filter(|_| editor.open) else {
+        return;
+    };
+    let to_world = |point: Vec2| screen.world_point(point);
+
+    let corners = [panel.min, Vec2::new(panel.max.x, panel.min.y), panel.max, Vec2::new(panel.min.x, panel.max.y), panel.min];
+    gizmos.linestrip_2d(corners.into_iter().filter_map(to_world), Color::GRAY);
+    let zero = [editor.screen_point(panel, (0.0, 0.0)), editor.screen_point(panel, (editor.max_r, 0.0))];
+    gizmos.linestrip_2d(zero.into_iter().filter_map(to_world), Color::DARK_GRAY);
+
+    let curve = (0..=CURVE_SAMPLES).map(|i| {
+        let r = editor.max_r * i as f32 / CURVE_SAMPLES as f32;
+        editor.screen_point(panel, (r, spline_field(&editor.points, r).0))
+    });
+    gizmos.linestrip_2d(curve.filter_map(to_world), Color::WHITE);
+
+    for (i, point) in editor.points.iter().enumerate() {
+        let center = editor.screen_point(panel, *point);
+        let (Some(world), Some(radius)) = (to_world(center), screen.world_length(center, HANDLE_RADIUS)) else {
+            continue;
+        };
+        let color = if editor.dragging == Some(i) { Color::ORANGE } else { Color::YELLOW };
+        gizmos.circle_2d(world, radius, color);
+    }
+}
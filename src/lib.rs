@@ -0,0 +1,2163 @@
+#![allow(non_snake_case)]
+
+mod annealing;
+mod attractor;
+mod background;
+mod barnes_hut;
+mod bin_record;
+mod boundary;
+mod brush;
+mod camera_fit;
+mod centroid_force;
+mod checkpoint;
+mod cli;
+mod colormap;
+mod compare;
+mod connectivity;
+mod culling;
+mod density;
+mod emitter;
+mod encounters;
+mod events;
+mod export;
+mod flow_arrows;
+mod force_bands;
+mod force_panel;
+mod gif;
+mod gif_record;
+mod ghost_trail;
+mod glider;
+#[cfg(feature = "gpu")]
+mod gpu;
+mod gradient_check;
+mod halo;
+mod headless;
+mod heatmap;
+mod inspector;
+mod kernel_editor;
+mod lifecycle;
+mod lifespan;
+mod lod;
+mod measure;
+mod metrics;
+mod minimap;
+mod monitor;
+mod motion_blur;
+mod outline;
+mod output;
+mod overlay;
+mod param_map;
+mod perturb;
+mod picking;
+mod pinning;
+mod plugin;
+mod presets;
+mod probe;
+mod probe_line;
+mod quality;
+mod rdf;
+mod realtime;
+mod recenter;
+mod reference;
+mod relax;
+mod replay;
+mod respawn;
+mod safe_mode;
+mod scene;
+mod selection;
+mod simulation;
+mod size_controller;
+mod snapshot;
+mod spawner;
+mod species;
+mod state;
+mod stats;
+mod streamlines;
+mod substeps;
+mod svg;
+mod target;
+mod template;
+mod timeline;
+mod timings;
+mod trace;
+mod trajectory;
+mod trajectory_check;
+mod truncation;
+mod validate;
+
+use std::{f32::consts::TAU, iter};
+
+use bevy::{
+    diagnostic::{Diagnostics, LogDiagnosticsPlugin},
+    ecs::{query::Has, schedule::ExecutorKind},
+    log::LogPlugin,
+    math::DVec3,
+    prelude::*,
+    sprite::Mesh2dHandle,
+};
+use clap::{error::ErrorKind, CommandFactory, Parser};
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use annealing::{AnnealingPlugin, PhaseSchedule};
+use attractor::{AttractorPlugin, AttractorSettings};
+use background::BackgroundSimPlugin;
+use barnes_hut::BarnesHut;
+use bin_record::{BinPlaybackPlugin, BinRecordPlugin, RecordingHeader};
+use boundary::{BoundaryMode, BoundaryPlugin, SoftBoundary, SoftBoundaryPlugin, WrapGhostsPlugin};
+use brush::BrushPlugin;
+use camera_fit::CameraFitPlugin;
+use centroid_force::{CentroidForce, CentroidForcePlugin};
+use checkpoint::CheckpointPlugin;
+use cli::Args;
+use colormap::{ColorMode, ColormapPlugin, Colormaps};
+use compare::ComparePlugin;
+use connectivity::ConnectivityPlugin;
+use culling::{is_visible, ViewBounds};
+use emitter::{Emitter, EmitterPlugin};
+use encounters::{CloseEncounters, CloseEncountersPlugin};
+use events::CreatureEventsPlugin;
+use export::ExportPlugin;
+use flow_arrows::FlowArrowsPlugin;
+use force_bands::{ForceBands, ForceBandsPlugin};
+use force_panel::ForcePanelPlugin;
+use gif_record::GifRecordPlugin;
+use ghost_trail::GhostTrailPlugin;
+#[cfg(feature = "gpu")]
+use gpu::GpuFieldsPlugin;
+use gradient_check::GradientCheckPlugin;
+use halo::HaloPlugin;
+use headless::{HeadlessPlugin, StepTimingPlugin};
+use heatmap::HeatmapPlugin;
+use inspector::InspectorPlugin;
+use kernel_editor::KernelEditorPlugin;
+use lifecycle::{Lifecycle, LifecyclePlugin};
+use lifespan::LifespanPlugin;
+use lod::LodPlugin;
+use measure::MeasurePlugin;
+use metrics::MetricsPlugin;
+use minimap::{MinimapCamera, MinimapPlugin};
+use monitor::MonitorPlugin;
+use motion_blur::MotionBlurPlugin;
+use outline::OutlinePlugin;
+use output::{create_file, OutputConfig, OutputName};
+use overlay::OverlayPlugin;
+use param_map::{ClassMap, ParameterAxis, ParamMapPlugin};
+use perturb::{PerturbPlugin, ScheduledPerturbation};
+use pinning::{Pinned, PinningPlugin};
+use presets::{AnimateParams, OscillateParam, OscillationControlsPlugin, PresetPlugin};
+use probe_line::ProbeLinePlugin;
+use quality::QualityPlugin;
+use rdf::{RadialDistribution, RadialDistributionPlugin};
+use realtime::RealtimeCapPlugin;
+use recenter::RecenterPlugin;
+use reference::ReferencePlugin;
+use relax::RelaxPlugin;
+use replay::{load_recording, RecordPlugin, ReplayPlugin};
+use respawn::RespawnPlugin;
+use safe_mode::SafeModePlugin;
+use scene::{DEMO_SEED, DISK_RADIUS, LATTICE_SPACING};
+use selection::SelectionPlugin;
+pub use density::Density;
+pub use plugin::{ParticleLeniaPlugin, ParticleRenderPlugin, SceneCameraPlugin, ScenePlugin};
+pub use scene::{CreatureSpawn, Scene, SpawnPattern};
+pub use simulation::{compute_fields, ParticleState, SimState, Simulation};
+use size_controller::{SizeController, SizeControllerPlugin};
+use snapshot::{SnapshotMode, SnapshotPlugin};
+use spawner::{ParticleBudget, SettleSteps, SpawnerPlugin};
+use species::{InteractionMatrix, Species, SpeciesLayersPlugin, SpeciesPlugin};
+use state::SavedState;
+use stats::{CreatureStats, StatsPlugin};
+pub use stats::{shape_descriptors, ShapeDescriptors};
+use streamlines::StreamlinePlugin;
+use substeps::SubstepPlugin;
+use target::{TargetProfile, TargetProfilePlugin};
+use template::CreatureTemplate;
+use timeline::{Timeline, TimelinePlugin};
+use timings::{SystemTimer, TimingsPlugin, SYSTEM_TIMINGS};
+use trace::TracePlugin;
+use trajectory::TrajectoryPlugin;
+use trajectory_check::{ReferenceTrajectory, TrajectoryCheckPlugin};
+use truncation::TruncationReportPlugin;
+use validate::FieldValidationPlugin;
+
+#[derive(Component)]
+struct Creature;
+
+// Position in `Scene::creatures` of the spawn a creature came from, for the
+// creatures spawned with the scene.
+#[derive(Component, Clone, Copy, Debug)]
+struct SceneIndex(usize);
+
+/// A creature's kernel, growth and repulsion parameters.
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
+pub struct Parameters {
+    pub mu_k: f32,
+    pub sigma_k: f32,
+    pub w_k: f32,
+    pub kernel: KernelKind,
+    
+    pub mu_g: f32,
+    pub sigma_g: f32,
+    pub growth: GrowthKind,
+    pub growth_kernel: KernelKind,
+
+    pub c_rep: f32,
+}
+
+impl Default for Parameters {
+    fn default() -> Self {
+        Self {
+            mu_k: 4.0,
+            sigma_k: 1.0,
+            w_k: 0.022,
+            kernel: KernelKind::Gaussian,
+            
+            mu_g: 0.6,
+            sigma_g: 0.15,
+            growth: GrowthKind::SingleGaussian,
+            growth_kernel: KernelKind::Gaussian,
+            
+            c_rep: 1.0,
+        }
+    }    
+}
+
+impl Parameters {
+    /// Checks the invariants the field functions rely on, so a bad value is
+    /// reported where it came from instead of turning into NaNs later.
+    pub fn validate(&self) -> Result<(), String> {
+        let check = |ok: bool, message: &str| if ok { Ok(()) } else { Err(message.to_string()) };
+        check(self.mu_k.is_finite() && self.mu_k >= 0.0, "mu_k must be finite and non-negative")?;
+        check(self.sigma_k.is_finite() && self.sigma_k > 0.0, "sigma_k must be finite and positive")?;
+        check(self.w_k.is_finite() && self.w_k >= 0.0, "w_k must be finite and non-negative")?;
+        check(self.mu_g.is_finite(), "mu_g must be finite")?;
+        check(self.sigma_g.is_finite() && self.sigma_g > 0.0, "sigma_g must be finite and positive")?;
+        check(self.c_rep.is_finite() && self.c_rep >= 0.0, "c_rep must be finite and non-negative")?;
+        if let GrowthKind::DoubleWell { mu_1, sigma_1, mu_2, sigma_2 } = self.growth {
+            check(mu_1.is_finite() && mu_2.is_finite(), "double-well centres must be finite")?;
+            check(sigma_1.is_finite() && sigma_1 > 0.0 && sigma_2.is_finite() && sigma_2 > 0.0, "double-well sigmas must be finite and positive")?;
+        }
+        for (name, kernel) in [("kernel", &self.kernel), ("growth_kernel", &self.growth_kernel)] {
+            if let KernelKind::Spline(points) = kernel {
+                check(!points.is_empty(), &format!("{} spline needs at least one control point", name))?;
+                check(points.iter().all(|(x, y)| x.is_finite() && y.is_finite()), &format!("{} spline points must be finite", name))?;
+                check(points.windows(2).all(|pair| pair[0].0 < pair[1].0), &format!("{} spline points must be in increasing order", name))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// K(r) and its slope.
+    pub fn kernel_field(&self, r: f32) -> (f32, f32) {
+        self.kernel.field(r, self.mu_k, self.sigma_k, self.w_k)
+    }
+
+    fn growth_field(&self, U: f32) -> (f32, f32) {
+        self.growth.field(&self.growth_kernel, U, self.mu_g, self.sigma_g)
+    }
+}
+
+// Shape of a bump of height `w` centred on `mu`, used for both the interaction
+// kernel K(r) and the growth mapping G(U).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum KernelKind {
+    #[default]
+    Gaussian,
+    /// (1 - t^2 / 4)^4, which vanishes two sigma from the centre
+    Quartic,
+    /// Two Gaussians, centred on mu / 2 and mu
+    TwoRings,
+    /// A curve through `(x, value)` control points sorted by `x`, times `w`,
+    /// drawn by the kernel editor. `mu` and `sigma` aren't used.
+    Spline(Vec<(f32, f32)>),
+}
+
+// Splines only come from the editor or a saved state, so they aren't a choice
+// on the command line.
+impl clap::ValueEnum for KernelKind {
+    fn value_variants<'a>() -> &'a [Self] {
+        const VARIANTS: &[KernelKind] = &[KernelKind::Gaussian, KernelKind::Quartic, KernelKind::TwoRings];
+        VARIANTS
+    }
+
+    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
+        match self {
+            KernelKind::Gaussian => Some(clap::builder::PossibleValue::new("gaussian")),
+            KernelKind::Quartic => Some(clap::builder::PossibleValue::new("quartic").help("(1 - t^2 / 4)^4, which vanishes two sigma from the centre")),
+            KernelKind::TwoRings => Some(clap::builder::PossibleValue::new("two-rings").help("Two Gaussians, centred on mu / 2 and mu")),
+            KernelKind::Spline(_) => None,
+        }
+    }
+}
+
+impl KernelKind {
+    fn field(&self, x: f32, mu: f32, sigma: f32, w: f32) -> (f32, f32) {
+        match self {
+            KernelKind::Gaussian => radial_field(x, mu, sigma, w),
+            KernelKind::Quartic => {
+                let t = (x - mu) / sigma;
+                let s = f32::max(0.0, 1.0 - 0.25 * t * t);
+                (w * s.powi(4), -2.0 * w * t * s.powi(3) / sigma)
+            }
+            KernelKind::TwoRings => {
+                let (y_1, dy_1) = radial_field(x, 0.5 * mu, sigma, w);
+                let (y_2, dy_2) = radial_field(x, mu, sigma, w);
+                (y_1 + y_2, dy_1 + dy_2)
+            }
+            KernelKind::Spline(points) => {
+                let (y, dy) = spline_field(points, x);
+                (w * y, w * dy)
+            }
+        }
+    }
+}
+
+// Shape of the growth mapping G(U). A double well, the sum of two bumps, has
+// two preferred densities instead of one.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum GrowthKind {
+    SingleGaussian,
+    DoubleWell { mu_1: f32, sigma_1: f32, mu_2: f32, sigma_2: f32 },
+}
+
+impl GrowthKind {
+    // `mu_g` and `sigma_g` are only used by the single well.
+    fn field(self, kernel: &KernelKind, U: f32, mu_g: f32, sigma_g: f32) -> (f32, f32) {
+        match self {
+            GrowthKind::SingleGaussian => kernel.field(U, mu_g, sigma_g, 1.0),
+            GrowthKind::DoubleWell { mu_1, sigma_1, mu_2, sigma_2 } => {
+                let (G_1, dG_1) = kernel.field(U, mu_1, sigma_1, 1.0);
+                let (G_2, dG_2) = kernel.field(U, mu_2, sigma_2, 1.0);
+                (G_1 + G_2, dG_1 + dG_2)
+            }
+        }
+    }
+}
+
+// Multiplies the integration step of a creature's particles. Inter-creature
+// repulsion is still evaluated once per step for both sides, so when two
+// interacting creatures run at different scales the exchange is no longer
+// reciprocal and momentum is not conserved between them.
+#[derive(Component, Clone, Copy, Debug)]
+struct TimeScale(f32);
+
+impl Default for TimeScale {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+#[derive(Component)]
+struct Particle;
+
+// Sizes the particle under `SizeMode::ByMass` and goes into exports; the
+// field sums don't weight by it.
+#[derive(Component)]
+struct Mass(f32);
+
+impl Default for Mass {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+#[derive(Component, Default)]
+struct Velocity(Vec3);
+
+// Growth parameters painted onto a single particle, used instead of its
+// creature's mu_g and sigma_g.
+#[derive(Component, Clone, Copy, Debug, Serialize, Deserialize)]
+struct GrowthOverride {
+    mu_g: f32,
+    sigma_g: f32,
+}
+
+impl GrowthOverride {
+    // The creature's growth mapping with the painted values. A double well
+    // doesn't use mu_g and sigma_g, so its wells are moved by the painted
+    // shift in mu_g and widened by the painted ratio of sigma_g instead.
+    fn growth_field(&self, parameters: &Parameters, U: f32) -> (f32, f32) {
+        let growth = match parameters.growth {
+            GrowthKind::SingleGaussian => GrowthKind::SingleGaussian,
+            GrowthKind::DoubleWell { mu_1, sigma_1, mu_2, sigma_2 } => {
+                let (shift, scale) = (self.mu_g - parameters.mu_g, self.sigma_g / parameters.sigma_g);
+                GrowthKind::DoubleWell { mu_1: mu_1 + shift, sigma_1: sigma_1 * scale, mu_2: mu_2 + shift, sigma_2: sigma_2 * scale }
+            }
+        };
+        growth.field(&parameters.growth_kernel, U, self.mu_g, self.sigma_g)
+    }
+}
+
+/// A particle's potential fields and their gradients, as of the last step.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Fields {
+    pub R_val: f32,
+    pub R_grad: Vec3,
+
+    pub U_val: f32,
+    pub U_grad: Vec3,
+
+    pub E_grad: Vec3,
+}
+
+impl Default for Fields {
+    fn default() -> Self {
+        Self {
+            R_val: 0.0,
+            R_grad: Vec3::ZERO,
+
+            U_val: 0.0,
+            U_grad: Vec3::ZERO,
+
+            E_grad: Vec3::ZERO,
+        }
+    }
+}
+
+// f64 running sums of the pair loop, copied back into `Fields` once all pairs
+// are in. Only attached to particles when `--wide-sums` is set.
+#[derive(Component, Default)]
+struct WideFields {
+    R_val: f64,
+    R_grad: DVec3,
+
+    U_val: f64,
+    U_grad: DVec3,
+}
+
+// Particles of the same creature within `NeighborRadius`, counted by the pair
+// loop for coloring by coordination. Only attached to particles in the window.
+#[derive(Component, Default)]
+struct NeighborCount(u32);
+
+// Distance within which the pair loop counts two particles as neighbors, the
+// kernel's reach two widths past mu_k when unset.
+#[derive(Resource, Default)]
+struct NeighborRadius(Option<f32>);
+
+#[derive(Resource, Clone, Copy, Debug)]
+enum SizeMode {
+    ByRVal { scale: f32 },
+    ByUVal { scale: f32 },
+    Constant { radius: f32 },
+    ByMass { scale: f32 },
+}
+
+impl Default for SizeMode {
+    fn default() -> Self {
+        SizeMode::ByRVal { scale: 5.0 }
+    }
+}
+
+impl SizeMode {
+    fn radius(self, parameters: &Parameters, fields: &Fields, mass: &Mass) -> f32 {
+        match self {
+            // R_val is at least R(0) = c_rep / 2 while particles count
+            // themselves, and capping it there keeps particles with no
+            // neighbors in range from growing without bound when they don't.
+            SizeMode::ByRVal { scale } => parameters.c_rep / (fields.R_val.max(parameters.c_rep / 2.0) * scale),
+            SizeMode::ByUVal { scale } => fields.U_val * scale,
+            SizeMode::Constant { radius } => radius,
+            SizeMode::ByMass { scale } => scale * mass.0.sqrt(),
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            SizeMode::ByRVal { .. } => SizeMode::ByUVal { scale: 0.8 },
+            SizeMode::ByUVal { .. } => SizeMode::Constant { radius: 0.5 },
+            SizeMode::Constant { .. } => SizeMode::ByMass { scale: 0.5 },
+            SizeMode::ByMass { .. } => SizeMode::default(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Dynamics {
+    Overdamped,
+    Momentum { damping: f32 },
+}
+
+#[derive(Resource)]
+struct Integrator {
+    dt: f32,
+    dynamics: Dynamics,
+    // Extra damping per unit of a particle's R_val, so dense cores lose speed
+    // faster than sparse edges.
+    density_damping: f32,
+    // Moves each step is split into, each a fraction of dt.
+    substeps: u32,
+    // Angular velocity of the frame particles are integrated in, about the
+    // world origin, counterclockwise, and whether it adds the Coriolis term
+    // to the centrifugal one.
+    frame_omega: f32,
+    coriolis: bool,
+}
+
+impl Integrator {
+    // Pseudo-force on a particle at `x` moving at `velocity` in the rotating
+    // frame: centrifugal `omega^2 x` outward plus Coriolis `-2 omega z × v`.
+    // In overdamped mode `velocity` is the last step's drift.
+    fn frame_force(&self, x: Vec3, velocity: Vec3) -> Vec3 {
+        if self.frame_omega == 0.0 {
+            return Vec3::ZERO;
+        }
+        let centrifugal = self.frame_omega * self.frame_omega * Vec3::new(x.x, x.y, 0.0);
+        let coriolis = if self.coriolis { 2.0 * self.frame_omega * Vec3::new(velocity.y, -velocity.x, 0.0) } else { Vec3::ZERO };
+        centrifugal + coriolis
+    }
+}
+
+impl Default for Integrator {
+    fn default() -> Self {
+        Self {
+            dt: 0.1,
+            dynamics: Dynamics::Overdamped,
+            density_damping: 0.0,
+            substeps: 1,
+            frame_omega: 0.0,
+            coriolis: false,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct ForceTerms {
+    growth: bool,
+    repulsion: bool,
+    // Attractors, the soft boundary, the centroid force and the target profile.
+    external: bool,
+}
+
+impl Default for ForceTerms {
+    fn default() -> Self {
+        Self { growth: true, repulsion: true, external: true }
+    }
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum ParticleShape {
+    #[default]
+    Circle,
+    Square,
+    Triangle,
+    /// A textured quad, see --particle-texture
+    Sprite,
+}
+
+impl ParticleShape {
+    // Every shape is inscribed in a circle of radius `size`.
+    fn mesh(self, size: f32, circle_vertices: usize) -> Mesh {
+        match self {
+            ParticleShape::Circle => shape::Circle { radius: size, vertices: circle_vertices }.into(),
+            ParticleShape::Square | ParticleShape::Sprite => shape::Quad::new(Vec2::splat(size * std::f32::consts::SQRT_2)).into(),
+            ParticleShape::Triangle => shape::RegularPolygon::new(size, 3).into(),
+        }
+    }
+}
+
+// Field that decides which overlapping particles are drawn on top. Only the
+// rendered depth changes: the offset goes on the GlobalTransform after
+// propagation, so the simulated z of each Transform is left alone.
+#[derive(clap::ValueEnum, Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum ZOrderMode {
+    /// Leave the draw order arbitrary
+    #[default]
+    Off,
+    /// Denser particles on top
+    RVal,
+    /// Particles with more growth potential on top
+    UVal,
+}
+
+// Half the depth range the offsets are squeezed into, small enough to keep
+// particles well clear of the camera's near and far planes.
+const Z_ORDER_SPAN: f32 = 0.5;
+
+impl ZOrderMode {
+    fn depth(self, fields: &Fields) -> f32 {
+        let value = match self {
+            ZOrderMode::Off => return 0.0,
+            ZOrderMode::RVal => fields.R_val,
+            ZOrderMode::UVal => fields.U_val,
+        };
+        Z_ORDER_SPAN * value / (1.0 + value.abs())
+    }
+}
+
+// Time constant, in seconds of wall-clock time, over which drawn particle radii
+// ease toward the size mode's value. `None` draws the raw value every frame.
+#[derive(Resource)]
+struct RadiusSmoothing(Option<f32>);
+
+impl Default for RadiusSmoothing {
+    fn default() -> Self {
+        RadiusSmoothing(Some(0.1))
+    }
+}
+
+// Smallest radius particles are drawn with, in logical pixels, whatever their
+// size mode gives, so dense cores don't shrink out of sight. Only drawing is
+// affected, not the dynamics.
+#[derive(Resource)]
+struct MinRenderRadius(f32);
+
+impl Default for MinRenderRadius {
+    fn default() -> Self {
+        MinRenderRadius(1.5)
+    }
+}
+
+// Radius a particle was last drawn with, unset until its first visible frame.
+#[derive(Component, Default)]
+struct DisplayRadius(Option<f32>);
+
+#[derive(Resource)]
+struct ParticleStyle {
+    shape: ParticleShape,
+    texture: Option<String>,
+    circle_vertices: usize,
+}
+
+impl Default for ParticleStyle {
+    fn default() -> Self {
+        Self { shape: ParticleShape::default(), texture: None, circle_vertices: 64 }
+    }
+}
+
+// Caps |E_grad| before integration so a single step can't launch particles.
+#[derive(Resource, Default)]
+struct ForceLimit(Option<f32>);
+
+// Scales every force by the step over N for the first N steps, a soft start
+// that lets creatures assemble without the transient of a full-strength
+// first step.
+#[derive(Resource, Default)]
+struct InteractionRamp(Option<u32>);
+
+impl InteractionRamp {
+    fn factor(&self, step: u64) -> f32 {
+        self.0.map_or(1.0, |steps| (step as f32 / steps as f32).min(1.0))
+    }
+}
+
+// Whether each particle counts itself at r = 0 in its own fields, which adds
+// R(0) = c_rep / 2 to R_val and K(0) to U_val: the floor an isolated
+// particle's fields sit at. Without it they start from zero: every particle
+// sits K(0) lower on the growth curve, draws smaller by U_val, and draws
+// larger by R_val, up to the size an isolated particle has with it.
+#[derive(Resource)]
+struct SelfField(bool);
+
+impl Default for SelfField {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+// Whether this world's `sum_pair_fields` visits every pair one by one, which
+// g(r), the force bands and the neighbor counts are gathered along the way
+// of, and whether it runs at all, which close encounters also need. The pair
+// loop is skipped with --barnes-hut or --gpu, and neither runs with --gpu or
+// in a window that doesn't step.
+#[derive(Resource)]
+struct DirectPairSums {
+    pair_loop: bool,
+    cpu: bool,
+}
+
+impl DirectPairSums {
+    fn warn_unless(&self, what: &str) {
+        if !self.pair_loop {
+            warn!("{} is only gathered from the direct pair loop, which --barnes-hut, --gpu, --background-sim and --play-bin skip", what);
+        }
+    }
+
+    // For what the tree gathers as well.
+    fn warn_unless_cpu(&self, what: &str) {
+        if !self.cpu {
+            warn!("{} are only counted in the CPU pair sums, which --gpu, --background-sim and --play-bin skip", what);
+        }
+    }
+}
+
+// Switches the kernel off between `r_on` and `r_cut` with a cubic that's flat
+// at both ends, so the force goes smoothly to zero and pairs beyond `r_cut`
+// can be skipped without a jump in it.
+#[derive(Resource, Default)]
+struct KernelCutoff(Option<(f32, f32)>);
+
+impl KernelCutoff {
+    fn reaches(&self, r: f32) -> bool {
+        self.0.is_none_or(|(_, r_cut)| r < r_cut)
+    }
+
+    fn apply(&self, r: f32, (K, dK): (f32, f32)) -> (f32, f32) {
+        let Some((r_on, r_cut)) = self.0.filter(|(r_on, _)| r > *r_on) else {
+            return (K, dK);
+        };
+        if r >= r_cut {
+            return (0.0, 0.0);
+        }
+        let width = r_cut - r_on;
+        let x = (r - r_on) / width;
+        let S = 1.0 - x * x * (3.0 - 2.0 * x);
+        let dS = -6.0 * x * (1.0 - x) / width;
+        (K * S, dK * S + K * dS)
+    }
+}
+
+// Per-axis scale applied to pair separations before taking their length, so
+// the kernel reaches further along axes with a scale below one.
+#[derive(Resource)]
+struct MetricScale(Vec3);
+
+impl Default for MetricScale {
+    fn default() -> Self {
+        Self(Vec3::ONE)
+    }
+}
+
+impl MetricScale {
+    // Distance r = |S d| between particles `d` apart, with its gradient with
+    // respect to the first particle, S^2 d / r. The fields stay potentials of
+    // this distance, so the forces they give are still conservative.
+    fn distance(&self, d: Vec3) -> (f32, Vec3) {
+        let scaled = self.0 * d;
+        let r = scaled.length();
+        (r, self.0 * scaled / r)
+    }
+}
+
+#[derive(Resource, Default)]
+struct InterCreature {
+    enabled: bool,
+}
+
+#[derive(Resource, Default)]
+struct SimStep(u64);
+
+// Precursors of instability observed during the latest step.
+#[derive(Resource, Clone, Copy, Debug)]
+struct StepDiagnostics {
+    min_pair_distance: f32,
+    close_encounters: u64,
+    max_force: f32,
+}
+
+impl Default for StepDiagnostics {
+    fn default() -> Self {
+        Self {
+            min_pair_distance: f32::INFINITY,
+            close_encounters: 0,
+            max_force: 0.0,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct SimRng(ChaCha8Rng);
+
+// RNG stream of the scene creature at `index`, independent of every other
+// creature's, so its cloud is the same however many were spawned before it.
+// `generation` counts its respawns, each of which draws a fresh cloud. The
+// first creature's first stream is the seed's own.
+fn creature_rng(seed: u64, index: usize, generation: u32) -> SmallRng {
+    // SplitMix64's finalizer, which scatters nearby keys and leaves 0 alone.
+    let mut key = (generation as u64) << 32 | index as u64;
+    key = (key ^ (key >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    key = (key ^ (key >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    SmallRng::seed_from_u64(seed ^ key ^ (key >> 31))
+}
+
+#[derive(Bundle)]
+struct CreatureBundle {
+    spatial: SpatialBundle,
+    parameters: Parameters,
+    time_scale: TimeScale,
+    stats: CreatureStats,
+    creature: Creature,
+}
+
+#[derive(Bundle)]
+struct ParticleBundle {
+    spatial: SpatialBundle,
+    fields: Fields,
+    mass: Mass,
+    velocity: Velocity,
+    particle: Particle,
+}
+
+/// Runs the app described by the process's command line.
+pub fn run() {
+    let args = Args::parse();
+    if let Some(candidates) = args.find_gliders {
+        glider::find_gliders(&args, candidates as usize);
+        return;
+    }
+    app(&args).run();
+}
+
+fn app(args: &Args) -> App {
+    let state_path = args.resume.as_ref().map(|path| checkpoint::resolve(path).unwrap_or_else(|err| {
+        eprintln!("failed to find a checkpoint in {}: {}", path.display(), err);
+        std::process::exit(1);
+    }));
+    let saved_state = state_path.as_ref().or(args.load_state.as_ref()).map(|path| SavedState::load(path).unwrap_or_else(|err| {
+        eprintln!("failed to read {}: {}", path.display(), err);
+        std::process::exit(1);
+    }));
+    if let (Some(path), Some(state)) = (&state_path, &saved_state) {
+        eprintln!("resuming from {} at step {}", path.display(), state.step);
+    }
+
+    let recording = args.play_bin.as_ref().map(|path| RecordingHeader::load(path).unwrap_or_else(|err| {
+        eprintln!("failed to read {}: {}", path.display(), err);
+        std::process::exit(1);
+    }));
+    let seed_arg = args.seed.or(recording.map(|recording| recording.seed));
+
+    let mut scene = match (args.demo, args.stress.as_deref()) {
+        _ if saved_state.is_some() => saved_state.as_ref().unwrap().scene(),
+        _ if args.compare.is_some() => {
+            let presets = args.compare.as_deref().unwrap();
+            Scene::compare(presets[0].parameters(), presets[1].parameters(), seed_arg.unwrap_or_else(|| thread_rng().gen()))
+        }
+        (_, Some([count, particles])) => Scene::stress(*count, *particles, seed_arg.unwrap_or(DEMO_SEED)),
+        (Some(demo), _) => Scene::demo(demo, seed_arg.unwrap_or(DEMO_SEED)),
+        (None, _) => Scene::single(seed_arg.unwrap_or_else(|| thread_rng().gen())),
+    };
+
+    for spawn in scene.creatures.iter_mut() {
+        if let Some(preset) = args.preset {
+            spawn.parameters = preset.parameters();
+        }
+        if let Some(target) = args.morph_to {
+            spawn.animation = Some(AnimateParams {
+                from: spawn.parameters.clone(),
+                to: target.parameters(),
+                duration: args.morph_duration,
+                elapsed: 0.0,
+            });
+        }
+    }
+
+    for spawn in scene.creatures.iter_mut() {
+        spawn.parameters.kernel = args.kernel.clone();
+        spawn.parameters.growth_kernel = args.growth_kernel.clone();
+    }
+
+    if let (Some(param), Some(amplitude)) = (args.oscillate, args.oscillate_amplitude) {
+        for spawn in scene.creatures.iter_mut() {
+            spawn.oscillation = Some(OscillateParam::new(param, amplitude, args.oscillate_frequency, &spawn.parameters));
+        }
+    }
+
+    if let Some([mu_1, sigma_1, mu_2, sigma_2]) = args.double_well.as_deref() {
+        for spawn in scene.creatures.iter_mut() {
+            spawn.parameters.growth = GrowthKind::DoubleWell {
+                mu_1: *mu_1,
+                sigma_1: *sigma_1,
+                mu_2: *mu_2,
+                sigma_2: *sigma_2,
+            };
+        }
+    }
+
+    // Checked here, before anything is spawned from it.
+    let density = args.density.as_deref().map(|source| {
+        let density = Density::parse(source).unwrap_or_else(|err| {
+            Args::command().error(ErrorKind::InvalidValue, format!("--density {}: {}", source, err)).exit()
+        });
+        if args.density_radius <= 0.0 {
+            Args::command().error(ErrorKind::InvalidValue, "--density-radius must be positive").exit();
+        }
+        if density.peak(args.density_radius) <= 0.0 {
+            Args::command().error(ErrorKind::InvalidValue, format!("--density {} is nowhere positive within --density-radius {}", source, args.density_radius)).exit();
+        }
+        density
+    });
+    for spawn in scene.creatures.iter_mut() {
+        if args.lattice {
+            spawn.pattern = SpawnPattern::Grid {
+                count: spawn.pattern.count(),
+                spacing: LATTICE_SPACING,
+            };
+        }
+        if let Some(fold) = args.symmetry.filter(|_| !matches!(spawn.pattern, SpawnPattern::Explicit(_))) {
+            spawn.pattern = SpawnPattern::RotationalSymmetry {
+                fold,
+                base_count: (spawn.pattern.count() as f32 / fold as f32).round().max(1.0) as usize,
+            };
+        }
+        if let Some(density) = density.as_ref().filter(|_| !matches!(spawn.pattern, SpawnPattern::Explicit(_))) {
+            spawn.pattern = SpawnPattern::Density {
+                count: spawn.pattern.count(),
+                radius: args.density_radius,
+                density: density.clone(),
+            };
+        }
+        if args.jitter.is_some() {
+            spawn.jitter = args.jitter;
+        }
+        spawn.settle_steps = args.settle_steps;
+    }
+
+    if let Some(total_mass) = args.total_mass {
+        if !(total_mass > 0.0 && args.particle_mass > 0.0) {
+            Args::command().error(ErrorKind::InvalidValue, "--total-mass and --particle-mass must be positive").exit();
+        }
+        for spawn in scene.creatures.iter_mut() {
+            let radius = match spawn.pattern {
+                SpawnPattern::Disk { radius, .. } => radius,
+                _ => DISK_RADIUS,
+            };
+            spawn.pattern = SpawnPattern::ByMass { total_mass, particle_mass: args.particle_mass, radius };
+        }
+        let count: usize = scene.creatures.iter().map(|spawn| spawn.pattern.count()).sum();
+        if count == 0 {
+            Args::command().error(ErrorKind::InvalidValue, "--total-mass is less than half a particle's --particle-mass").exit();
+        }
+        if let Some(budget) = args.particle_budget.filter(|budget| count > *budget) {
+            Args::command().error(ErrorKind::InvalidValue, format!(
+                "--total-mass {} of particles of mass {} makes {} particles in all, over the --particle-budget of {}",
+                total_mass, args.particle_mass, count, budget,
+            )).exit();
+        }
+    }
+
+    // After the options above, which the template's own parameters and
+    // settled shape stand in for.
+    if let Some(path) = &args.template {
+        let template = CreatureTemplate::load(path).unwrap_or_else(|err| {
+            eprintln!("failed to read {}: {}", path.display(), err);
+            std::process::exit(1);
+        });
+        let spawn = template.spawn(Vec3::new(args.template_at[0], args.template_at[1], 0.0));
+        if args.demo.is_none() && args.stress.is_none() {
+            scene.creatures = vec![spawn];
+        } else {
+            scene.creatures.push(spawn);
+        }
+    }
+
+    // Starts where the reference does, as it was spawned there exactly.
+    let reference = args.check_trajectory.as_ref().map(|path| {
+        let reference = ReferenceTrajectory::load(path).unwrap_or_else(|err| {
+            eprintln!("failed to read {}: {}", path.display(), err);
+            std::process::exit(1);
+        });
+        let dt = Integrator::default().dt;
+        if let Some(reference_dt) = reference.dt.filter(|reference_dt| *reference_dt != dt) {
+            eprintln!("{} was integrated with dt {}, this simulation steps by {}", path.display(), reference_dt, dt);
+            std::process::exit(1);
+        }
+        (path.clone(), reference)
+    });
+    if let Some((_, reference)) = &reference {
+        let parameters = scene.creatures.first().map(|spawn| spawn.parameters.clone()).unwrap_or_default();
+        scene.creatures = vec![CreatureSpawn {
+            parameters,
+            pattern: SpawnPattern::Explicit(reference.initial_positions()),
+            jitter: Some(0.0),
+            ..default()
+        }];
+    }
+
+    if let Some([vx, vy]) = args.initial_velocity.as_deref() {
+        for spawn in scene.creatures.iter_mut() {
+            spawn.initial_velocity = Vec3::new(*vx, *vy, 0.0);
+        }
+    }
+
+    for (index, spawn) in scene.creatures.iter().enumerate() {
+        let mut targets = iter::once(&spawn.parameters).chain(spawn.animation.as_ref().map(|animation| &animation.to));
+        if let Err(err) = targets.try_for_each(Parameters::validate) {
+            Args::command().error(ErrorKind::InvalidValue, format!("creature {} has invalid parameters: {}", index, err)).exit();
+        }
+    }
+
+    // Splines aren't in the compute shader, whether drawn in the editor,
+    // exported as a template or saved with a state.
+    #[cfg(feature = "gpu")]
+    if args.gpu {
+        let mut parameters = scene.creatures.iter().map(|spawn| &spawn.parameters)
+            .chain(saved_state.iter().flat_map(SavedState::parameters));
+        if parameters.any(|parameters| matches!(parameters.kernel, KernelKind::Spline(_)) || matches!(parameters.growth_kernel, KernelKind::Spline(_))) {
+            Args::command().error(ErrorKind::ArgumentConflict, "--gpu can't run spline kernels, which the scene, template or saved state has").exit();
+        }
+    }
+
+    let seed = scene.seed;
+    let mut app = App::new();
+    app.add_plugins((CreatureEventsPlugin, PresetPlugin, StatsPlugin, SpawnerPlugin))
+        .insert_resource(InterCreature {
+            enabled: args.inter_creature || scene.inter_creature,
+        })
+        .insert_resource(ForceTerms { growth: !args.no_growth, repulsion: !args.no_repulsion, external: !args.no_external_forces })
+        .insert_resource(ForceLimit(args.max_force))
+        .insert_resource(InteractionRamp(args.interaction_ramp))
+        .insert_resource(KernelCutoff(args.kernel_cutoff.as_deref().map(|cutoff| (cutoff[0], cutoff[1]))))
+        .insert_resource(SelfField(!args.no_self_field))
+        .insert_resource(MetricScale(args.metric_scale.as_deref().map_or(Vec3::ONE, |scale| {
+            Vec3::new(scale[0], scale[1], scale.get(2).copied().unwrap_or(1.0))
+        })))
+        .insert_resource(ParticleBudget(args.particle_budget))
+        .insert_resource(SettleSteps(args.settle_steps))
+        .insert_resource(Integrator {
+            dynamics: if args.momentum || scene.damping.is_some() {
+                Dynamics::Momentum { damping: args.damping.or(scene.damping).unwrap_or(1.0) }
+            } else {
+                Dynamics::Overdamped
+            },
+            density_damping: args.density_damping,
+            substeps: args.substeps,
+            frame_omega: args.frame_omega,
+            coriolis: args.coriolis,
+            ..default()
+        })
+        .add_plugins(ScenePlugin { scene });
+
+    // The window's copy of the scene only shows the steps the thread takes.
+    #[cfg(feature = "gpu")]
+    let field_sums = !args.gpu;
+    #[cfg(not(feature = "gpu"))]
+    let field_sums = true;
+    let cpu = field_sums && !args.background_sim && args.play_bin.is_none();
+    app.insert_resource(DirectPairSums { pair_loop: cpu && args.barnes_hut.is_none(), cpu });
+    if args.background_sim {
+        app.add_plugins((BackgroundSimPlugin { args: Args { seed: Some(seed), ..args.clone() } }, TimingsPlugin))
+            .init_resource::<SimStep>()
+            .init_resource::<StepDiagnostics>();
+    } else if let Some(path) = &args.play_bin {
+        app.add_plugins((BinPlaybackPlugin { path: path.clone() }, TimingsPlugin))
+            .init_resource::<SimStep>()
+            .init_resource::<StepDiagnostics>();
+    } else if field_sums {
+        app.add_plugins(ParticleLeniaPlugin::default());
+    } else {
+        app.add_plugins(ParticleLeniaPlugin::default().without_field_sums());
+    }
+
+    if let Some(path) = &args.interaction_matrix {
+        let matrix = InteractionMatrix::load(path).unwrap_or_else(|err| {
+            eprintln!("failed to read {}: {}", path.display(), err);
+            std::process::exit(1);
+        });
+        app.insert_resource(matrix).add_plugins(SpeciesPlugin);
+    } else {
+        app.init_resource::<InteractionMatrix>();
+    }
+
+    if let Some(state) = saved_state {
+        if let Some(rng) = state.rng() {
+            app.insert_resource(SimRng(rng));
+        }
+        app.insert_resource(SimStep(state.step))
+            .insert_resource(state)
+            .add_systems(Startup, state::spawn_saved_state);
+        // Saved states leave the fields out, so until the first step they'd
+        // read as zero.
+        if args.recompute_fields_on_load {
+            app.add_systems(PostStartup, (sum_pair_fields, calculate_fields, clamp_forces).chain());
+        }
+    }
+
+    #[cfg(feature = "gpu")]
+    if args.gpu {
+        app.add_plugins(GpuFieldsPlugin);
+    }
+
+    let task_pool = TaskPoolPlugin {
+        task_pool_options: args.threads.map_or_else(TaskPoolOptions::default, |threads| TaskPoolOptions::with_num_threads(threads.get())),
+    };
+
+    if args.headless {
+        app.add_plugins((MinimalPlugins.set(task_pool), HeadlessPlugin { steps: args.steps }));
+        // Several headless apps can be built in one process, as the glider
+        // search does, but only the first can set up logging.
+        if !bevy::utils::tracing::dispatcher::has_been_set() {
+            app.add_plugins(log_plugin(args));
+        }
+        if args.stress.is_some() {
+            app.add_plugins(StepTimingPlugin);
+        }
+        if args.tui {
+            app.add_plugins(MonitorPlugin);
+        }
+        if let Some(path) = &args.write_snapshot {
+            app.add_plugins(SnapshotPlugin { mode: SnapshotMode::Write(path.clone()) });
+        }
+        if let Some(path) = &args.check_snapshot {
+            app.add_plugins(SnapshotPlugin {
+                mode: SnapshotMode::Check { path: path.clone(), tolerance: args.snapshot_tolerance },
+            });
+        }
+        if let Some((path, reference)) = reference {
+            app.add_plugins(TrajectoryCheckPlugin { path, reference, tolerance: args.trajectory_tolerance });
+        }
+    } else {
+        let mut colormaps = Colormaps::default();
+        if let Some(path) = &args.colormaps {
+            if let Err(err) = colormaps.load(path) {
+                eprintln!("failed to read {}: {}", path.display(), err);
+                std::process::exit(1);
+            }
+        }
+        if let Some(name) = &args.colormap {
+            if let Err(err) = colormaps.select(name) {
+                Args::command().error(ErrorKind::InvalidValue, err).exit();
+            }
+        }
+        if !(args.attractor_range > 0.0 && args.attractor_softening > 0.0 && args.attractor_sigma > 0.0) {
+            Args::command().error(ErrorKind::InvalidValue, "--attractor-range, --attractor-softening and --attractor-sigma must be positive").exit();
+        }
+        let attractors = AttractorSettings {
+            strength: args.attractor_strength,
+            range: args.attractor_range,
+            softening: args.attractor_softening,
+            sigma: args.attractor_sigma,
+            ..default()
+        };
+
+        app.add_plugins((DefaultPlugins.set(task_pool).set(log_plugin(args)), ColormapPlugin { mode: args.color_mode, colormaps }, AttractorPlugin { settings: attractors }, BrushPlugin, ConnectivityPlugin { max_distance: args.edge_distance }, ExportPlugin { output: OutputConfig { dir: args.export_dir.clone(), name_template: args.name_template.clone() } }, InspectorPlugin, OutlinePlugin { mode: args.outline, alpha: args.outline_alpha, every: args.outline_every }, OverlayPlugin, ParticleRenderPlugin, PinningPlugin, QualityPlugin { quality: args.quality }, SelectionPlugin, StreamlinePlugin, TracePlugin))
+            .insert_resource(args.z_order)
+            .insert_resource(if args.raw_radius { RadiusSmoothing(None) } else { RadiusSmoothing::default() })
+            .insert_resource(MinRenderRadius(args.min_render_radius))
+            .insert_resource(NeighborRadius(args.neighbor_radius))
+            .insert_resource(ParticleStyle {
+                shape: args.particle_shape,
+                texture: args.particle_texture.clone(),
+                ..default()
+            })
+            .add_systems(PreUpdate, attach_neighbor_counts)
+            .add_systems(Update, (bevy::window::close_on_esc, toggle_growth, cycle_size_mode, toggle_radius_smoothing, spawner::spawn_from_input))
+            .add_plugins((
+                FlowArrowsPlugin { enabled: args.flow_arrows, resolution: args.flow_arrows_resolution, every: args.flow_arrows_every },
+                ForceBandsPlugin { enabled: args.force_bands, width: args.force_band_width, count: args.force_band_count as usize },
+                GhostTrailPlugin { count: args.ghost_count as usize, alpha: args.ghost_alpha },
+                HaloPlugin { enabled: args.halos, sigmas: args.halo_sigmas, alpha: args.halo_alpha },
+                HeatmapPlugin { enabled: args.heatmap, resolution: args.heatmap_resolution, every: args.heatmap_every },
+                MeasurePlugin { corner: args.coordinate_readout, precision: args.readout_precision },
+                MinimapPlugin { enabled: args.minimap },
+                MotionBlurPlugin { enabled: args.motion_blur, intensity: args.motion_blur_intensity },
+                OscillationControlsPlugin,
+                ProbeLinePlugin { samples: args.probe_line_samples as usize },
+            ));
+
+        if args.compare.is_some() {
+            app.add_plugins(ComparePlugin);
+        } else {
+            app.add_plugins(SceneCameraPlugin);
+        }
+        if args.fit_camera {
+            if !(0.0..0.5).contains(&args.fit_margin) {
+                Args::command().error(ErrorKind::InvalidValue, "--fit-margin must be at least 0 and below 0.5").exit();
+            }
+            app.add_plugins(CameraFitPlugin { margin: args.fit_margin });
+        }
+        if let Some(scale) = args.lod_scale {
+            if scale <= 0.0 {
+                Args::command().error(ErrorKind::InvalidValue, "--lod-scale must be positive").exit();
+            }
+            app.add_plugins(LodPlugin { scale });
+        }
+        if args.interaction_matrix.is_some() {
+            app.add_plugins(SpeciesLayersPlugin);
+        }
+        if args.counter_rotate_camera {
+            app.add_systems(Update, counter_rotate_camera.after(advance_step));
+        }
+        // Splines aren't in the compute shader, and edits wouldn't reach a
+        // background simulation.
+        #[cfg(feature = "gpu")]
+        let kernel_editor = !args.gpu && !args.background_sim;
+        #[cfg(not(feature = "gpu"))]
+        let kernel_editor = !args.background_sim;
+        if kernel_editor {
+            app.add_plugins(KernelEditorPlugin);
+        }
+        // Only this world's steps can be held still, watched converge, or have
+        // force terms switched off.
+        if !args.background_sim && args.play_bin.is_none() {
+            if args.relax_tolerance <= 0.0 || args.relax_max_steps == 0 {
+                Args::command().error(ErrorKind::InvalidValue, "--relax-tolerance and --relax-max-steps must be positive").exit();
+            }
+            app.add_plugins((
+                ForcePanelPlugin { open: args.force_panel },
+                RelaxPlugin { tolerance: args.relax_tolerance, max_steps: args.relax_max_steps },
+            ));
+        }
+        if !args.background_sim {
+            let axes = [args.param_map_axes[0], args.param_map_axes[1]];
+            let ranges = match args.param_map_range.as_deref() {
+                Some([x_min, x_max, y_min, y_max]) => [(*x_min, *x_max), (*y_min, *y_max)],
+                _ => axes.map(ParameterAxis::default_range),
+            };
+            if ranges.iter().any(|(min, max)| min >= max) {
+                Args::command().error(ErrorKind::InvalidValue, "--param-map-range needs each MIN below its MAX").exit();
+            }
+            let classes = args.param_map_classes.as_ref().map(|path| ClassMap::load(path, axes).unwrap_or_else(|err| {
+                eprintln!("failed to read {}: {}", path.display(), err);
+                std::process::exit(1);
+            }));
+            app.add_plugins(ParamMapPlugin { open: args.param_map, axes, ranges, classes });
+        }
+
+        if let Some(path) = &args.record {
+            let (path, file) = create_file(path, &OutputName { step: 0, seed, creature: None }).unwrap_or_else(|err| {
+                eprintln!("failed to create {}: {}", path.display(), err);
+                std::process::exit(1);
+            });
+            info!("recording input to {}, replay with --seed {}", path.display(), seed);
+            app.add_plugins(RecordPlugin { file });
+        }
+        if let Some(path) = &args.reference {
+            app.add_plugins(ReferencePlugin {
+                path: path.clone(),
+                center: Vec2::new(args.reference_center[0], args.reference_center[1]),
+                width: args.reference_width,
+                opacity: args.reference_opacity,
+            });
+        }
+        if let Some(path) = &args.record_gif {
+            if args.gif_fps <= 0.0 {
+                Args::command().error(ErrorKind::InvalidValue, "--gif-fps must be positive").exit();
+            }
+            let (path, file) = create_file(path, &OutputName { step: 0, seed, creature: None }).unwrap_or_else(|err| {
+                eprintln!("failed to create {}: {}", path.display(), err);
+                std::process::exit(1);
+            });
+            app.add_plugins(GifRecordPlugin {
+                file,
+                path,
+                frames: args.gif_frames as usize,
+                fps: args.gif_fps,
+                downscale: args.gif_downscale,
+            });
+        }
+        if let Some(path) = &args.replay {
+            let records = load_recording(path).unwrap_or_else(|err| {
+                eprintln!("failed to read {}: {}", path.display(), err);
+                std::process::exit(1);
+            });
+            app.add_plugins(ReplayPlugin { records });
+        }
+    }
+
+    if args.wide_sums {
+        app.add_systems(PreUpdate, attach_wide_fields);
+    }
+    if let Some(path) = args.anneal.as_ref().filter(|_| !args.background_sim) {
+        let schedule = PhaseSchedule::load(path).unwrap_or_else(|err| {
+            eprintln!("failed to read {}: {}", path.display(), err);
+            std::process::exit(1);
+        });
+        app.add_plugins(AnnealingPlugin { schedule });
+    }
+    if !args.background_sim {
+        app.add_plugins(GradientCheckPlugin { tolerance: args.gradient_tolerance });
+    }
+    if let Some(path) = args.timeline.as_ref().filter(|_| !args.background_sim) {
+        let timeline = Timeline::load(path).unwrap_or_else(|err| {
+            eprintln!("failed to read {}: {}", path.display(), err);
+            std::process::exit(1);
+        });
+        app.add_plugins(TimelinePlugin { timeline });
+    }
+    if let Some([r_on, r_cut]) = args.kernel_cutoff.as_deref() {
+        if !(0.0 <= *r_on && r_on < r_cut) {
+            Args::command().error(ErrorKind::InvalidValue, "--kernel-cutoff needs 0 <= R_ON < R_CUT").exit();
+        }
+    }
+    if let Some(theta) = args.barnes_hut {
+        if theta <= 0.0 {
+            Args::command().error(ErrorKind::InvalidValue, "--barnes-hut must be positive").exit();
+        }
+        app.insert_resource(BarnesHut { theta });
+    }
+
+    if let Some(mode) = args.boundary {
+        let rect = args.bounds.as_deref().map(|bounds| Rect::new(bounds[0], bounds[1], bounds[2], bounds[3]));
+        if rect.is_none() && !args.fit_bounds {
+            Args::command()
+                .error(ErrorKind::MissingRequiredArgument, "--boundary needs either --bounds or --fit-bounds")
+                .exit();
+        }
+        app.add_plugins(BoundaryPlugin {
+            mode,
+            rect,
+            fit_to_camera: args.fit_bounds,
+        });
+        if !args.headless {
+            app.add_systems(Update, boundary::draw_bounds);
+        }
+        if !args.headless && mode == BoundaryMode::Periodic {
+            app.add_plugins(WrapGhostsPlugin { enabled: args.wrap_ghosts });
+        }
+    }
+
+    if let Some(radius) = args.soft_boundary {
+        let boundary = SoftBoundary { radius, stiffness: args.boundary_stiffness };
+        if !args.background_sim {
+            app.add_plugins(SoftBoundaryPlugin { boundary });
+        }
+        if !args.headless {
+            app.insert_resource(boundary).add_systems(Update, boundary::draw_soft_boundary);
+        }
+    }
+
+    if let Some(thresholds) = args.lifecycle.as_deref() {
+        app.add_plugins(LifecyclePlugin {
+            rules: Lifecycle {
+                birth_threshold: thresholds[0],
+                death_threshold: thresholds[1],
+                starve_steps: args.starve_steps,
+            },
+        });
+    }
+
+    if let Some(max_lifespan) = args.max_lifespan {
+        app.add_plugins(LifespanPlugin { max_lifespan });
+    }
+
+    if (args.emitter.is_some() || !args.headless) && !args.background_sim {
+        if args.emitter_rate <= 0.0 {
+            Args::command().error(ErrorKind::InvalidValue, "--emitter-rate must be positive").exit();
+        }
+        let template = Emitter {
+            position: Vec3::ZERO,
+            rate: args.emitter_rate,
+            initial_velocity: Vec3::new(args.emitter_velocity[0], args.emitter_velocity[1], 0.0),
+            max_particles: args.emitter_max,
+        };
+        app.add_plugins(EmitterPlugin {
+            emitters: args.emitter.as_deref()
+                .map(|position| Emitter { position: Vec3::new(position[0], position[1], 0.0), ..template })
+                .into_iter()
+                .collect(),
+            template,
+            // Shaped like the scene's creatures, whose kinds the command line sets.
+            parameters: Parameters {
+                kernel: args.kernel.clone(),
+                growth_kernel: args.growth_kernel.clone(),
+                ..args.preset.map_or_else(Parameters::default, |preset| preset.parameters())
+            },
+            interactive: !args.headless,
+        });
+    }
+
+    if let Some(profile) = args.target_profile.as_deref().filter(|_| !args.background_sim) {
+        if profile[2] <= 0.0 {
+            Args::command().error(ErrorKind::InvalidValue, "--target-profile needs a positive RADIUS").exit();
+        }
+        app.add_plugins(TargetProfilePlugin {
+            profile: TargetProfile {
+                center: profile[0],
+                edge: profile[1],
+                radius: profile[2],
+                strength: args.target_strength,
+            },
+        });
+    }
+
+    if let Some(target_spread) = args.target_spread.filter(|_| !args.background_sim) {
+        if target_spread <= 0.0 {
+            Args::command().error(ErrorKind::InvalidValue, "--target-spread must be positive").exit();
+        }
+        app.add_plugins(SizeControllerPlugin {
+            controller: SizeController { target_spread, gain: args.size_gain, enabled: true },
+        });
+    }
+
+    if let Some(strength) = args.centroid_force.filter(|_| !args.background_sim) {
+        if args.centroid_range <= 0.0 {
+            Args::command().error(ErrorKind::InvalidValue, "--centroid-range must be positive").exit();
+        }
+        app.add_plugins(CentroidForcePlugin {
+            force: CentroidForce { strength, falloff: args.centroid_falloff.kind(args.centroid_range) },
+        });
+    }
+
+    if !args.background_sim {
+        app.add_plugins(PerturbPlugin {
+            offset: Vec3::new(args.perturb_offset[0], args.perturb_offset[1], 0.0),
+            scheduled: args.perturb_particle.map(|particle| ScheduledPerturbation { step: args.perturb_step, particle }),
+            interactive: !args.headless,
+        });
+    }
+
+    if let Some(max_distance) = args.recenter_distance.filter(|_| !args.background_sim) {
+        app.add_plugins(RecenterPlugin { max_distance });
+    }
+
+    if args.substeps > 1 && !args.background_sim {
+        app.add_plugins(SubstepPlugin);
+    }
+
+    if args.safe_mode && !args.background_sim {
+        app.add_plugins(SafeModePlugin { max_reductions: args.max_dt_reductions });
+    }
+
+    if args.validate_fields {
+        app.add_plugins(FieldValidationPlugin { tolerance: args.validate_tolerance });
+    }
+
+    // Only the cut and tree sums leave anything out to report.
+    let truncated = args.kernel_cutoff.is_some() || args.barnes_hut.is_some();
+    if truncated && (args.truncation_report.is_some() || !args.headless) && !args.background_sim {
+        app.add_plugins(TruncationReportPlugin {
+            every: args.truncation_report,
+            sample: args.truncation_sample as usize,
+            interactive: !args.headless,
+        });
+    } else if args.truncation_report.is_some() && !truncated {
+        Args::command().error(ErrorKind::ArgumentConflict, "--truncation-report needs --kernel-cutoff or --barnes-hut").exit();
+    }
+
+    if args.auto_respawn && !args.background_sim {
+        app.add_plugins(RespawnPlugin { max_spread: args.death_spread });
+    }
+
+    if args.log_timings {
+        app.add_plugins(LogDiagnosticsPlugin {
+            filter: Some(SYSTEM_TIMINGS.iter().map(|(id, _)| *id).collect()),
+            ..default()
+        });
+    }
+
+    if args.log_diagnostics {
+        app.add_systems(Update, log_diagnostics.after(update_position).before(advance_step));
+    } else {
+        app.add_systems(Update, trace_step.after(update_position).before(advance_step));
+    }
+
+    if args.metrics_jsonl {
+        app.add_plugins(MetricsPlugin { every: args.metrics_every, shape: args.shape_metrics });
+    }
+
+    // The window's copy under --background-sim doesn't step, so it isn't held back.
+    if let Some(hz) = args.realtime_cap.filter(|_| !args.background_sim) {
+        if !(hz.is_finite() && hz > 0.0) {
+            Args::command().error(ErrorKind::InvalidValue, "--realtime-cap must be positive").exit();
+        }
+        app.add_plugins(RealtimeCapPlugin { hz });
+    }
+
+    if let Some(every) = args.checkpoint_interval {
+        app.add_plugins(CheckpointPlugin { dir: args.checkpoint_dir.clone(), every, keep: args.checkpoint_keep as usize });
+    }
+
+    #[cfg(feature = "gpu")]
+    let tree_or_gpu = args.barnes_hut.is_some() || args.gpu;
+    #[cfg(not(feature = "gpu"))]
+    let tree_or_gpu = args.barnes_hut.is_some();
+    if args.color_mode == ColorMode::ByNeighborCount && tree_or_gpu {
+        Args::command().error(ErrorKind::ArgumentConflict, "--color-mode by-neighbor-count counts neighbors in the direct pair loop, which --barnes-hut and --gpu skip").exit();
+    }
+
+    if !args.headless || args.rdf_csv.is_some() {
+        let csv = args.rdf_csv.as_ref().map(|path| {
+            let (_, file) = create_file(path, &OutputName { step: 0, seed, creature: None }).unwrap_or_else(|err| {
+                eprintln!("failed to create {}: {}", path.display(), err);
+                std::process::exit(1);
+            });
+            file
+        });
+        app.add_plugins(RadialDistributionPlugin { max_r: args.rdf_max, bins: args.rdf_bins as usize, interactive: !args.headless, csv });
+    }
+
+    if !args.headless || args.encounters_csv.is_some() {
+        let csv = args.encounters_csv.as_ref().map(|path| {
+            let (_, file) = create_file(path, &OutputName { step: 0, seed, creature: None }).unwrap_or_else(|err| {
+                eprintln!("failed to create {}: {}", path.display(), err);
+                std::process::exit(1);
+            });
+            file
+        });
+        app.add_plugins(CloseEncountersPlugin { bins: args.encounter_bins as usize, interactive: !args.headless, csv });
+    }
+
+    if let Some(path) = args.record_bin.as_ref().filter(|_| !args.background_sim) {
+        let (_, file) = create_file(path, &OutputName { step: 0, seed, creature: None }).unwrap_or_else(|err| {
+            eprintln!("failed to create {}: {}", path.display(), err);
+            std::process::exit(1);
+        });
+        app.add_plugins(BinRecordPlugin { file, seed });
+    }
+
+    if let Some(path) = args.trajectory_csv.as_ref().filter(|_| !args.background_sim) {
+        if !(args.log_particle_fraction > 0.0 && args.log_particle_fraction <= 1.0) {
+            Args::command().error(ErrorKind::InvalidValue, "--log-particle-fraction must be in (0, 1]").exit();
+        }
+        let (_, file) = create_file(path, &OutputName { step: 0, seed, creature: None }).unwrap_or_else(|err| {
+            eprintln!("failed to create {}: {}", path.display(), err);
+            std::process::exit(1);
+        });
+        app.add_plugins(TrajectoryPlugin {
+            file,
+            seed,
+            step_stride: args.log_step_stride,
+            particle_fraction: args.log_particle_fraction,
+        });
+    }
+
+    // Last, so it covers the schedules every plugin above added to.
+    if args.deterministic {
+        for (_, schedule) in app.world.resource_mut::<Schedules>().iter_mut() {
+            schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+        }
+    }
+
+    app
+}
+
+// --log-filter is added to the defaults, which quiet wgpu and naga, and
+// RUST_LOG replaces the lot when it's set.
+fn log_plugin(args: &Args) -> LogPlugin {
+    let mut plugin = LogPlugin::default();
+    if let Some(filter) = &args.log_filter {
+        plugin.filter = format!("{},{}", plugin.filter, filter);
+    }
+    plugin
+}
+
+fn attach_wide_fields(mut commands: Commands, particle_query: Query<Entity, Added<Particle>>) {
+    for entity in particle_query.iter() {
+        commands.entity(entity).insert(WideFields::default());
+    }
+}
+
+fn attach_neighbor_counts(mut commands: Commands, particle_query: Query<Entity, Added<Particle>>) {
+    for entity in particle_query.iter() {
+        commands.entity(entity).insert(NeighborCount::default());
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    scene: Option<Res<Scene>>,
+) {
+    let mut camera = Camera2dBundle::default();
+    if let Some(scene) = scene {
+        camera.projection.scale = scene.camera_scale;
+    }
+    commands.spawn(camera);
+}
+
+// Turns the camera back by the angle the frame has turned through, taking the
+// time as the step count times the current dt.
+fn counter_rotate_camera(
+    step: Res<SimStep>,
+    integrator: Res<Integrator>,
+    mut camera_query: Query<&mut Transform, (With<Camera>, Without<MinimapCamera>)>,
+) {
+    let angle = integrator.frame_omega * integrator.dt * step.0 as f32;
+    for mut transform in camera_query.iter_mut() {
+        transform.rotation = Quat::from_rotation_z(-angle);
+    }
+}
+
+fn spawn_scene(
+    mut commands: Commands,
+    integrator: Res<Integrator>,
+    scene: Res<Scene>,
+) {
+    for (index, spawn) in scene.creatures.iter().enumerate() {
+        let mut rng = creature_rng(scene.seed, index, 0);
+        let creature = spawn_creature(&mut commands, &mut rng, spawn, integrator.dt);
+        commands.entity(creature).insert(SceneIndex(index));
+    }
+}
+
+fn spawn_creature(
+    commands: &mut Commands,
+    rng: &mut impl Rng,
+    spawn: &CreatureSpawn,
+    dt: f32,
+) -> Entity {
+    let mut creature = commands.spawn(CreatureBundle {
+        spatial: SpatialBundle {
+            transform: Transform::from_translation(spawn.position),
+            ..default()
+        },
+        parameters: spawn.parameters.clone(),
+        time_scale: TimeScale(spawn.time_scale),
+        stats: CreatureStats::default(),
+        creature: Creature,
+    });
+    if let Some(animation) = &spawn.animation {
+        creature.insert(animation.clone());
+    }
+    if let Some(oscillation) = &spawn.oscillation {
+        creature.insert(oscillation.clone());
+    }
+    creature.with_children(|parent| {
+        let mut positions: Vec<Vec3> = match &spawn.pattern {
+            SpawnPattern::Disk { radius, .. } | SpawnPattern::ByMass { radius, .. } => (0..spawn.pattern.count())
+                .map(|_| {
+                    let r = radius * rng.gen::<f32>().sqrt();
+                    let theta = rng.gen::<f32>() * TAU;
+                    Vec3::new(r * theta.cos(), r * theta.sin(), 0.0)
+                })
+                .collect(),
+            SpawnPattern::Grid { count, spacing } => {
+                let side = (*count as f32).sqrt().ceil() as usize;
+                let lattice: Vec<Vec3> = (0..*count)
+                    .map(|i| Vec3::new((i % side) as f32, (i / side) as f32, 0.0) * *spacing)
+                    .collect();
+                // Centre on the mean so a partly filled last row doesn't shift the creature.
+                let centroid = lattice.iter().sum::<Vec3>() / lattice.len().max(1) as f32;
+                lattice.into_iter().map(|x| x - centroid).collect()
+            }
+            SpawnPattern::Density { count, radius, density } => density.sample(rng, *count, *radius),
+            SpawnPattern::Explicit(positions) => positions.clone(),
+            SpawnPattern::RotationalSymmetry { fold, base_count } => {
+                let wedge = TAU / *fold as f32;
+                let base: Vec<Vec3> = (0..*base_count)
+                    .map(|_| {
+                        let r = DISK_RADIUS * rng.gen::<f32>().sqrt();
+                        let theta = rng.gen::<f32>() * wedge;
+                        Vec3::new(r * theta.cos(), r * theta.sin(), 0.0)
+                    })
+                    .collect();
+                // Turned about the origin, which is then the centroid.
+                (0..*fold)
+                    .flat_map(|k| {
+                        let rotation = Quat::from_rotation_z(k as f32 * wedge);
+                        base.iter().map(move |x| rotation * *x)
+                    })
+                    .collect()
+            }
+        };
+
+        let jitter = spawn.jitter.unwrap_or_else(|| spawn.pattern.default_jitter());
+        if jitter > 0.0 {
+            for position in positions.iter_mut() {
+                position.x += rng.gen_range(-jitter..=jitter);
+                position.y += rng.gen_range(-jitter..=jitter);
+            }
+        }
+        probe::settle(&mut positions, &spawn.parameters, spawn.settle_steps, dt);
+
+        for position in positions {
+
+            parent.spawn(ParticleBundle {
+                spatial: SpatialBundle {
+                    transform: Transform::from_translation(position),
+                    ..default()
+                },
+                fields: Fields::default(),
+                mass: Mass(spawn.pattern.particle_mass()),
+                velocity: Velocity(spawn.initial_velocity),
+                particle: Particle,
+            });
+        }
+    });
+    creature.id()
+}
+
+fn attach_particle_meshes(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    asset_server: Res<AssetServer>,
+    style: Res<ParticleStyle>,
+    particle_query: Query<Entity, Added<Particle>>,
+) {
+    let texture = match style.shape {
+        ParticleShape::Sprite => style.texture.as_ref().map(|path| asset_server.load(path.as_str())),
+        _ => None,
+    };
+
+    for entity in particle_query.iter() {
+        commands.entity(entity).insert((
+            Mesh2dHandle(meshes.add(style.shape.mesh(0.5, style.circle_vertices))),
+            materials.add(ColorMaterial {
+                color: Color::WHITE,
+                texture: texture.clone(),
+            }),
+            DisplayRadius::default(),
+        ));
+    }
+}
+
+fn add_repulsion(fields: &mut Fields, wide: Option<&mut WideFields>, R: f32, R_grad: Vec3) {
+    match wide {
+        Some(wide) => {
+            wide.R_val += R as f64;
+            wide.R_grad += R_grad.as_dvec3();
+        }
+        None => {
+            fields.R_val += R;
+            fields.R_grad += R_grad;
+        }
+    }
+}
+
+fn add_kernel(fields: &mut Fields, wide: Option<&mut WideFields>, K: f32, K_grad: Vec3) {
+    match wide {
+        Some(wide) => {
+            wide.U_val += K as f64;
+            wide.U_grad += K_grad.as_dvec3();
+        }
+        None => {
+            fields.U_val += K;
+            fields.U_grad += K_grad;
+        }
+    }
+}
+
+fn repulsion_field(r: f32, c_rep: f32) -> (f32, f32) {
+    let t = f32::max(0.0, 1.0 - r);
+    (0.5 * c_rep * t * t, -c_rep * t)
+}
+
+// Once the bump has underflowed to 0 so has its slope, and is returned as
+// such rather than as the product of the then infinite `t` and 0, which is
+// NaN, as it can be far out on a narrow bump.
+fn radial_field(x: f32, mu: f32, sigma: f32, w: f32) -> (f32, f32) {
+    let t = (x - mu) / sigma;
+    let y = w * (-t * t).exp();
+    if y == 0.0 {
+        return (0.0, 0.0);
+    }
+    (y, -2.0 * t * y / sigma)
+}
+
+// Cubic Hermite curve through the control points, so both the value and its
+// derivative are continuous and the forces don't jump as a particle crosses a
+// control point. Interior tangents are the spacing-weighted mean of the slopes
+// on either side; the end tangents are flat, matching the constant value the
+// curve keeps past either end.
+fn spline_field(points: &[(f32, f32)], x: f32) -> (f32, f32) {
+    let (Some(first), Some(last)) = (points.first(), points.last()) else {
+        return (0.0, 0.0);
+    };
+    if x <= first.0 {
+        return (first.1, 0.0);
+    }
+    if x >= last.0 {
+        return (last.1, 0.0);
+    }
+
+    let tangent = |i: usize| {
+        if i == 0 || i == points.len() - 1 {
+            return 0.0;
+        }
+        let ((x_0, y_0), (x_1, y_1), (x_2, y_2)) = (points[i - 1], points[i], points[i + 1]);
+        let (h_0, h_1) = (x_1 - x_0, x_2 - x_1);
+        if h_0 <= 0.0 || h_1 <= 0.0 {
+            return 0.0;
+        }
+        ((y_2 - y_1) / h_1 * h_0 + (y_1 - y_0) / h_0 * h_1) / (h_0 + h_1)
+    };
+
+    let i = points.partition_point(|(x_i, _)| *x_i <= x) - 1;
+    let ((x_0, y_0), (x_1, y_1)) = (points[i], points[i + 1]);
+    let h = x_1 - x_0;
+    if h <= 0.0 {
+        return (y_1, 0.0);
+    }
+    let (m_0, m_1) = (tangent(i), tangent(i + 1));
+    let t = (x - x_0) / h;
+    let (t2, t3) = (t * t, t * t * t);
+    let y = (2.0 * t3 - 3.0 * t2 + 1.0) * y_0 + (t3 - 2.0 * t2 + t) * h * m_0
+        + (-2.0 * t3 + 3.0 * t2) * y_1 + (t3 - t2) * h * m_1;
+    let dy = (6.0 * t2 - 6.0 * t) * (y_0 - y_1) / h + (3.0 * t2 - 4.0 * t + 1.0) * m_0 + (3.0 * t2 - 2.0 * t) * m_1;
+    (y, dy)
+}
+
+/// Distance past which the repulsion and the kernel have both fallen below
+/// `tol` of their peaks: `mu_k + sigma_k sqrt(ln(1 / tol))` for a Gaussian,
+/// where the quartic and the outer of the two rings reach it, and the last
+/// control point of a spline.
+pub fn effective_radius(parameters: &Parameters, tol: f32) -> f32 {
+    let tol = tol.clamp(f32::MIN_POSITIVE, 1.0);
+    let repulsion = 1.0 - tol.sqrt();
+    let kernel = match &parameters.kernel {
+        KernelKind::Gaussian | KernelKind::TwoRings => parameters.mu_k + parameters.sigma_k * (-tol.ln()).sqrt(),
+        // Solving (1 - t^2 / 4)^4 = tol for t.
+        KernelKind::Quartic => parameters.mu_k + parameters.sigma_k * 2.0 * (1.0 - tol.powf(0.25)).sqrt(),
+        KernelKind::Spline(points) => points.last().map_or(0.0, |(x, _)| *x),
+    };
+    f32::max(repulsion, kernel)
+}
+
+// Sums the repulsion and kernel fields over every pair of particles, leaving
+// the energy gradient to `calculate_fields`.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn sum_pair_fields(
+    mut timers: Diagnostics,
+    inter_creature: Res<InterCreature>,
+    metric: Res<MetricScale>,
+    matrix: Res<InteractionMatrix>,
+    cutoff: Res<KernelCutoff>,
+    self_field: Res<SelfField>,
+    barnes_hut: Option<Res<BarnesHut>>,
+    mut rdf: Option<ResMut<RadialDistribution>>,
+    mut encounters: Option<ResMut<CloseEncounters>>,
+    mut force_bands: Option<ResMut<ForceBands>>,
+    neighbor_radius: Option<Res<NeighborRadius>>,
+    mut diagnostics: ResMut<StepDiagnostics>,
+    creature_query: Query<(&Transform, &Parameters, &Children), With<Creature>>,
+    mut particle_query: Query<(&Transform, &mut Fields, Option<&mut WideFields>, Option<&mut NeighborCount>), With<Particle>>,
+    species_query: Query<&Species, With<Particle>>,
+) {
+    let species = |entity: &Entity| species_query.get(*entity).copied().unwrap_or_default();
+
+    let _timer = SystemTimer::start(&mut timers, timings::SUM_PAIR_FIELDS);
+    let mut min_pair_distance = f32::INFINITY;
+    let mut histogram = rdf.as_deref_mut().filter(|rdf| rdf.recording);
+    if let Some(histogram) = histogram.as_deref_mut() {
+        histogram.start_sample();
+    }
+    let mut close_encounters = 0;
+    if let Some(encounters) = encounters.as_deref_mut() {
+        encounters.start_step();
+    }
+    let mut bands = force_bands.as_deref_mut().filter(|bands| bands.particle.is_some());
+    if let Some(bands) = bands.as_deref_mut() {
+        bands.clear();
+    }
+
+    for (_, parameters, children) in creature_query.iter() {
+        let neighbor_radius = neighbor_radius.as_ref().and_then(|radius| radius.0)
+            .unwrap_or_else(|| effective_radius(parameters, (-4.0f32).exp()));
+        for child in children.iter() {
+            let (_, mut fields, wide, neighbors) = particle_query.get_mut(*child).unwrap();
+            if let Some(mut neighbors) = neighbors {
+                neighbors.0 = 0;
+            }
+            fields.R_val = if self_field.0 { repulsion_field(0.0, parameters.c_rep).0 } else { 0.0 };
+            fields.R_grad = Vec3::ZERO;
+            
+            fields.U_val = match self_field.0 {
+                false => 0.0,
+                true if matrix.is_empty() => parameters.kernel_field(0.0).0,
+                true => matrix.kernel_field(parameters, species(child), species(child), 0.0).0,
+            };
+            fields.U_grad = Vec3::ZERO;
+
+            fields.E_grad = Vec3::ZERO;
+
+            if let Some(mut wide) = wide {
+                *wide = WideFields {
+                    R_val: fields.R_val as f64,
+                    U_val: fields.U_val as f64,
+                    ..default()
+                };
+            }
+        }
+
+        // The tree is only used without species, wide sums or a metric.
+        if let Some(barnes_hut) = &barnes_hut {
+            let positions: Vec<_> = children.iter().map(|child| particle_query.get(*child).unwrap().0.translation).collect();
+            let (sums, min_distance, close) = barnes_hut::sum_fields(parameters, &positions, barnes_hut.theta);
+            min_pair_distance = min_pair_distance.min(min_distance);
+            close_encounters += close.len() as u64;
+            if let Some(encounters) = encounters.as_deref_mut() {
+                close.into_iter().for_each(|r| encounters.record(r));
+            }
+            for (child, sum) in iter::zip(children.iter(), sums) {
+                let (_, mut fields, ..) = particle_query.get_mut(*child).unwrap();
+                add_repulsion(&mut fields, None, sum.R_val, sum.R_grad);
+                add_kernel(&mut fields, None, sum.U_val, sum.U_grad);
+            }
+            continue;
+        }
+
+        for (child_i, child_j) in children.iter()
+            .enumerate()
+            .flat_map(|(index, child)| iter::zip(
+                iter::repeat(child),
+                children.iter().skip(index + 1),
+            ))
+        {
+            let [(transform_i, mut fields_i, mut wide_i, neighbors_i), (transform_j, mut fields_j, mut wide_j, neighbors_j)] = particle_query.get_many_mut([*child_i, *child_j]).unwrap();
+            
+            let (r, r_grad) = metric.distance(transform_i.translation - transform_j.translation);
+            min_pair_distance = min_pair_distance.min(r);
+            if r < neighbor_radius {
+                for mut neighbors in [neighbors_i, neighbors_j].into_iter().flatten() {
+                    neighbors.0 += 1;
+                }
+            }
+            if let Some(histogram) = histogram.as_deref_mut() {
+                histogram.record(r);
+            }
+
+            if r < 1.0 {
+                close_encounters += 1;
+                if let Some(encounters) = encounters.as_deref_mut() {
+                    encounters.record(r);
+                }
+                let (R, dR) = repulsion_field(r, parameters.c_rep);
+                add_repulsion(&mut fields_i, wide_i.as_deref_mut(), R, r_grad * dR);
+                add_repulsion(&mut fields_j, wide_j.as_deref_mut(), R, -r_grad * dR);
+                if let Some(bands) = bands.as_deref_mut() {
+                    bands.add_repulsion([*child_i, *child_j], r, [r_grad * dR, -r_grad * dR]);
+                }
+            }
+
+            if !cutoff.reaches(r) {
+                continue;
+            }
+            if matrix.is_empty() {
+                let (K, dK) = cutoff.apply(r, parameters.kernel_field(r));
+                add_kernel(&mut fields_i, wide_i.as_deref_mut(), K, r_grad * dK);
+                add_kernel(&mut fields_j, wide_j.as_deref_mut(), K, -r_grad * dK);
+                if let Some(bands) = bands.as_deref_mut() {
+                    bands.add_kernel([*child_i, *child_j], r, [r_grad * dK, -r_grad * dK]);
+                }
+            } else {
+                // Each side feels the other through its own row of the matrix.
+                let (species_i, species_j) = (species(child_i), species(child_j));
+                let (K_i, dK_i) = cutoff.apply(r, matrix.kernel_field(parameters, species_i, species_j, r));
+                let (K_j, dK_j) = cutoff.apply(r, matrix.kernel_field(parameters, species_j, species_i, r));
+                add_kernel(&mut fields_i, wide_i.as_deref_mut(), K_i, r_grad * dK_i);
+                add_kernel(&mut fields_j, wide_j.as_deref_mut(), K_j, -r_grad * dK_j);
+                if let Some(bands) = bands.as_deref_mut() {
+                    bands.add_kernel([*child_i, *child_j], r, [r_grad * dK_i, -r_grad * dK_j]);
+                }
+            }
+        }
+    }
+
+    if inter_creature.enabled {
+        for [(creature_a, parameters_a, children_a), (creature_b, parameters_b, children_b)] in creature_query.iter_combinations() {
+            let c_rep = 0.5 * (parameters_a.c_rep + parameters_b.c_rep);
+
+            for (child_i, child_j) in children_a.iter()
+                .flat_map(|child| iter::zip(iter::repeat(child), children_b.iter()))
+            {
+                let [(transform_i, mut fields_i, mut wide_i, _), (transform_j, mut fields_j, mut wide_j, _)] = particle_query.get_many_mut([*child_i, *child_j]).unwrap();
+
+                let x_i = creature_a.translation + transform_i.translation;
+                let x_j = creature_b.translation + transform_j.translation;
+                let (r, r_grad) = metric.distance(x_i - x_j);
+                min_pair_distance = min_pair_distance.min(r);
+
+                if r < 1.0 {
+                    close_encounters += 1;
+                    if let Some(encounters) = encounters.as_deref_mut() {
+                        encounters.record(r);
+                    }
+                    let (R, dR) = repulsion_field(r, c_rep);
+                    add_repulsion(&mut fields_i, wide_i.as_deref_mut(), R, r_grad * dR);
+                    add_repulsion(&mut fields_j, wide_j.as_deref_mut(), R, -r_grad * dR);
+                    if let Some(bands) = bands.as_deref_mut() {
+                        bands.add_repulsion([*child_i, *child_j], r, [r_grad * dR, -r_grad * dR]);
+                    }
+                }
+            }
+        }
+    }
+
+    diagnostics.min_pair_distance = min_pair_distance;
+    diagnostics.close_encounters = close_encounters;
+}
+
+fn calculate_fields(
+    mut timers: Diagnostics,
+    force_terms: Res<ForceTerms>,
+    ramp: Res<InteractionRamp>,
+    step: Res<SimStep>,
+    creature_query: Query<(&Parameters, &Children), With<Creature>>,
+    mut particle_query: Query<(&mut Fields, Option<&WideFields>), With<Particle>>,
+    growth_query: Query<&GrowthOverride, With<Particle>>,
+) {
+    let _timer = SystemTimer::start(&mut timers, timings::CALCULATE_FIELDS);
+    let strength = ramp.factor(step.0);
+    for (parameters, children) in creature_query.iter() {
+        for child in children.iter() {
+            let (mut fields, wide) = particle_query.get_mut(*child).unwrap();
+            if let Some(wide) = wide {
+                fields.R_val = wide.R_val as f32;
+                fields.R_grad = wide.R_grad.as_vec3();
+                fields.U_val = wide.U_val as f32;
+                fields.U_grad = wide.U_grad.as_vec3();
+            }
+
+            let mut E_grad = if force_terms.repulsion { fields.R_grad } else { Vec3::ZERO };
+            if force_terms.growth {
+                let (_, dG) = match growth_query.get(*child) {
+                    Ok(growth) => growth.growth_field(parameters, fields.U_val),
+                    Err(_) => parameters.growth_field(fields.U_val),
+                };
+                E_grad -= dG * fields.U_grad;
+            }
+            fields.E_grad = strength * E_grad;
+        }
+    }
+}
+
+fn clamp_forces(
+    force_limit: Res<ForceLimit>,
+    mut particle_query: Query<&mut Fields, With<Particle>>,
+) {
+    let Some(max_force) = force_limit.0 else {
+        return;
+    };
+    for mut fields in particle_query.iter_mut() {
+        if fields.E_grad.length_squared() > max_force * max_force {
+            fields.E_grad = fields.E_grad.clamp_length_max(max_force);
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_position(
+    mut timers: Diagnostics,
+    integrator: Res<Integrator>,
+    mut diagnostics: ResMut<StepDiagnostics>,
+    creature_query: Query<(&Transform, &TimeScale, &Children), With<Creature>>,
+    mut particle_query: Query<(&mut Transform, &mut Velocity, &Fields, Has<Pinned>), (With<Particle>, Without<Creature>)>,
+) {
+    let _timer = SystemTimer::start(&mut timers, timings::UPDATE_POSITION);
+    let mut max_force: f32 = 0.0;
+
+    for (creature_transform, time_scale, children) in creature_query.iter() {
+        let dt = integrator.dt * time_scale.0 / integrator.substeps as f32;
+        for child in children.iter() {
+            let Ok((mut transform, mut velocity, fields, pinned)) = particle_query.get_mut(*child) else {
+                continue;
+            };
+            max_force = max_force.max(fields.E_grad.length());
+            if pinned {
+                velocity.0 = Vec3::ZERO;
+                continue;
+            }
+            let friction = integrator.density_damping * fields.R_val;
+            let E_grad = fields.E_grad - integrator.frame_force(creature_transform.translation + transform.translation, velocity.0);
+            velocity.0 = match integrator.dynamics {
+                // Friction lowers the mobility, shortening the effective step.
+                Dynamics::Overdamped => -E_grad / (1.0 + friction),
+                Dynamics::Momentum { damping } => velocity.0 + dt * (-E_grad - (damping + friction) * velocity.0),
+            };
+            transform.translation += dt * velocity.0;
+        }
+    }
+
+    diagnostics.max_force = max_force;
+}
+
+fn log_diagnostics(
+    step: Res<SimStep>,
+    diagnostics: Res<StepDiagnostics>,
+) {
+    info!(
+        "step {}: min pair distance {:.4}, {} close encounters, max |E_grad| {:.4}",
+        step.0, diagnostics.min_pair_distance, diagnostics.close_encounters, diagnostics.max_force,
+    );
+}
+
+// The same summary as --log-diagnostics, at trace level and with the values
+// as fields, e.g. for RUST_LOG=particle_lenia=trace.
+fn trace_step(
+    step: Res<SimStep>,
+    diagnostics: Res<StepDiagnostics>,
+) {
+    trace!(
+        step = step.0,
+        min_pair_distance = diagnostics.min_pair_distance,
+        close_encounters = diagnostics.close_encounters,
+        max_force = diagnostics.max_force,
+        "step",
+    );
+}
+
+fn advance_step(mut step: ResMut<SimStep>) {
+    step.0 += 1;
+}
+
+fn toggle_growth(
+    keys: Res<Input<KeyCode>>,
+    mut force_terms: ResMut<ForceTerms>,
+) {
+    if keys.just_pressed(KeyCode::G) {
+        force_terms.growth = !force_terms.growth;
+        info!("growth field {}", if force_terms.growth { "enabled" } else { "disabled" });
+    }
+}
+
+fn cycle_size_mode(
+    keys: Res<Input<KeyCode>>,
+    mut size_mode: ResMut<SizeMode>,
+) {
+    if keys.just_pressed(KeyCode::S) {
+        *size_mode = size_mode.next();
+        info!("size mode: {:?}", *size_mode);
+    }
+}
+
+fn toggle_radius_smoothing(
+    keys: Res<Input<KeyCode>>,
+    mut smoothing: ResMut<RadiusSmoothing>,
+) {
+    if keys.just_pressed(KeyCode::R) {
+        smoothing.0 = match smoothing.0 {
+            Some(_) => None,
+            None => RadiusSmoothing::default().0,
+        };
+        info!("radius smoothing: {:?}", smoothing.0);
+    }
+}
+
+fn apply_z_order(
+    z_order: Res<ZOrderMode>,
+    mut particle_query: Query<(&Fields, &mut GlobalTransform), With<Particle>>,
+) {
+    if *z_order == ZOrderMode::Off {
+        return;
+    }
+    for (fields, mut global_transform) in particle_query.iter_mut() {
+        let mut affine = global_transform.affine();
+        affine.translation.z += z_order.depth(fields);
+        *global_transform = affine.into();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_size(
+    mut timers: Diagnostics,
+    time: Res<Time>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    size_mode: Res<SizeMode>,
+    smoothing: Res<RadiusSmoothing>,
+    min_radius: Res<MinRenderRadius>,
+    style: Res<ParticleStyle>,
+    view_bounds: ViewBounds,
+    projection_query: Query<&OrthographicProjection, Without<MinimapCamera>>,
+    creature_query: Query<(&Transform, &Parameters, &Children, &Visibility), With<Creature>>,
+    mut particle_query: Query<(&Transform, &Mesh2dHandle, &Fields, &Mass, &mut DisplayRadius), With<Particle>>,
+) {
+    let _timer = SystemTimer::start(&mut timers, timings::UPDATE_SIZE);
+    let visible_rects = view_bounds.rects();
+    // Fraction of the remaining gap closed this frame, independent of frame rate.
+    let alpha = smoothing.0.map_or(1.0, |tau| 1.0 - (-time.delta_seconds() / tau).exp());
+    // World units a logical pixel spans, in the most zoomed-out view.
+    let pixel = projection_query.iter().map(|projection| projection.scale).fold(0.0, f32::max);
+    let min_radius = min_radius.0 * pixel;
+
+    for (creature_transform, parameters, children, visibility) in creature_query.iter() {
+        // Drawn as a single blob when zoomed out far enough.
+        if visibility == Visibility::Hidden {
+            continue;
+        }
+        for child in children.iter() {
+            let Ok((transform, mesh, fields, mass, mut display_radius)) = particle_query.get_mut(*child) else {
+                continue;
+            };
+            if !is_visible(&visible_rects, (creature_transform.translation + transform.translation).truncate()) {
+                continue;
+            }
+            let target = size_mode.radius(parameters, fields, mass);
+            let r = display_radius.0.map_or(target, |r| r + (target - r) * alpha).max(min_radius);
+            display_radius.0 = Some(r);
+            let _ = meshes.set(&mesh.0, style.shape.mesh(r, style.circle_vertices));
+        }
+    }
+}
\ No newline at end of file
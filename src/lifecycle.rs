@@ -0,0 +1,113 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    advance_step, spawner::ParticleBudget, species::Species, update_position, Creature, Fields, Mass,
+    Particle, ParticleBundle, SimRng, Velocity,
+};
+
+// Distance from its parent a newborn particle is placed at, well inside the
+// repulsion range so the two push apart over the next few steps.
+const BIRTH_OFFSET: f32 = 0.5;
+
+/// Birth and death rules: particles denser than `birth_threshold` bud a new
+/// particle, and particles sparser than `death_threshold` for `starve_steps`
+/// consecutive steps die.
+#[derive(Resource, Clone, Debug)]
+pub struct Lifecycle {
+    pub birth_threshold: f32,
+    pub death_threshold: f32,
+    pub starve_steps: u32,
+}
+
+/// Consecutive steps a particle has spent below the death threshold.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct Starving(pub u32);
+
+pub struct LifecyclePlugin {
+    pub rules: Lifecycle,
+}
+
+impl Plugin for LifecyclePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.rules.clone())
+            .add_systems(Update, birth_and_death.after(update_position).before(advance_step));
+    }
+}
+
+// Births are capped by the particle budget, or when there is none, by the
+// particle count when the rules first ran, so the total stays roughly constant.
+#[allow(clippy::too_many_arguments)]
+fn birth_and_death(
+    mut commands: Commands,
+    rules: Res<Lifecycle>,
+    budget: Res<ParticleBudget>,
+    mut initial_count: Local<Option<usize>>,
+    mut rng: ResMut<SimRng>,
+    creature_query: Query<(Entity, &Children), With<Creature>>,
+    mut particle_query: Query<(&Transform, &Fields, &Velocity, Option<&mut Starving>), With<Particle>>,
+    species_query: Query<&Species, With<Particle>>,
+) {
+    let total: usize = creature_query.iter().map(|(_, children)| children.len()).sum();
+    let cap = budget.0.unwrap_or_else(|| *initial_count.get_or_insert(total));
+
+    let mut deaths = 0;
+    let mut births = Vec::new();
+    // In spawn order, which a reloaded state keeps, so newborns draw
+    // from the RNG in the same order.
+    let mut creatures: Vec<_> = creature_query.iter().collect();
+    creatures.sort_by_key(|(creature, _)| *creature);
+    for (creature, children) in creatures {
+        for child in children.iter() {
+            let Ok((transform, fields, velocity, starving)) = particle_query.get_mut(*child) else {
+                continue;
+            };
+
+            if fields.U_val < rules.death_threshold {
+                let steps = starving.map_or(1, |mut starving| {
+                    starving.0 += 1;
+                    starving.0
+                });
+                if steps == 1 {
+                    commands.entity(*child).insert(Starving(1));
+                }
+                if steps >= rules.starve_steps {
+                    commands.entity(*child).despawn_recursive();
+                    deaths += 1;
+                }
+                continue;
+            }
+            if let Some(mut starving) = starving {
+                starving.0 = 0;
+            }
+
+            if fields.U_val > rules.birth_threshold {
+                births.push((creature, transform.translation, velocity.0, species_query.get(*child).ok().copied()));
+            }
+        }
+    }
+
+    let room = (cap + deaths).saturating_sub(total);
+    for (creature, position, velocity, species) in births.into_iter().take(room) {
+        let theta = rng.0.gen::<f32>() * TAU;
+        let offset = BIRTH_OFFSET * Vec3::new(theta.cos(), theta.sin(), 0.0);
+        commands.entity(creature).with_children(|parent| {
+            let mut child = parent.spawn(ParticleBundle {
+                spatial: SpatialBundle {
+                    transform: Transform::from_translation(position + offset),
+                    ..default()
+                },
+                fields: Fields::default(),
+                mass: Mass::default(),
+                velocity: Velocity(velocity),
+                particle: Particle,
+            });
+            // Newborns take after their parent.
+            if let Some(species) = species {
+                child.insert(species);
+            }
+        });
+    }
+}
@@ -0,0 +1,118 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    advance_step, spawner::ParticleBudget, species::Species, update_position, Creature, Fields, Mass,
+    Particle, ParticleBundle, SimRng, Velocity,
+};
+
+// Distance from the centroid replacements are scattered within, so they
+// don't land on top of each other.
+const REPLACEMENT_SCATTER: f32 = 0.5;
+
+/// Steps a particle has lived.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Age(pub u32);
+
+/// Despawns every particle once it's `max_lifespan` steps old and spawns a
+/// replacement of the same species near its creature's centroid, for a steady
+/// turnover of a creature's particles at a constant count. Particles that
+/// exist when it first runs get random ages, so they don't all go at once,
+/// unless they were loaded with the ages they had when saved.
+pub struct LifespanPlugin {
+    pub max_lifespan: u32,
+}
+
+#[derive(Resource)]
+struct MaxLifespan(u32);
+
+impl Plugin for LifespanPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(MaxLifespan(self.max_lifespan.max(1)))
+            .add_systems(Update, age_out.after(update_position).before(advance_step));
+    }
+}
+
+// Replacements are capped by the particle budget, or when there is none, by
+// the particle count when this first ran.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn age_out(
+    mut commands: Commands,
+    max_lifespan: Res<MaxLifespan>,
+    budget: Res<ParticleBudget>,
+    mut initial_count: Local<Option<usize>>,
+    mut seen: Local<bool>,
+    mut rng: ResMut<SimRng>,
+    creature_query: Query<(Entity, &Children), With<Creature>>,
+    mut particle_query: Query<(&Transform, Option<&mut Age>, Option<&Species>), With<Particle>>,
+) {
+    let total: usize = creature_query.iter().map(|(_, children)| children.len()).sum();
+    let cap = budget.0.unwrap_or_else(|| *initial_count.get_or_insert(total));
+
+    // A state loaded with its ages carries on from them instead.
+    let stagger = !*seen && particle_query.iter().all(|(_, age, _)| age.is_none());
+
+    let mut deaths = 0;
+    let mut replacements = Vec::new();
+    // In spawn order, which a reloaded state keeps, so replacements draw
+    // from the RNG in the same order.
+    let mut creatures: Vec<_> = creature_query.iter().collect();
+    creatures.sort_by_key(|(creature, _)| *creature);
+    for (creature, children) in creatures {
+        let mut centroid = Vec3::ZERO;
+        let mut dying = Vec::new();
+        for child in children.iter() {
+            let Ok((transform, age, species)) = particle_query.get_mut(*child) else {
+                continue;
+            };
+            centroid += transform.translation;
+
+            let Some(mut age) = age else {
+                // Staggered at first, and from birth for particles spawned since.
+                let start = if stagger { rng.0.gen_range(0..max_lifespan.0) } else { 0 };
+                commands.entity(*child).insert(Age(start));
+                continue;
+            };
+            age.0 += 1;
+            if age.0 >= max_lifespan.0 {
+                dying.push((*child, species.copied()));
+            }
+        }
+        if children.is_empty() {
+            continue;
+        }
+        centroid /= children.len() as f32;
+
+        for (child, species) in dying {
+            commands.entity(child).despawn_recursive();
+            deaths += 1;
+            replacements.push((creature, centroid, species));
+        }
+    }
+    *seen = true;
+
+    let room = (cap + deaths).saturating_sub(total);
+    for (creature, centroid, species) in replacements.into_iter().take(room) {
+        let offset = REPLACEMENT_SCATTER * rng.0.gen::<f32>().sqrt() * Vec2::from_angle(rng.0.gen::<f32>() * TAU);
+        commands.entity(creature).with_children(|parent| {
+            let mut child = parent.spawn((
+                ParticleBundle {
+                    spatial: SpatialBundle {
+                        transform: Transform::from_translation(centroid + offset.extend(0.0)),
+                        ..default()
+                    },
+                    fields: Fields::default(),
+                    mass: Mass::default(),
+                    velocity: Velocity::default(),
+                    particle: Particle,
+                },
+                Age(0),
+            ));
+            if let Some(species) = species {
+                child.insert(species);
+            }
+        });
+    }
+}
@@ -0,0 +1,125 @@
+use bevy::{prelude::*, sprite::Mesh2dHandle, utils::HashSet};
+
+use crate::{
+    colormap::{ColorMode, Colormaps},
+    minimap::MinimapCamera,
+    stats::CreatureStats,
+    update_position, Creature, Fields, NeighborCount, Particle, ParticleStyle,
+};
+
+// Smallest radius a blob is drawn with, so a creature that has collapsed to a
+// point stays visible.
+const MIN_BLOB_RADIUS: f32 = 0.5;
+
+/// Once the camera's projection scale passes `scale`, hides every creature's
+/// particles and draws each creature as a single disk at its centroid, as
+/// wide as its spread, colored by the mean over its particles of the field
+/// the color mode shows. Only the drawing changes; the simulation still steps
+/// every particle.
+pub struct LodPlugin {
+    pub scale: f32,
+}
+
+#[derive(Resource)]
+struct LodScale(f32);
+
+// The blob standing in for a creature, spawned the first time it's needed.
+#[derive(Component)]
+struct CreatureBlob(Entity);
+
+#[derive(Component)]
+struct Blob;
+
+impl Plugin for LodPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(LodScale(self.scale))
+            .add_systems(Update, (spawn_blobs, update_blobs).chain().after(update_position));
+    }
+}
+
+fn spawn_blobs(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    style: Res<ParticleStyle>,
+    mut mesh: Local<Option<Handle<Mesh>>>,
+    creature_query: Query<Entity, (With<Creature>, Without<CreatureBlob>)>,
+) {
+    let mesh = mesh.get_or_insert_with(|| meshes.add(style.shape.mesh(1.0, style.circle_vertices))).clone();
+    for creature in creature_query.iter() {
+        let blob = commands.spawn((
+            ColorMesh2dBundle {
+                mesh: Mesh2dHandle(mesh.clone()),
+                material: materials.add(ColorMaterial::from(Color::WHITE)),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            Blob,
+        )).id();
+        commands.entity(creature).insert(CreatureBlob(blob));
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn update_blobs(
+    mut commands: Commands,
+    lod_scale: Res<LodScale>,
+    mode: Res<ColorMode>,
+    colormaps: Res<Colormaps>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    projection_query: Query<&OrthographicProjection, Without<MinimapCamera>>,
+    mut creature_query: Query<(&CreatureStats, &CreatureBlob, &Children, &mut Visibility), (With<Creature>, Without<Blob>)>,
+    particle_query: Query<(&Fields, Option<&NeighborCount>), With<Particle>>,
+    mut blob_query: Query<(Entity, &mut Transform, &Handle<ColorMaterial>, &mut Visibility), With<Blob>>,
+) {
+    let scale = projection_query.iter().map(|projection| projection.scale).fold(0.0, f32::max);
+    let coarse = scale > lod_scale.0;
+
+    // Blobs of creatures that have since died or merged go.
+    let owned: HashSet<Entity> = creature_query.iter().map(|(_, blob, ..)| blob.0).collect();
+    for (entity, ..) in blob_query.iter() {
+        if !owned.contains(&entity) {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    let particles = if coarse { Visibility::Hidden } else { Visibility::Inherited };
+    for (.., mut visibility) in creature_query.iter_mut() {
+        if *visibility != particles {
+            *visibility = particles;
+        }
+    }
+    if !coarse {
+        for (.., mut visibility) in blob_query.iter_mut() {
+            *visibility = Visibility::Hidden;
+        }
+        return;
+    }
+
+    let means: Vec<Option<f32>> = creature_query.iter()
+        .map(|(_, _, children, _)| {
+            let (sum, count) = particle_query.iter_many(children)
+                .filter_map(|(fields, neighbors)| mode.value(fields, neighbors))
+                .fold((0.0, 0), |(sum, count), value| (sum + value, count + 1));
+            (count > 0).then(|| sum / count as f32)
+        })
+        .collect();
+    let (min, max) = means.iter().flatten().fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), mean| (min.min(*mean), max.max(*mean)));
+    let range = (max - min).max(f32::EPSILON);
+
+    for ((stats, blob, ..), mean) in creature_query.iter().zip(means) {
+        let Ok((_, mut transform, material, mut visibility)) = blob_query.get_mut(blob.0) else {
+            continue;
+        };
+        if !stats.centroid.is_finite() {
+            *visibility = Visibility::Hidden;
+            continue;
+        }
+        *visibility = Visibility::Inherited;
+        transform.translation = stats.centroid;
+        transform.scale = Vec3::splat(stats.spread.max(MIN_BLOB_RADIUS));
+        if let Some(material) = materials.get_mut(material) {
+            material.color = mean.map_or(Color::WHITE, |mean| colormaps.current().sample((mean - min) / range));
+        }
+    }
+}
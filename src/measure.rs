@@ -0,0 +1,179 @@
+use std::fmt::Write;
+
+use bevy::prelude::*;
+use clap::ValueEnum;
+
+use crate::{force_panel::ForcePanel, kernel_editor::KernelEditor, minimap::MinimapCamera, param_map::ParamMap, picking::Cursor, probe_line::ProbeLine};
+
+const READOUT_MARGIN: f32 = 8.0;
+// Radius of the marks at the ends of a measurement, in logical pixels.
+const END_MARK: f32 = 4.0;
+
+/// Corner of the window the cursor readout is shown in.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReadoutCorner {
+    TopLeft,
+    TopRight,
+    #[default]
+    BottomLeft,
+    BottomRight,
+    /// No readout, though measurements still show while measuring
+    Off,
+}
+
+/// A distance between two points in the world, picked by clicking while
+/// measuring. With only `start` picked, it runs to the cursor.
+#[derive(Resource, Default)]
+pub struct Measurement {
+    pub active: bool,
+    pub start: Option<Vec2>,
+    pub end: Option<Vec2>,
+}
+
+/// Shows the cursor's world coordinates in a corner, and with A toggling
+/// measuring, the distance between the next two points clicked.
+pub struct MeasurePlugin {
+    pub corner: ReadoutCorner,
+    pub precision: usize,
+}
+
+#[derive(Resource)]
+struct Readout {
+    corner: ReadoutCorner,
+    precision: usize,
+}
+
+#[derive(Component)]
+struct ReadoutText;
+
+impl Plugin for MeasurePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Measurement>()
+            .insert_resource(Readout { corner: self.corner, precision: self.precision })
+            .add_systems(Startup, spawn_readout)
+            .add_systems(Update, (toggle_measuring, pick_measure_point, update_readout, draw_measurement).chain());
+    }
+}
+
+fn spawn_readout(mut commands: Commands, readout: Res<Readout>) {
+    let margin = Val::Px(READOUT_MARGIN);
+    let mut style = Style {
+        position_type: PositionType::Absolute,
+        ..default()
+    };
+    match readout.corner {
+        ReadoutCorner::TopLeft => (style.top, style.left) = (margin, margin),
+        ReadoutCorner::TopRight => (style.top, style.right) = (margin, margin),
+        // Measurements still need somewhere to show.
+        ReadoutCorner::BottomLeft | ReadoutCorner::Off => (style.bottom, style.left) = (margin, margin),
+        ReadoutCorner::BottomRight => (style.bottom, style.right) = (margin, margin),
+    }
+    commands.spawn((
+        TextBundle::from_section("", TextStyle {
+            font_size: 16.0,
+            color: Color::WHITE,
+            ..default()
+        })
+        .with_style(style),
+        ReadoutText,
+    ));
+}
+
+fn toggle_measuring(keys: Res<Input<KeyCode>>, mut measurement: ResMut<Measurement>) {
+    if keys.just_pressed(KeyCode::A) {
+        *measurement = Measurement { active: !measurement.active, ..default() };
+    }
+}
+
+// A click starts a measurement, the next ends it, and the one after that
+// starts another. Clicks on the kernel editor are for its control points,
+// clicks on the parameter map for its parameters, clicks with the probe
+// line on for placing it, and clicks on the force panel for its checkboxes.
+fn pick_measure_point(
+    buttons: Res<Input<MouseButton>>,
+    cursor: Cursor,
+    editor: Option<Res<KernelEditor>>,
+    param_map: Option<Res<ParamMap>>,
+    probe_line: Option<Res<ProbeLine>>,
+    force_panel: Option<Res<ForcePanel>>,
+    mut measurement: ResMut<Measurement>,
+) {
+    if !measurement.active || !buttons.just_pressed(MouseButton::Left) {
+        return;
+    }
+    if probe_line.is_some_and(|probe| probe.active) || force_panel.is_some_and(|panel| panel.hovered()) {
+        return;
+    }
+    if editor.is_some_and(|editor| cursor.screen_position().is_some_and(|point| editor.covers(point))) {
+        return;
+    }
+    if param_map.is_some_and(|map| cursor.screen_position().is_some_and(|point| map.covers(point))) {
+        return;
+    }
+    let Some(point) = cursor.world_position() else {
+        return;
+    };
+    match (measurement.start, measurement.end) {
+        (Some(_), None) => measurement.end = Some(point),
+        _ => (measurement.start, measurement.end) = (Some(point), None),
+    }
+}
+
+fn update_readout(
+    readout: Res<Readout>,
+    measurement: Res<Measurement>,
+    cursor: Cursor,
+    mut text_query: Query<&mut Text, With<ReadoutText>>,
+) {
+    let precision = readout.precision;
+    let position = cursor.world_position();
+    let mut text = String::new();
+    if readout.corner != ReadoutCorner::Off {
+        match position {
+            Some(position) => {
+                let _ = write!(text, "x {:.*}  y {:.*}", precision, position.x, precision, position.y);
+            }
+            None => text.push_str("x -  y -"),
+        }
+    }
+    if measurement.active {
+        if !text.is_empty() {
+            text.push('\n');
+        }
+        match measurement.start.zip(measurement.end.or(position)) {
+            Some((start, end)) => {
+                let delta = end - start;
+                let _ = write!(
+                    text,
+                    "distance {:.*} (dx {:.*}, dy {:.*})",
+                    precision, delta.length(), precision, delta.x, precision, delta.y,
+                );
+            }
+            None => text.push_str("measuring: click two points"),
+        }
+    }
+
+    for mut readout_text in text_query.iter_mut() {
+        readout_text.sections[0].value.clone_from(&text);
+    }
+}
+
+fn draw_measurement(
+    mut gizmos: Gizmos,
+    measurement: Res<Measurement>,
+    cursor: Cursor,
+    projection_query: Query<&OrthographicProjection, Without<MinimapCamera>>,
+) {
+    if !measurement.active {
+        return;
+    }
+    let Some((start, end)) = measurement.start.zip(measurement.end.or_else(|| cursor.world_position())) else {
+        return;
+    };
+    let scale = projection_query.iter().map(|projection| projection.scale).fold(0.0, f32::max);
+    let color = if measurement.end.is_some() { Color::YELLOW } else { Color::YELLOW.with_a(0.6) };
+    gizmos.line_2d(start, end, color);
+    for point in [start, end] {
+        gizmos.circle_2d(point, END_MARK * scale, color);
+    }
+}
@@ -0,0 +1,84 @@
+use std::io::Write;
+
+use bevy::{app::AppExit, prelude::*};
+use serde::Serialize;
+
+use crate::{
+    advance_step,
+    stats::{update_creature_stats, CreatureStats, ShapeDescriptors},
+    update_position, Creature, SimStep, StepDiagnostics,
+};
+
+pub struct MetricsPlugin {
+    pub every: u64,
+    pub shape: bool,
+}
+
+#[derive(Resource)]
+struct MetricsEvery(u64);
+
+#[derive(Resource)]
+struct ShapeMetrics(bool);
+
+#[derive(Serialize)]
+struct MetricsLine {
+    step: u64,
+    min_pair_distance: f32,
+    close_encounters: u64,
+    max_force: f32,
+    creatures: Vec<CreatureMetrics>,
+}
+
+#[derive(Serialize)]
+struct CreatureMetrics {
+    creature: u32,
+    centroid: [f32; 3],
+    spread: f32,
+    clusters: usize,
+    energy: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shape: Option<ShapeDescriptors>,
+}
+
+impl Plugin for MetricsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(MetricsEvery(self.every))
+            .insert_resource(ShapeMetrics(self.shape))
+            .add_systems(Update, emit_metrics.after(update_creature_stats).after(update_position).before(advance_step));
+    }
+}
+
+fn emit_metrics(
+    step: Res<SimStep>,
+    every: Res<MetricsEvery>,
+    shape: Res<ShapeMetrics>,
+    diagnostics: Res<StepDiagnostics>,
+    creature_query: Query<(Entity, &CreatureStats), With<Creature>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if !step.0.is_multiple_of(every.0) {
+        return;
+    }
+
+    let line = MetricsLine {
+        step: step.0,
+        min_pair_distance: diagnostics.min_pair_distance,
+        close_encounters: diagnostics.close_encounters,
+        max_force: diagnostics.max_force,
+        creatures: creature_query.iter()
+            .map(|(entity, stats)| CreatureMetrics {
+                creature: entity.index(),
+                centroid: stats.centroid.to_array(),
+                spread: stats.spread,
+                clusters: stats.clusters,
+                energy: stats.energy,
+                shape: shape.0.then_some(stats.shape),
+            })
+            .collect(),
+    };
+
+    let mut stdout = std::io::stdout().lock();
+    if serde_json::to_writer(&mut stdout, &line).is_err() || writeln!(stdout).is_err() {
+        exit.send(AppExit);
+    }
+}
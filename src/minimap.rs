@@ -0,0 +1,248 @@
+use bevy::{
+    core_pipeline::clear_color::ClearColorConfig,
+    prelude::*,
+    render::{camera::Viewport, view::RenderLayers},
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+    window::PrimaryWindow,
+};
+
+use crate::{culling::ViewBounds, stats::CreatureStats, Creature};
+
+// Past the species layers for any matrix of up to 30 species.
+const MINIMAP_LAYER: u8 = RenderLayers::TOTAL_LAYERS as u8 - 1;
+// Side of the minimap as a fraction of the window's shorter side, and its
+// distance from the bottom left corner in logical pixels, above the
+// coordinate readout.
+const MINIMAP_FRACTION: f32 = 0.25;
+const MINIMAP_MARGIN: Vec2 = Vec2::new(8.0, 48.0);
+// World units kept around the creatures and the main view, as a fraction of
+// their extent, and the least extent shown.
+const FIT_MARGIN: f32 = 0.1;
+const MIN_EXTENT: f32 = 40.0;
+// Radius of a centroid's dot in logical pixels.
+const DOT_RADIUS: f32 = 2.5;
+
+/// The camera drawing the minimap, which the cursor, culling and particle
+/// sizing leave out of their views.
+#[derive(Component)]
+pub struct MinimapCamera;
+
+/// Shows a zoomed-out view of every creature's centroid in the bottom left
+/// corner, with the main camera's view outlined, toggled with Y. Clicking or
+/// dragging on it moves the main camera there.
+pub struct MinimapPlugin {
+    pub enabled: bool,
+}
+
+#[derive(Resource)]
+struct MinimapEnabled(bool);
+
+// The dot drawn for a creature's centroid.
+#[derive(Component)]
+struct CentroidDot(Entity);
+
+#[derive(Component)]
+struct MinimapBackground;
+
+#[derive(Component)]
+struct MainViewBox;
+
+#[derive(Resource)]
+struct DotAssets {
+    mesh: Mesh2dHandle,
+    material: Handle<ColorMaterial>,
+}
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(MinimapEnabled(self.enabled))
+            .add_systems(Startup, spawn_minimap)
+            .add_systems(Update, (toggle_minimap, recenter_main_camera, fit_minimap, sync_dots).chain());
+    }
+}
+
+fn spawn_minimap(
+    mut commands: Commands,
+    enabled: Res<MinimapEnabled>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let layer = RenderLayers::layer(MINIMAP_LAYER);
+    let mut camera = Camera2dBundle::default();
+    camera.camera.order = 10;
+    camera.camera.is_active = enabled.0;
+    // Clearing would wipe the whole window, so the background is a sprite.
+    camera.camera_2d.clear_color = ClearColorConfig::None;
+    commands.spawn((camera, UiCameraConfig { show_ui: false }, layer, MinimapCamera));
+
+    for (color, z, marker) in [(Color::rgba(0.1, 0.1, 0.1, 0.85), 0.0, true), (Color::rgba(1.0, 1.0, 1.0, 0.15), 1.0, false)] {
+        let mut sprite = commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(Vec2::ONE),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0.0, 0.0, z),
+                ..default()
+            },
+            layer,
+        ));
+        if marker {
+            sprite.insert(MinimapBackground);
+        } else {
+            sprite.insert(MainViewBox);
+        }
+    }
+
+    commands.insert_resource(DotAssets {
+        mesh: Mesh2dHandle(meshes.add(shape::Circle::new(1.0).into())),
+        material: materials.add(ColorMaterial::from(Color::WHITE)),
+    });
+}
+
+fn toggle_minimap(
+    keys: Res<Input<KeyCode>>,
+    mut enabled: ResMut<MinimapEnabled>,
+    mut camera_query: Query<&mut Camera, With<MinimapCamera>>,
+) {
+    if keys.just_pressed(KeyCode::Y) {
+        enabled.0 = !enabled.0;
+        for mut camera in camera_query.iter_mut() {
+            camera.is_active = enabled.0;
+        }
+    }
+}
+
+/// Whether `point`, in logical window coordinates, is over the minimap drawn
+/// by `camera` while it's shown.
+pub fn minimap_covers(camera: &Camera, point: Vec2) -> bool {
+    camera.is_active && camera.logical_viewport_rect().is_some_and(|viewport| viewport.contains(point))
+}
+
+#[allow(clippy::type_complexity)]
+fn recenter_main_camera(
+    buttons: Res<Input<MouseButton>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    minimap_query: Query<(&Camera, &GlobalTransform), With<MinimapCamera>>,
+    mut main_query: Query<&mut Transform, (With<Camera>, Without<MinimapCamera>)>,
+) {
+    if !buttons.pressed(MouseButton::Left) {
+        return;
+    }
+    let Some(cursor) = window_query.get_single().ok().and_then(|window| window.cursor_position()) else {
+        return;
+    };
+    let Ok((camera, camera_transform)) = minimap_query.get_single() else {
+        return;
+    };
+    let Some(viewport) = camera.logical_viewport_rect().filter(|_| minimap_covers(camera, cursor)) else {
+        return;
+    };
+    let Some(point) = camera.viewport_to_world_2d(camera_transform, cursor - viewport.min) else {
+        return;
+    };
+    for mut transform in main_query.iter_mut() {
+        transform.translation = point.extend(transform.translation.z);
+    }
+}
+
+// Places the minimap in its corner and frames every centroid and the main
+// view in it.
+#[allow(clippy::type_complexity)]
+fn fit_minimap(
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    view_bounds: ViewBounds,
+    stats_query: Query<&CreatureStats, With<Creature>>,
+    mut camera_query: Query<(&mut Camera, &mut OrthographicProjection, &mut Transform), With<MinimapCamera>>,
+    mut background_query: Query<&mut Transform, (With<MinimapBackground>, Without<MinimapCamera>, Without<MainViewBox>)>,
+    mut view_box_query: Query<&mut Transform, (With<MainViewBox>, Without<MinimapCamera>, Without<MinimapBackground>)>,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let Ok((mut camera, mut projection, mut camera_transform)) = camera_query.get_single_mut() else {
+        return;
+    };
+    if !camera.is_active {
+        return;
+    }
+
+    let scale_factor = window.scale_factor() as f32;
+    let side = (MINIMAP_FRACTION * window.width().min(window.height())).max(1.0);
+    let physical_side = (side * scale_factor) as u32;
+    let physical_position = UVec2::new(
+        (MINIMAP_MARGIN.x * scale_factor) as u32,
+        (window.physical_height() as f32 - (side + MINIMAP_MARGIN.y) * scale_factor).max(0.0) as u32,
+    );
+    if camera.viewport.as_ref().is_none_or(|viewport| {
+        viewport.physical_position != physical_position || viewport.physical_size.x != physical_side
+    }) {
+        camera.viewport = Some(Viewport {
+            physical_position,
+            physical_size: UVec2::splat(physical_side.max(1)),
+            ..default()
+        });
+    }
+
+    let main_view = view_bounds.view_rect();
+    let mut bounds = stats_query.iter()
+        .map(|stats| stats.centroid.truncate())
+        .filter(|centroid| centroid.is_finite())
+        .fold(main_view.unwrap_or(Rect { min: Vec2::splat(f32::INFINITY), max: Vec2::splat(f32::NEG_INFINITY) }), |rect, centroid| rect.union_point(centroid));
+    if !bounds.min.is_finite() {
+        bounds = Rect::from_center_size(Vec2::ZERO, Vec2::ZERO);
+    }
+    let extent = (bounds.size().max_element() * (1.0 + 2.0 * FIT_MARGIN)).max(MIN_EXTENT);
+    projection.scale = extent / side;
+    camera_transform.translation = bounds.center().extend(camera_transform.translation.z);
+
+    for mut transform in background_query.iter_mut() {
+        transform.translation = bounds.center().extend(transform.translation.z);
+        transform.scale = Vec3::new(extent, extent, 1.0);
+    }
+    for mut transform in view_box_query.iter_mut() {
+        let view = main_view.unwrap_or_default();
+        transform.translation = view.center().extend(transform.translation.z);
+        transform.scale = view.size().extend(1.0);
+    }
+}
+
+// Gives each creature a dot at its centroid, sized to stay the same on
+// screen, and removes the dots of creatures that are gone.
+fn sync_dots(
+    mut commands: Commands,
+    assets: Res<DotAssets>,
+    stats_query: Query<(Entity, &CreatureStats), With<Creature>>,
+    projection_query: Query<&OrthographicProjection, With<MinimapCamera>>,
+    mut dot_query: Query<(Entity, &CentroidDot, &mut Transform, &mut Visibility)>,
+) {
+    let radius = DOT_RADIUS * projection_query.get_single().map_or(1.0, |projection| projection.scale);
+    let mut seen = Vec::new();
+    for (entity, dot, mut transform, mut visibility) in dot_query.iter_mut() {
+        let Ok((_, stats)) = stats_query.get(dot.0) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+        seen.push(dot.0);
+        let finite = stats.centroid.is_finite();
+        *visibility = if finite { Visibility::Inherited } else { Visibility::Hidden };
+        if finite {
+            transform.translation = stats.centroid.truncate().extend(2.0);
+            transform.scale = Vec3::new(radius, radius, 1.0);
+        }
+    }
+
+    for (creature, stats) in stats_query.iter().filter(|(creature, _)| !seen.contains(creature)) {
+        commands.spawn((
+            MaterialMesh2dBundle {
+                mesh: assets.mesh.clone(),
+                material: assets.material.clone(),
+                transform: Transform::from_translation(stats.centroid.truncate().extend(2.0)).with_scale(Vec3::new(radius, radius, 1.0)),
+                ..default()
+            },
+            RenderLayers::layer(MINIMAP_LAYER),
+            CentroidDot(creature),
+        ));
+    }
+}
@@ -0,0 +1,66 @@
+use std::{
+    io::Write,
+    time::{Duration, Instant},
+};
+
+use bevy::prelude::*;
+
+use crate::{advance_step, stats::CreatureStats, update_position, Creature, SimStep};
+
+const REFRESH: Duration = Duration::from_millis(250);
+
+/// Redraws a line per creature on stderr a few times a second, in place, with
+/// the step, the step rate and the creature's centroid, clusters and energy.
+pub struct MonitorPlugin;
+
+#[derive(Resource)]
+struct Monitor {
+    last: Option<(Instant, u64)>,
+    steps_per_second: f64,
+    lines: usize,
+}
+
+impl Plugin for MonitorPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Monitor { last: None, steps_per_second: 0.0, lines: 0 })
+            .add_systems(Update, draw_monitor.after(update_position).before(advance_step));
+    }
+}
+
+fn draw_monitor(
+    step: Res<SimStep>,
+    mut monitor: ResMut<Monitor>,
+    creature_query: Query<(Entity, &CreatureStats), With<Creature>>,
+) {
+    let now = Instant::now();
+    match monitor.last {
+        Some((last, _)) if now - last < REFRESH => return,
+        Some((last, last_step)) => {
+            monitor.steps_per_second = step.0.saturating_sub(last_step) as f64 / (now - last).as_secs_f64();
+        }
+        None => {}
+    }
+    monitor.last = Some((now, step.0));
+
+    let mut creatures: Vec<_> = creature_query.iter().collect();
+    creatures.sort_by_key(|(entity, _)| *entity);
+
+    // Back to the start of the previous block, then over it line by line.
+    let mut text = String::new();
+    if monitor.lines > 0 {
+        text += &format!("\x1b[{}F", monitor.lines);
+    }
+    text += &format!("step {} | {:.1} steps/s | {} creatures\x1b[K\n", step.0, monitor.steps_per_second, creatures.len());
+    for (entity, stats) in creatures.iter() {
+        text += &format!(
+            "  {:>5}  centroid ({:>8.2}, {:>8.2})  clusters {:>3}  energy {:>8.4}\x1b[K\n",
+            entity.index(), stats.centroid.x, stats.centroid.y, stats.clusters, stats.energy,
+        );
+    }
+    // Clear what's left of a longer previous block.
+    text += "\x1b[J";
+    monitor.lines = creatures.len() + 1;
+
+    let mut stderr = std::io::stderr().lock();
+    let _ = stderr.write_all(text.as_bytes()).and_then(|_| stderr.flush());
+}
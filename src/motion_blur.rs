@@ -0,0 +1,79 @@
+use bevy::prelude::*;
+
+use crate::{
+    culling::{is_visible, ViewBounds},
+    update_position, Creature, DisplayRadius, Integrator, Particle, TimeScale, Velocity,
+};
+
+// Lines fanned across each streak, and how opaque it is at the particle.
+const STREAK_LINES: usize = 5;
+const STREAK_ALPHA: f32 = 0.6;
+// Streaks shorter than this fraction of the particle's radius are skipped, so
+// slow particles stay crisp.
+const MIN_STREAK: f32 = 0.25;
+
+/// Z toggles a motion-blur streak behind each visible particle, tapering from
+/// its edge and color to a point `intensity` steps of travel back along its
+/// velocity.
+pub struct MotionBlurPlugin {
+    pub enabled: bool,
+    pub intensity: f32,
+}
+
+#[derive(Resource)]
+struct MotionBlur {
+    enabled: bool,
+    intensity: f32,
+}
+
+impl Plugin for MotionBlurPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(MotionBlur { enabled: self.enabled, intensity: self.intensity })
+            .add_systems(Update, (toggle_motion_blur, draw_motion_blur.after(update_position)));
+    }
+}
+
+fn toggle_motion_blur(keys: Res<Input<KeyCode>>, mut blur: ResMut<MotionBlur>) {
+    if keys.just_pressed(KeyCode::Z) {
+        blur.enabled = !blur.enabled;
+        info!("motion blur {}", if blur.enabled { "enabled" } else { "disabled" });
+    }
+}
+
+fn draw_motion_blur(
+    blur: Res<MotionBlur>,
+    integrator: Res<Integrator>,
+    materials: Res<Assets<ColorMaterial>>,
+    view_bounds: ViewBounds,
+    mut gizmos: Gizmos,
+    creature_query: Query<(&Transform, &TimeScale, &Children), With<Creature>>,
+    particle_query: Query<(&Transform, &Velocity, &DisplayRadius, &Handle<ColorMaterial>), With<Particle>>,
+) {
+    if !blur.enabled {
+        return;
+    }
+    let visible_rects = view_bounds.rects();
+
+    for (creature_transform, time_scale, children) in creature_query.iter() {
+        let travel = blur.intensity * integrator.dt * time_scale.0;
+        for (transform, velocity, radius, material) in particle_query.iter_many(children) {
+            let Some(radius) = radius.0 else {
+                continue;
+            };
+            let center = (creature_transform.translation + transform.translation).truncate();
+            let streak = -velocity.0.truncate() * travel;
+            if streak.length() < MIN_STREAK * radius || !is_visible(&visible_rects, center) {
+                continue;
+            }
+
+            let color = materials.get(material).map_or(Color::WHITE, |material| material.color);
+            let across = streak.perp().normalize() * radius;
+            for line in 0..STREAK_LINES {
+                let offset = line as f32 / (STREAK_LINES - 1) as f32 * 2.0 - 1.0;
+                // Outer lines fade faster, so the streak thins toward its edges.
+                let alpha = STREAK_ALPHA * (1.0 - 0.5 * offset.abs());
+                gizmos.line_gradient_2d(center + offset * across, center + streak, color.with_a(alpha), color.with_a(0.0));
+            }
+        }
+    }
+}
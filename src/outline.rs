@@ -0,0 +1,169 @@
+use bevy::{prelude::*, utils::HashMap};
+use clap::ValueEnum;
+
+use crate::{Creature, Particle};
+
+/// Outline drawn around each creature, cycled with B.
+#[derive(ValueEnum, Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutlineMode {
+    #[default]
+    Off,
+    /// Convex hull of the particles
+    Hull,
+    /// Alpha shape, which follows concave edges down to the --outline-alpha radius
+    Alpha,
+}
+
+impl OutlineMode {
+    fn next(self) -> Self {
+        match self {
+            OutlineMode::Off => OutlineMode::Hull,
+            OutlineMode::Hull => OutlineMode::Alpha,
+            OutlineMode::Alpha => OutlineMode::Off,
+        }
+    }
+}
+
+pub struct OutlinePlugin {
+    pub mode: OutlineMode,
+    pub alpha: f32,
+    /// Frames between recomputing the outlines
+    pub every: u32,
+}
+
+#[derive(Resource)]
+struct OutlineSettings {
+    alpha: f32,
+    every: u32,
+}
+
+// Each creature's outline edges, relative to the creature so they follow it
+// between updates.
+#[derive(Resource, Default)]
+struct Outlines {
+    edges: HashMap<Entity, Vec<(Vec2, Vec2)>>,
+    frames_since_update: u32,
+}
+
+impl Plugin for OutlinePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.mode)
+            .insert_resource(OutlineSettings { alpha: self.alpha, every: self.every.max(1) })
+            .init_resource::<Outlines>()
+            .add_systems(Update, (cycle_outline, update_outlines, draw_outlines).chain());
+    }
+}
+
+fn cycle_outline(keys: Res<Input<KeyCode>>, mut mode: ResMut<OutlineMode>) {
+    if keys.just_pressed(KeyCode::B) {
+        *mode = mode.next();
+        info!("outline: {:?}", *mode);
+    }
+}
+
+fn update_outlines(
+    mode: Res<OutlineMode>,
+    settings: Res<OutlineSettings>,
+    mut outlines: ResMut<Outlines>,
+    creature_query: Query<(Entity, &Children), With<Creature>>,
+    particle_query: Query<&Transform, With<Particle>>,
+) {
+    outlines.frames_since_update += 1;
+    if *mode == OutlineMode::Off {
+        outlines.edges.clear();
+        return;
+    }
+    if !mode.is_changed() && outlines.frames_since_update < settings.every {
+        return;
+    }
+    outlines.frames_since_update = 0;
+
+    outlines.edges.clear();
+    for (entity, children) in creature_query.iter() {
+        let points: Vec<_> = particle_query.iter_many(children).map(|transform| transform.translation.truncate()).collect();
+        let edges = match *mode {
+            OutlineMode::Off => continue,
+            OutlineMode::Hull => {
+                let hull = convex_hull(&points);
+                hull.iter().zip(hull.iter().cycle().skip(1)).map(|(a, b)| (*a, *b)).collect()
+            }
+            OutlineMode::Alpha => alpha_shape(&points, settings.alpha),
+        };
+        outlines.edges.insert(entity, edges);
+    }
+}
+
+fn draw_outlines(
+    outlines: Res<Outlines>,
+    mut gizmos: Gizmos,
+    creature_query: Query<&Transform, With<Creature>>,
+) {
+    for (entity, edges) in outlines.edges.iter() {
+        let Ok(transform) = creature_query.get(*entity) else {
+            continue;
+        };
+        let offset = transform.translation.truncate();
+        for (a, b) in edges {
+            gizmos.line_2d(offset + *a, offset + *b, Color::ALICE_BLUE);
+        }
+    }
+}
+
+/// Convex hull of `points`, counterclockwise, by Andrew's monotone chain.
+pub fn convex_hull(points: &[Vec2]) -> Vec<Vec2> {
+    let mut sorted = points.to_vec();
+    sorted.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    sorted.dedup();
+    if sorted.len() < 3 {
+        return sorted;
+    }
+
+    // The lower chain left to right, then the upper chain right to left, each
+    // dropping points that don't turn left. Each chain's last point starts the
+    // other one.
+    let mut hull: Vec<Vec2> = Vec::with_capacity(sorted.len() + 1);
+    for chain in [sorted.clone(), sorted.into_iter().rev().collect()] {
+        let start = hull.len();
+        for point in chain {
+            while hull.len() >= start + 2 {
+                let [a, b] = [hull[hull.len() - 2], hull[hull.len() - 1]];
+                if (b - a).perp_dot(point - b) > 0.0 {
+                    break;
+                }
+                hull.pop();
+            }
+            hull.push(point);
+        }
+        hull.pop();
+    }
+    hull
+}
+
+/// Boundary edges of the alpha shape of `points`: the pairs closer than
+/// `2 alpha` with a circle of radius `alpha` through both that no other point
+/// is inside, as if rolling a ball of that radius around the set.
+pub fn alpha_shape(points: &[Vec2], alpha: f32) -> Vec<(Vec2, Vec2)> {
+    let reach = 2.0 * alpha;
+    let neighbors: Vec<Vec<usize>> = points.iter()
+        .map(|a| (0..points.len()).filter(|j| points[*j] != *a && a.distance(points[*j]) < reach).collect())
+        .collect();
+
+    let mut edges = Vec::new();
+    for (i, a) in points.iter().enumerate() {
+        for &j in neighbors[i].iter().filter(|j| **j > i) {
+            let b = points[j];
+            let middle = 0.5 * (*a + b);
+            let half = 0.5 * a.distance(b);
+            let offset = (b - *a).perp().normalize() * (alpha * alpha - half * half).max(0.0).sqrt();
+
+            // Only points near `a` can fall inside a circle through it.
+            let empty = |center: Vec2| neighbors[i].iter()
+                .filter(|k| **k != j)
+                .all(|k| points[*k].distance_squared(center) >= alpha * alpha * (1.0 - 1e-4));
+            if empty(middle + offset) || empty(middle - offset) {
+                edges.push((*a, b));
+            }
+        }
+    }
+    edges
+}
@@ -0,0 +1,73 @@
+use std::{
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::prelude::*;
+
+/// Where the window's exports go and what they're called. The template may
+/// use `{step}`, zero-padded to six digits, `{seed}`, `{timestamp}` in Unix
+/// seconds and `{creature}`, which is `all` for exports of the whole frame.
+#[derive(Resource, Clone, Debug)]
+pub struct OutputConfig {
+    pub dir: PathBuf,
+    pub name_template: String,
+}
+
+/// Values the placeholders of a name template are filled with.
+pub struct OutputName {
+    pub step: u64,
+    pub seed: u64,
+    pub creature: Option<Entity>,
+}
+
+impl OutputName {
+    /// Fills in the placeholders of `template`.
+    pub fn resolve(&self, template: &str) -> String {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs());
+        template
+            .replace("{step}", &format!("{:06}", self.step))
+            .replace("{seed}", &self.seed.to_string())
+            .replace("{timestamp}", &timestamp.to_string())
+            .replace("{creature}", &self.creature.map_or_else(|| "all".to_string(), |creature| creature.index().to_string()))
+    }
+}
+
+impl OutputConfig {
+    /// Resolves the template to a path under the output directory, with
+    /// `extension` if given, and creates the directories leading to it. A
+    /// name that's already taken gets `-1`, `-2`, ... appended.
+    pub fn path(&self, name: &OutputName, extension: Option<&str>) -> io::Result<PathBuf> {
+        let stem = name.resolve(&self.name_template);
+
+        let with_extension = |stem: &str| match extension {
+            Some(extension) => self.dir.join(format!("{}.{}", stem, extension)),
+            None => self.dir.join(stem),
+        };
+        let mut path = with_extension(&stem);
+        let mut suffix = 0;
+        while path.exists() {
+            suffix += 1;
+            path = with_extension(&format!("{}-{}", stem, suffix));
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        Ok(path)
+    }
+}
+
+/// Creates the file at a path given on the command line, which may use the
+/// same placeholders as a name template, along with its directories. Unlike
+/// [`OutputConfig::path`], an existing file is overwritten.
+pub fn create_file(path: &Path, name: &OutputName) -> io::Result<(PathBuf, File)> {
+    let path = PathBuf::from(name.resolve(&path.to_string_lossy()));
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let file = File::create(&path)?;
+    Ok((path, file))
+}
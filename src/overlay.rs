@@ -0,0 +1,85 @@
+use std::fmt::Write;
+
+use bevy::{diagnostic::DiagnosticsStore, prelude::*};
+
+use crate::{
+    annealing::{CurrentPhase, PhaseSchedule},
+    encounters::CloseEncounters,
+    timings::SYSTEM_TIMINGS,
+    SimStep, StepDiagnostics,
+};
+
+#[derive(Component)]
+struct OverlayText;
+
+pub struct OverlayPlugin;
+
+impl Plugin for OverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_overlay)
+            .add_systems(Update, (toggle_overlay, update_overlay));
+    }
+}
+
+fn spawn_overlay(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section("", TextStyle {
+            font_size: 16.0,
+            color: Color::WHITE,
+            ..default()
+        })
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.0),
+            left: Val::Px(8.0),
+            ..default()
+        }),
+        OverlayText,
+    ));
+}
+
+fn toggle_overlay(
+    keys: Res<Input<KeyCode>>,
+    mut overlay_query: Query<&mut Visibility, With<OverlayText>>,
+) {
+    if keys.just_pressed(KeyCode::H) {
+        for mut visibility in overlay_query.iter_mut() {
+            *visibility = match *visibility {
+                Visibility::Hidden => Visibility::Inherited,
+                _ => Visibility::Hidden,
+            };
+        }
+    }
+}
+
+fn update_overlay(
+    step: Res<SimStep>,
+    diagnostics: Res<StepDiagnostics>,
+    encounters: Option<Res<CloseEncounters>>,
+    store: Res<DiagnosticsStore>,
+    schedule: Option<Res<PhaseSchedule>>,
+    phase: Option<Res<CurrentPhase>>,
+    mut overlay_query: Query<&mut Text, With<OverlayText>>,
+) {
+    let mut text = String::new();
+    let _ = writeln!(text, "step {}", step.0);
+    if let Some(description) = schedule.zip(phase).and_then(|(schedule, phase)| phase.describe(&schedule)) {
+        let _ = writeln!(text, "{}", description);
+    }
+    let _ = writeln!(text, "min pair distance {:.4}", diagnostics.min_pair_distance);
+    let _ = write!(text, "close encounters {}", diagnostics.close_encounters);
+    if let Some(encounters) = encounters {
+        let _ = write!(text, ", {} since reset, {:.2} per step", encounters.total(), encounters.per_step());
+    }
+    let _ = writeln!(text);
+    let _ = write!(text, "max |E_grad| {:.4}", diagnostics.max_force);
+    for (id, name) in SYSTEM_TIMINGS {
+        if let Some(ms) = store.get(id).and_then(|diagnostic| diagnostic.smoothed()) {
+            let _ = write!(text, "\n{} {:.2} ms", name, ms);
+        }
+    }
+
+    for mut overlay in overlay_query.iter_mut() {
+        overlay.sections[0].value.clone_from(&text);
+    }
+}
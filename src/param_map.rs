@@ -0,0 +1,372 @@
+use std::{fmt::Write, fs, path::Path};
+
+use bevy::{prelude::*, window::PrimaryWindow};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::{selection::Selection, Creature, Parameters};
+
+// Side of the map and its distance from the bottom-right corner, in logical
+// pixels.
+const MAP_SIZE: f32 = 220.0;
+const MAP_MARGIN: f32 = 16.0;
+const MARKER_SIZE: f32 = 6.0;
+// Colors of a class map's classes, in the order they first appear in it.
+const CLASS_COLORS: [Color; 8] = [
+    Color::rgba(0.9, 0.3, 0.3, 0.6),
+    Color::rgba(0.3, 0.8, 0.3, 0.6),
+    Color::rgba(0.3, 0.5, 0.9, 0.6),
+    Color::rgba(0.9, 0.8, 0.2, 0.6),
+    Color::rgba(0.7, 0.3, 0.9, 0.6),
+    Color::rgba(0.2, 0.8, 0.8, 0.6),
+    Color::rgba(0.9, 0.5, 0.1, 0.6),
+    Color::rgba(0.6, 0.6, 0.6, 0.6),
+];
+
+/// A scalar parameter the map can have along one of its axes.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParameterAxis {
+    MuK,
+    SigmaK,
+    #[value(name = "w-k")]
+    WK,
+    MuG,
+    SigmaG,
+    CRep,
+}
+
+impl ParameterAxis {
+    /// The parameter's field name, which a class map's columns are named by.
+    pub fn name(self) -> &'static str {
+        match self {
+            ParameterAxis::MuK => "mu_k",
+            ParameterAxis::SigmaK => "sigma_k",
+            ParameterAxis::WK => "w_k",
+            ParameterAxis::MuG => "mu_g",
+            ParameterAxis::SigmaG => "sigma_g",
+            ParameterAxis::CRep => "c_rep",
+        }
+    }
+
+    /// Range the axis spans unless one is given, around the defaults.
+    pub fn default_range(self) -> (f32, f32) {
+        match self {
+            ParameterAxis::MuK => (0.0, 8.0),
+            ParameterAxis::SigmaK => (0.2, 3.0),
+            ParameterAxis::WK => (0.0, 0.06),
+            ParameterAxis::MuG => (0.0, 1.2),
+            ParameterAxis::SigmaG => (0.02, 0.4),
+            ParameterAxis::CRep => (0.0, 3.0),
+        }
+    }
+
+    pub fn get(self, parameters: &Parameters) -> f32 {
+        match self {
+            ParameterAxis::MuK => parameters.mu_k,
+            ParameterAxis::SigmaK => parameters.sigma_k,
+            ParameterAxis::WK => parameters.w_k,
+            ParameterAxis::MuG => parameters.mu_g,
+            ParameterAxis::SigmaG => parameters.sigma_g,
+            ParameterAxis::CRep => parameters.c_rep,
+        }
+    }
+
+    /// The nearest value to `value` that [`Parameters::validate`] accepts
+    /// for this parameter.
+    pub fn clamp(self, value: f32) -> f32 {
+        match self {
+            ParameterAxis::MuK | ParameterAxis::WK | ParameterAxis::CRep => value.max(0.0),
+            ParameterAxis::SigmaK | ParameterAxis::SigmaG => value.max(f32::MIN_POSITIVE),
+            ParameterAxis::MuG => value,
+        }
+    }
+
+    pub fn set(self, parameters: &mut Parameters, value: f32) {
+        match self {
+            ParameterAxis::MuK => parameters.mu_k = value,
+            ParameterAxis::SigmaK => parameters.sigma_k = value,
+            ParameterAxis::WK => parameters.w_k = value,
+            ParameterAxis::MuG => parameters.mu_g = value,
+            ParameterAxis::SigmaG => parameters.sigma_g = value,
+            ParameterAxis::CRep => parameters.c_rep = value,
+        }
+    }
+}
+
+/// How a sweep classified the points of the map, read from a CSV file with a
+/// column named after each axis's parameter and a `class` column, one row a
+/// point. Points are drawn as cells as wide as the sweep's step along each
+/// axis.
+#[derive(Clone, Debug)]
+pub struct ClassMap {
+    points: Vec<(Vec2, usize)>,
+    classes: Vec<String>,
+    cell_size: Vec2,
+}
+
+impl ClassMap {
+    pub fn load(path: &Path, axes: [ParameterAxis; 2]) -> Result<Self, String> {
+        let csv = fs::read_to_string(path).map_err(|err| err.to_string())?;
+        let mut lines = csv.lines().filter(|line| !line.trim().is_empty());
+        let header: Vec<&str> = lines.next().ok_or("the class map is empty")?.split(',').map(str::trim).collect();
+        let column = |name: &str| header.iter().position(|column| *column == name).ok_or(format!("the class map has no {} column", name));
+        let (x, y, class) = (column(axes[0].name())?, column(axes[1].name())?, column("class")?);
+
+        let mut map = ClassMap { points: Vec::new(), classes: Vec::new(), cell_size: Vec2::ZERO };
+        for (index, line) in lines.enumerate() {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let field = |column: usize| fields.get(column).copied().ok_or(format!("row {} is missing a column", index + 1));
+            let number = |column: usize| field(column)?.parse::<f32>().map_err(|err| format!("row {}: {}", index + 1, err));
+            let point = Vec2::new(number(x)?, number(y)?);
+            let name = field(class)?;
+            let class = map.classes.iter().position(|known| known == name).unwrap_or_else(|| {
+                map.classes.push(name.to_string());
+                map.classes.len() - 1
+            });
+            map.points.push((point, class));
+        }
+
+        // The smallest gap between distinct coordinates along each axis.
+        let step = |coordinates: Vec<f32>| {
+            let mut coordinates = coordinates;
+            coordinates.sort_by(f32::total_cmp);
+            coordinates.windows(2).map(|pair| pair[1] - pair[0]).filter(|gap| *gap > f32::EPSILON).reduce(f32::min)
+        };
+        let range = |axis: ParameterAxis| axis.default_range().1 - axis.default_range().0;
+        map.cell_size = Vec2::new(
+            step(map.points.iter().map(|(point, _)| point.x).collect()).unwrap_or(range(axes[0]) / 20.0),
+            step(map.points.iter().map(|(point, _)| point.y).collect()).unwrap_or(range(axes[1]) / 20.0),
+        );
+        Ok(map)
+    }
+}
+
+/// F1 opens a map in the bottom-right corner with two parameters along its
+/// axes and a marker at the edited creature's values. Clicking or dragging on
+/// it sets those parameters of the selected creatures, or of every creature
+/// when none are selected, with a sweep's classes colored in behind if a
+/// class map is given.
+pub struct ParamMapPlugin {
+    pub open: bool,
+    pub axes: [ParameterAxis; 2],
+    pub ranges: [(f32, f32); 2],
+    pub classes: Option<ClassMap>,
+}
+
+#[derive(Resource)]
+pub struct ParamMap {
+    open: bool,
+    axes: [ParameterAxis; 2],
+    ranges: [(f32, f32); 2],
+    last_applied: Option<Vec2>,
+    panel: Option<Rect>,
+}
+
+impl ParamMap {
+    /// Whether `point`, in logical window coordinates, is over the open map.
+    pub fn covers(&self, point: Vec2) -> bool {
+        self.open && self.panel.is_some_and(|panel| panel.contains(point))
+    }
+
+    // Where on the map, from its bottom-left corner in logical pixels, a pair
+    // of values is.
+    fn map_point(&self, values: Vec2) -> Vec2 {
+        let [(x_min, x_max), (y_min, y_max)] = self.ranges;
+        Vec2::new((values.x - x_min) / (x_max - x_min), (values.y - y_min) / (y_max - y_min)) * MAP_SIZE
+    }
+
+    fn values(&self, panel: Rect, point: Vec2) -> Vec2 {
+        let [(x_min, x_max), (y_min, y_max)] = self.ranges;
+        let fraction = Vec2::new(point.x - panel.min.x, panel.max.y - point.y) / MAP_SIZE;
+        Vec2::new(x_min + fraction.x * (x_max - x_min), y_min + fraction.y * (y_max - y_min))
+    }
+}
+
+#[derive(Resource)]
+struct MapClasses(Option<ClassMap>);
+
+#[derive(Component)]
+struct MapPanel;
+
+#[derive(Component)]
+struct MapMarker;
+
+#[derive(Component)]
+struct MapLabel;
+
+impl Plugin for ParamMapPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ParamMap { open: self.open, axes: self.axes, ranges: self.ranges, last_applied: None, panel: None })
+            .insert_resource(MapClasses(self.classes.clone()))
+            .add_systems(Startup, spawn_map)
+            .add_systems(Update, (toggle_map, click_map, update_marker).chain());
+    }
+}
+
+fn spawn_map(mut commands: Commands, map: Res<ParamMap>, classes: Res<MapClasses>) {
+    let absolute = |left: f32, bottom: f32, size: Vec2| Style {
+        position_type: PositionType::Absolute,
+        left: Val::Px(left),
+        bottom: Val::Px(bottom),
+        width: Val::Px(size.x),
+        height: Val::Px(size.y),
+        ..default()
+    };
+    let panel = NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            right: Val::Px(MAP_MARGIN),
+            bottom: Val::Px(MAP_MARGIN),
+            width: Val::Px(MAP_SIZE),
+            height: Val::Px(MAP_SIZE),
+            overflow: Overflow::clip(),
+            ..default()
+        },
+        background_color: Color::rgba(0.1, 0.1, 0.1, 0.85).into(),
+        visibility: if map.open { Visibility::Inherited } else { Visibility::Hidden },
+        ..default()
+    };
+
+    commands.spawn((panel, MapPanel)).with_children(|panel| {
+        if let Some(classes) = &classes.0 {
+            let [(x_min, x_max), (y_min, y_max)] = map.ranges;
+            let cell = classes.cell_size / Vec2::new(x_max - x_min, y_max - y_min) * MAP_SIZE;
+            for (point, class) in classes.points.iter() {
+                let corner = map.map_point(*point) - 0.5 * cell;
+                panel.spawn(NodeBundle {
+                    style: absolute(corner.x, corner.y, cell),
+                    background_color: CLASS_COLORS[class % CLASS_COLORS.len()].into(),
+                    ..default()
+                });
+            }
+        }
+        panel.spawn((
+            NodeBundle {
+                style: absolute(0.0, 0.0, Vec2::splat(MARKER_SIZE)),
+                background_color: Color::WHITE.into(),
+                ..default()
+            },
+            MapMarker,
+        ));
+        // The values, then a legend of the classes in their colors.
+        let style = |color: Color| TextStyle {
+            font_size: 14.0,
+            color,
+            ..default()
+        };
+        let mut sections = vec![TextSection::new("", style(Color::WHITE))];
+        if let Some(classes) = &classes.0 {
+            for (index, class) in classes.classes.iter().enumerate() {
+                let separator = if index == 0 { "\n" } else { "  " };
+                sections.push(TextSection::new(format!("{}{}", separator, class), style(CLASS_COLORS[index % CLASS_COLORS.len()].with_a(1.0))));
+            }
+        }
+        panel.spawn((
+            TextBundle::from_sections(sections)
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(4.0),
+                top: Val::Px(4.0),
+                ..default()
+            }),
+            MapLabel,
+        ));
+    });
+}
+
+fn toggle_map(
+    keys: Res<Input<KeyCode>>,
+    mut map: ResMut<ParamMap>,
+    mut panel_query: Query<&mut Visibility, With<MapPanel>>,
+) {
+    if !keys.just_pressed(KeyCode::F1) {
+        return;
+    }
+    map.open = !map.open;
+    map.last_applied = None;
+    for mut visibility in panel_query.iter_mut() {
+        *visibility = if map.open { Visibility::Inherited } else { Visibility::Hidden };
+    }
+}
+
+fn click_map(
+    buttons: Res<Input<MouseButton>>,
+    selection: Res<Selection>,
+    mut map: ResMut<ParamMap>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut creature_query: Query<(Entity, &mut Parameters), With<Creature>>,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let min = Vec2::new(window.width() - MAP_MARGIN - MAP_SIZE, window.height() - MAP_MARGIN - MAP_SIZE);
+    let panel = Rect::from_corners(min, min + MAP_SIZE);
+    map.panel = Some(panel);
+    if !map.open || !buttons.pressed(MouseButton::Left) {
+        map.last_applied = None;
+        return;
+    }
+    let Some(cursor) = window.cursor_position().filter(|cursor| panel.contains(*cursor)) else {
+        return;
+    };
+    let values = map.values(panel, cursor);
+    if map.last_applied == Some(values) {
+        return;
+    }
+    map.last_applied = Some(values);
+
+    let [x_axis, y_axis] = map.axes;
+    for (entity, mut parameters) in creature_query.iter_mut() {
+        if !selection.0.is_empty() && !selection.0.contains(&entity) {
+            continue;
+        }
+        let mut edited = parameters.clone();
+        x_axis.set(&mut edited, values.x);
+        y_axis.set(&mut edited, values.y);
+        match edited.validate() {
+            Ok(()) => *parameters = edited,
+            Err(err) => warn!("kept the previous parameters of {:?}: {}", entity, err),
+        }
+    }
+}
+
+// The marker follows the first selected creature, or the first creature
+// when none are selected.
+fn update_marker(
+    map: Res<ParamMap>,
+    selection: Res<Selection>,
+    creature_query: Query<(Entity, &Parameters), With<Creature>>,
+    mut marker_query: Query<(&mut Style, &mut Visibility), With<MapMarker>>,
+    mut label_query: Query<&mut Text, With<MapLabel>>,
+) {
+    if !map.open {
+        return;
+    }
+    let shown = selection.0.iter().find_map(|entity| creature_query.get(*entity).ok())
+        .or_else(|| creature_query.iter().min_by_key(|(entity, _)| *entity));
+    let [x_axis, y_axis] = map.axes;
+    let values = shown.map(|(_, parameters)| Vec2::new(x_axis.get(parameters), y_axis.get(parameters)));
+
+    let point = values.map(|values| map.map_point(values)).filter(|point| point.cmpge(Vec2::ZERO).all() && point.cmple(Vec2::splat(MAP_SIZE)).all());
+    for (mut style, mut visibility) in marker_query.iter_mut() {
+        *visibility = if point.is_some() { Visibility::Inherited } else { Visibility::Hidden };
+        if let Some(point) = point {
+            style.left = Val::Px(point.x - 0.5 * MARKER_SIZE);
+            style.bottom = Val::Px(point.y - 0.5 * MARKER_SIZE);
+        }
+    }
+
+    for mut text in label_query.iter_mut() {
+        let mut label = String::new();
+        match values {
+            Some(values) => {
+                let _ = write!(label, "{} {:.3}  {} {:.3}", x_axis.name(), values.x, y_axis.name(), values.y);
+            }
+            None => {
+                let _ = write!(label, "{} -  {} -", x_axis.name(), y_axis.name());
+            }
+        }
+        text.sections[0].value = label;
+    }
+}
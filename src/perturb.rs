@@ -0,0 +1,102 @@
+use bevy::prelude::*;
+
+use crate::{
+    picking::{nearest_particle, Cursor},
+    selection::PICK_RADIUS,
+    sum_pair_fields, Creature, Particle, SimStep,
+};
+
+/// Offset a single particle is moved by, exactly, to study how a localized
+/// disturbance spreads. Alt-click applies it to the particle under the
+/// cursor, and `scheduled` applies it at a given step to the particle at an
+/// index in spawn order, for runs that should repeat exactly.
+pub struct PerturbPlugin {
+    pub offset: Vec3,
+    pub scheduled: Option<ScheduledPerturbation>,
+    /// Whether there's a window to click in
+    pub interactive: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ScheduledPerturbation {
+    pub step: u64,
+    /// Index over every particle, creatures in spawn order and each
+    /// creature's particles in child order
+    pub particle: usize,
+}
+
+#[derive(Resource)]
+struct Perturbation {
+    offset: Vec3,
+    scheduled: Option<ScheduledPerturbation>,
+}
+
+impl Plugin for PerturbPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Perturbation { offset: self.offset, scheduled: self.scheduled })
+            // Before the fields are summed, so this step already feels it.
+            .add_systems(Update, perturb_scheduled.before(sum_pair_fields));
+        if self.interactive {
+            app.add_systems(Update, perturb_on_click.before(sum_pair_fields));
+        }
+    }
+}
+
+pub fn perturb_modifier_pressed(keys: &Input<KeyCode>) -> bool {
+    keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight])
+}
+
+#[allow(clippy::type_complexity)]
+fn perturb_on_click(
+    buttons: Res<Input<MouseButton>>,
+    keys: Res<Input<KeyCode>>,
+    step: Res<SimStep>,
+    perturbation: Res<Perturbation>,
+    cursor: Cursor,
+    creature_query: Query<(Entity, &Transform, &Children), With<Creature>>,
+    mut particle_queries: ParamSet<(
+        Query<&Transform, With<Particle>>,
+        Query<&mut Transform, (With<Particle>, Without<Creature>)>,
+    )>,
+) {
+    if !buttons.just_pressed(MouseButton::Left) || !perturb_modifier_pressed(&keys) {
+        return;
+    }
+    let Some((_, particle)) = cursor.world_position()
+        .and_then(|point| nearest_particle(point, PICK_RADIUS, &creature_query, &particle_queries.p0()))
+    else {
+        return;
+    };
+
+    if let Ok(mut transform) = particle_queries.p1().get_mut(particle) {
+        transform.translation += perturbation.offset;
+        info!("step {}: moved {:?} by {}", step.0, particle, perturbation.offset);
+    }
+}
+
+fn perturb_scheduled(
+    step: Res<SimStep>,
+    perturbation: Res<Perturbation>,
+    creature_query: Query<(Entity, &Children), With<Creature>>,
+    mut particle_query: Query<&mut Transform, (With<Particle>, Without<Creature>)>,
+) {
+    let Some(scheduled) = perturbation.scheduled.filter(|scheduled| scheduled.step == step.0) else {
+        return;
+    };
+
+    let mut creatures: Vec<_> = creature_query.iter().collect();
+    creatures.sort_by_key(|(entity, _)| *entity);
+    let particle = creatures.iter()
+        .flat_map(|(_, children)| children.iter())
+        .filter(|child| particle_query.contains(**child))
+        .nth(scheduled.particle)
+        .copied();
+
+    match particle.and_then(|particle| particle_query.get_mut(particle).ok().map(|transform| (particle, transform))) {
+        Some((particle, mut transform)) => {
+            transform.translation += perturbation.offset;
+            info!("step {}: moved {:?} by {}", step.0, particle, perturbation.offset);
+        }
+        None => warn!("step {}: there is no particle {} to perturb", step.0, scheduled.particle),
+    }
+}
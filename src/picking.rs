@@ -0,0 +1,82 @@
+use bevy::{
+    ecs::{query::Has, system::SystemParam},
+    prelude::*,
+    window::PrimaryWindow,
+};
+
+use crate::{minimap::MinimapCamera, replay::ReplayCursor, Creature, Particle};
+
+#[derive(SystemParam)]
+pub struct Cursor<'w, 's> {
+    window_query: Query<'w, 's, &'static Window, With<PrimaryWindow>>,
+    camera_query: Query<'w, 's, (&'static Camera, &'static GlobalTransform, Has<MinimapCamera>)>,
+    replay_cursor: Option<Res<'w, ReplayCursor>>,
+}
+
+impl Cursor<'_, '_> {
+    /// Cursor position in logical window coordinates, except while replaying.
+    pub fn screen_position(&self) -> Option<Vec2> {
+        if self.replay_cursor.is_some() {
+            return None;
+        }
+        self.window_query.get_single().ok()?.cursor_position()
+    }
+
+    pub fn world_position(&self) -> Option<Vec2> {
+        if let Some(replay_cursor) = &self.replay_cursor {
+            return replay_cursor.0;
+        }
+        let window = self.window_query.get_single().ok()?;
+        let cursor = window.cursor_position()?;
+        // With a split screen, through whichever camera's viewport the cursor
+        // is over, and nowhere when that's the minimap's.
+        let (camera, camera_transform, minimap) = self.camera_query.iter()
+            .filter(|(camera, ..)| camera.is_active)
+            .filter(|(camera, ..)| camera.logical_viewport_rect().is_some_and(|viewport| viewport.contains(cursor)))
+            .max_by_key(|(camera, ..)| camera.order)?;
+        if minimap {
+            return None;
+        }
+        let viewport = camera.logical_viewport_rect()?;
+        camera.viewport_to_world_2d(camera_transform, cursor - viewport.min)
+    }
+}
+
+/// Maps logical window coordinates to the world, through whichever camera's
+/// viewport they're in, for gizmos drawn in a fixed place on screen.
+#[derive(SystemParam)]
+pub struct ScreenSpace<'w, 's> {
+    camera_query: Query<'w, 's, (&'static Camera, &'static GlobalTransform), Without<MinimapCamera>>,
+}
+
+impl ScreenSpace<'_, '_> {
+    pub fn world_point(&self, point: Vec2) -> Option<Vec2> {
+        self.camera_query.iter()
+            .filter(|(camera, _)| camera.is_active)
+            .find_map(|(camera, camera_transform)| {
+                let viewport = camera.logical_viewport_rect()?;
+                viewport.contains(point).then(|| camera.viewport_to_world_2d(camera_transform, point - viewport.min))?
+            })
+    }
+
+    /// World length of `length` logical pixels at `point`.
+    pub fn world_length(&self, point: Vec2, length: f32) -> Option<f32> {
+        Some(self.world_point(point)?.distance(self.world_point(point + Vec2::X * length)?))
+    }
+}
+
+/// The particle nearest to `point` within `radius`, with the creature it belongs to.
+pub fn nearest_particle(
+    point: Vec2,
+    radius: f32,
+    creature_query: &Query<(Entity, &Transform, &Children), With<Creature>>,
+    particle_query: &Query<&Transform, With<Particle>>,
+) -> Option<(Entity, Entity)> {
+    creature_query.iter()
+        .flat_map(|(entity, creature_transform, children)| children.iter()
+            .filter_map(|child| particle_query.get(*child).ok().map(|transform| (*child, transform)))
+            .map(move |(child, transform)| (entity, child, (creature_transform.translation + transform.translation).truncate().distance(point))))
+        .filter(|(_, _, r)| *r < radius)
+        .min_by(|(_, _, a), (_, _, b)| a.total_cmp(b))
+        .map(|(creature, particle, _)| (creature, particle))
+}
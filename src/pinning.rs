@@ -0,0 +1,63 @@
+use bevy::prelude::*;
+
+use crate::{
+    picking::{nearest_particle, Cursor},
+    selection::PICK_RADIUS,
+    Creature, DisplayRadius, Particle,
+};
+
+/// A particle `update_position` leaves in place. It still feels the fields
+/// and still contributes to its neighbors'.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct Pinned;
+
+pub struct PinningPlugin;
+
+impl Plugin for PinningPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (toggle_pin, draw_pinned).chain());
+    }
+}
+
+pub fn pin_modifier_pressed(keys: &Input<KeyCode>) -> bool {
+    keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+}
+
+// Ctrl-click pins the particle under the cursor, or unpins it.
+fn toggle_pin(
+    mut commands: Commands,
+    buttons: Res<Input<MouseButton>>,
+    keys: Res<Input<KeyCode>>,
+    cursor: Cursor,
+    creature_query: Query<(Entity, &Transform, &Children), With<Creature>>,
+    particle_query: Query<&Transform, With<Particle>>,
+    pinned_query: Query<(), With<Pinned>>,
+) {
+    if !buttons.just_pressed(MouseButton::Left) || !pin_modifier_pressed(&keys) {
+        return;
+    }
+    let Some((_, particle)) = cursor.world_position()
+        .and_then(|point| nearest_particle(point, PICK_RADIUS, &creature_query, &particle_query))
+    else {
+        return;
+    };
+
+    if pinned_query.contains(particle) {
+        commands.entity(particle).remove::<Pinned>();
+    } else {
+        commands.entity(particle).insert(Pinned);
+    }
+}
+
+fn draw_pinned(
+    mut gizmos: Gizmos,
+    creature_query: Query<(&Transform, &Children), With<Creature>>,
+    pinned_query: Query<(&Transform, &DisplayRadius), With<Pinned>>,
+) {
+    for (creature_transform, children) in creature_query.iter() {
+        for (transform, display_radius) in pinned_query.iter_many(children) {
+            let radius = display_radius.0.unwrap_or(0.5);
+            gizmos.circle_2d((creature_transform.translation + transform.translation).truncate(), radius + 0.15, Color::ORANGE);
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use bevy::{
+    ecs::schedule::{BoxedScheduleLabel, ScheduleLabel},
+    prelude::*,
+    transform::TransformSystem,
+};
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+
+use crate::{
+    advance_step, apply_z_order, attach_particle_meshes, calculate_fields, clamp_forces, setup, spawn_scene,
+    species::InteractionMatrix, sum_pair_fields, timings::TimingsPlugin, update_position, update_size, ForceLimit,
+    ForceTerms, Integrator, InterCreature, InteractionRamp, KernelCutoff, MetricScale, MinRenderRadius, ParticleStyle,
+    RadiusSmoothing, Scene, SelfField, SimRng, SimStep, SizeMode, StepDiagnostics, ZOrderMode,
+};
+
+/// The simulation step on its own: the pair sums, the force terms and the
+/// move, with their resources at their defaults, and no window, camera or
+/// creatures. Add [`ScenePlugin`] to spawn creatures, and
+/// [`ParticleRenderPlugin`] and [`SceneCameraPlugin`] to draw them.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use particle_lenia::{ParticleLeniaPlugin, Scene, ScenePlugin};
+/// App::new()
+///     .add_plugins((MinimalPlugins, ParticleLeniaPlugin::default(), ScenePlugin { scene: Scene::single(7) }))
+///     .run();
+/// ```
+pub struct ParticleLeniaPlugin {
+    schedule: BoxedScheduleLabel,
+    field_sums: bool,
+}
+
+impl Default for ParticleLeniaPlugin {
+    fn default() -> Self {
+        Self { schedule: Box::new(Update), field_sums: true }
+    }
+}
+
+impl ParticleLeniaPlugin {
+    /// Steps the simulation in `schedule` instead of Update, e.g. FixedUpdate
+    /// to decouple it from the frame rate, or a schedule of the embedding
+    /// app's own that it runs when it likes.
+    pub fn in_schedule(schedule: impl ScheduleLabel) -> Self {
+        Self { schedule: Box::new(schedule), ..default() }
+    }
+
+    // Leaves the pair sums to another plugin, e.g. the compute shader's.
+    pub(crate) fn without_field_sums(self) -> Self {
+        Self { field_sums: false, ..self }
+    }
+}
+
+impl Plugin for ParticleLeniaPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(TimingsPlugin)
+            .init_resource::<InterCreature>()
+            .init_resource::<ForceTerms>()
+            .init_resource::<ForceLimit>()
+            .init_resource::<InteractionRamp>()
+            .init_resource::<KernelCutoff>()
+            .init_resource::<SelfField>()
+            .init_resource::<MetricScale>()
+            .init_resource::<Integrator>()
+            .init_resource::<InteractionMatrix>()
+            .init_resource::<SimStep>()
+            .init_resource::<StepDiagnostics>()
+            .add_systems(self.schedule.clone(), (calculate_fields, clamp_forces, update_position, advance_step).chain());
+        if self.field_sums {
+            app.add_systems(self.schedule.clone(), sum_pair_fields.before(calculate_fields));
+        }
+    }
+}
+
+/// Spawns the creatures of `scene` at startup, with the RNG seeded from it.
+pub struct ScenePlugin {
+    pub scene: Scene,
+}
+
+impl Plugin for ScenePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SimRng(ChaCha8Rng::seed_from_u64(self.scene.seed)))
+            .insert_resource(self.scene.clone())
+            .add_systems(Startup, spawn_scene);
+    }
+}
+
+/// Gives each particle a mesh and material, and sizes and layers them by
+/// their fields every frame. Needs the render plugins, but spawns no camera.
+pub struct ParticleRenderPlugin;
+
+impl Plugin for ParticleRenderPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SizeMode>()
+            .init_resource::<ZOrderMode>()
+            .init_resource::<RadiusSmoothing>()
+            .init_resource::<MinRenderRadius>()
+            .init_resource::<ParticleStyle>()
+            .add_systems(PreUpdate, attach_particle_meshes)
+            .add_systems(Update, update_size.after(calculate_fields))
+            .add_systems(PostUpdate, apply_z_order.after(TransformSystem::TransformPropagate));
+    }
+}
+
+/// Spawns a 2D camera at startup, zoomed to the scene's camera scale if
+/// there's a [`Scene`].
+pub struct SceneCameraPlugin;
+
+impl Plugin for SceneCameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup);
+    }
+}
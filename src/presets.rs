@@ -0,0 +1,180 @@
+use std::f32::consts::TAU;
+
+use bevy::{ecs::query::Has, prelude::*};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::{advance_step, param_map::ParameterAxis, selection::Selection, Creature, Integrator, Parameters, TimeScale};
+
+// Factor F7 and F8 scale the oscillation's amplitude and frequency by.
+const OSCILLATION_STEP: f32 = 1.25;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Preset {
+    /// The original parameters
+    Default,
+    /// Soft repulsion, packs into a dense blob
+    Tight,
+    /// Heavier kernel weight, settles at a lower density
+    Loose,
+}
+
+impl Preset {
+    pub fn parameters(self) -> Parameters {
+        match self {
+            Preset::Default => Parameters::default(),
+            Preset::Tight => Parameters {
+                c_rep: 0.4,
+                ..default()
+            },
+            Preset::Loose => Parameters {
+                mu_k: 6.0,
+                w_k: 0.06,
+                ..default()
+            },
+        }
+    }
+}
+
+impl Parameters {
+    pub fn lerp(&self, other: &Parameters, t: f32) -> Parameters {
+        let mix = |a: f32, b: f32| a + (b - a) * t;
+        Parameters {
+            mu_k: mix(self.mu_k, other.mu_k),
+            sigma_k: mix(self.sigma_k, other.sigma_k),
+            w_k: mix(self.w_k, other.w_k),
+            // Kernel and growth kinds don't blend, so the morph switches over halfway.
+            kernel: if t < 0.5 { self.kernel.clone() } else { other.kernel.clone() },
+            mu_g: mix(self.mu_g, other.mu_g),
+            sigma_g: mix(self.sigma_g, other.sigma_g),
+            growth: if t < 0.5 { self.growth } else { other.growth },
+            growth_kernel: if t < 0.5 { self.growth_kernel.clone() } else { other.growth_kernel.clone() },
+            c_rep: mix(self.c_rep, other.c_rep),
+        }
+    }
+}
+
+/// Moves a creature's parameters from `from` to `to` over `duration` units of simulation time.
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
+pub struct AnimateParams {
+    pub from: Parameters,
+    pub to: Parameters,
+    pub duration: f32,
+    pub elapsed: f32,
+}
+
+/// Swings one of a creature's parameters about its value as
+/// `base + amplitude * sin(2 pi frequency t)`, with `t` in units of
+/// simulation time, to drive the creature periodically. While an
+/// [`AnimateParams`] is moving the parameter too, `base` follows it, and it
+/// moves to wherever the parameter is set by anything else. The swing is
+/// clamped to the values [`Parameters::validate`] accepts.
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
+pub struct OscillateParam {
+    pub param: ParameterAxis,
+    pub amplitude: f32,
+    pub frequency: f32,
+    pub base: f32,
+    pub elapsed: f32,
+    // What the parameter was last set to here, to tell other edits from it.
+    #[serde(skip)]
+    set: Option<f32>,
+}
+
+impl OscillateParam {
+    pub fn new(param: ParameterAxis, amplitude: f32, frequency: f32, parameters: &Parameters) -> Self {
+        Self { param, amplitude, frequency, base: param.get(parameters), elapsed: 0.0, set: None }
+    }
+
+    fn value(&self) -> f32 {
+        self.param.clamp(self.base + self.amplitude * (TAU * self.frequency * self.elapsed).sin())
+    }
+}
+
+pub struct PresetPlugin;
+
+impl Plugin for PresetPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (animate_parameters, oscillate_parameters).chain().after(advance_step));
+    }
+}
+
+/// Keys to adjust the oscillation of the selected creatures, or of every
+/// oscillating creature when none are selected: F7 and Shift-F7 scale the
+/// amplitude down and up, F8 and Shift-F8 the frequency.
+pub struct OscillationControlsPlugin;
+
+impl Plugin for OscillationControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, adjust_oscillation.before(oscillate_parameters));
+    }
+}
+
+fn animate_parameters(
+    mut commands: Commands,
+    integrator: Res<Integrator>,
+    mut creature_query: Query<(Entity, &TimeScale, &mut Parameters, &mut AnimateParams), With<Creature>>,
+) {
+    for (entity, time_scale, mut parameters, mut animation) in creature_query.iter_mut() {
+        animation.elapsed += integrator.dt * time_scale.0;
+        let t = if animation.duration > 0.0 {
+            (animation.elapsed / animation.duration).min(1.0)
+        } else {
+            1.0
+        };
+
+        *parameters = animation.from.lerp(&animation.to, t);
+        if t >= 1.0 {
+            commands.entity(entity).remove::<AnimateParams>();
+        }
+    }
+}
+
+fn oscillate_parameters(
+    integrator: Res<Integrator>,
+    mut creature_query: Query<(&TimeScale, &mut Parameters, &mut OscillateParam, Has<AnimateParams>), With<Creature>>,
+) {
+    for (time_scale, mut parameters, mut oscillation, animating) in creature_query.iter_mut() {
+        // The animation has just set the parameter to where it's headed, so
+        // that's the value to swing about, as is one the param map, the
+        // timeline or anything else gave it since the last swing.
+        let current = oscillation.param.get(&parameters);
+        if animating || oscillation.set.is_some_and(|set| set != current) {
+            oscillation.base = current;
+        }
+        oscillation.elapsed += integrator.dt * time_scale.0;
+        let value = oscillation.value();
+        oscillation.param.set(&mut parameters, value);
+        oscillation.set = Some(value);
+    }
+}
+
+fn adjust_oscillation(
+    keys: Res<Input<KeyCode>>,
+    selection: Res<Selection>,
+    mut creature_query: Query<(Entity, &mut OscillateParam), With<Creature>>,
+) {
+    let factor = if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        OSCILLATION_STEP
+    } else {
+        1.0 / OSCILLATION_STEP
+    };
+    let (amplitude, frequency) = (keys.just_pressed(KeyCode::F7), keys.just_pressed(KeyCode::F8));
+    if !amplitude && !frequency {
+        return;
+    }
+
+    for (entity, mut oscillation) in creature_query.iter_mut() {
+        if !selection.0.is_empty() && !selection.0.contains(&entity) {
+            continue;
+        }
+        if amplitude {
+            oscillation.amplitude *= factor;
+        } else {
+            // Keep the phase where it is, so the parameter doesn't jump.
+            oscillation.elapsed /= factor;
+            oscillation.frequency *= factor;
+        }
+        info!("creature {:?} oscillates {} by {:.4} at {:.4}", entity, oscillation.param.name(), oscillation.amplitude, oscillation.frequency);
+    }
+}
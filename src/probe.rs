@@ -0,0 +1,70 @@
+use bevy::prelude::*;
+
+use crate::{repulsion_field, Creature, Fields, Parameters, Particle};
+
+/// Fields a test particle of a creature with `parameters` would see at `point`.
+pub fn probe_fields(point: Vec3, parameters: &Parameters, positions: &[Vec3]) -> Fields {
+    let mut fields = Fields {
+        R_val: repulsion_field(0.0, parameters.c_rep).0,
+        U_val: parameters.kernel_field(0.0).0,
+        ..default()
+    };
+
+    for x in positions {
+        let r = point.distance(*x);
+        if r < f32::EPSILON {
+            continue;
+        }
+        let r_grad = (point - *x) / r;
+
+        if r < 1.0 {
+            let (R, dR) = repulsion_field(r, parameters.c_rep);
+            fields.R_val += R;
+            fields.R_grad += r_grad * dR;
+        }
+
+        let (K, dK) = parameters.kernel_field(r);
+        fields.U_val += K;
+        fields.U_grad += r_grad * dK;
+    }
+
+    let (_, dG) = parameters.growth_field(fields.U_val);
+    fields.E_grad = fields.R_grad - (dG * fields.U_grad);
+    fields
+}
+
+/// Relaxes an isolated creature's particles in place with `steps` overdamped
+/// steps of size `dt`, as the simulation would without any other creature around.
+pub fn settle(positions: &mut [Vec3], parameters: &Parameters, steps: usize, dt: f32) {
+    for _ in 0..steps {
+        let gradients: Vec<Vec3> = positions.iter()
+            .map(|x| probe_fields(*x, parameters, positions).E_grad)
+            .collect();
+        for (x, E_grad) in positions.iter_mut().zip(gradients) {
+            *x -= dt * E_grad;
+        }
+    }
+}
+
+/// World-space particle positions of every creature, paired with its parameters.
+pub fn creature_positions(
+    creature_query: &Query<(&Transform, &Parameters, &Children), With<Creature>>,
+    particle_query: &Query<&Transform, With<Particle>>,
+) -> Vec<(Parameters, Vec<Vec3>)> {
+    creature_query.iter()
+        .map(|(creature_transform, parameters, children)| {
+            let positions = children.iter()
+                .filter_map(|child| particle_query.get(*child).ok())
+                .map(|transform| creature_transform.translation + transform.translation)
+                .collect();
+            (parameters.clone(), positions)
+        })
+        .collect()
+}
+
+/// Sum of the energy gradients every creature would exert on a test particle at `point`.
+pub fn probe_energy_gradient(point: Vec3, creatures: &[(Parameters, Vec<Vec3>)]) -> Vec3 {
+    creatures.iter()
+        .map(|(parameters, positions)| probe_fields(point, parameters, positions).E_grad)
+        .sum()
+}
@@ -0,0 +1,214 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    force_panel::ForcePanel,
+    kernel_editor::KernelEditor,
+    minimap::MinimapCamera,
+    param_map::ParamMap,
+    picking::{Cursor, ScreenSpace},
+    probe::creature_positions,
+    repulsion_field, Creature, Parameters, Particle,
+};
+
+// Size of the plot and its distance from the top-right corner, in logical
+// pixels.
+const PLOT_SIZE: Vec2 = Vec2::new(320.0, 160.0);
+const PLOT_MARGIN: f32 = 16.0;
+// How close to an end of the line a press has to be to grab it, and the
+// radius of the marks drawn there, in logical pixels.
+const GRAB_RADIUS: f32 = 8.0;
+const END_MARK: f32 = 4.0;
+const U_COLOR: Color = Color::CYAN;
+const R_COLOR: Color = Color::ORANGE_RED;
+
+/// A segment in the world the U and R fields are sampled along. While
+/// `active`, left-dragging draws a new one, or moves whichever end the drag
+/// starts on.
+#[derive(Resource, Default)]
+pub struct ProbeLine {
+    pub active: bool,
+    line: Option<(Vec2, Vec2)>,
+    // The end being dragged, 0 for the start and 1 for the end.
+    dragging: Option<usize>,
+    // U and R at each sample, from start to end.
+    samples: Vec<(f32, f32)>,
+}
+
+/// F5 toggles a probe line, and a plot in the top-right corner of the U and
+/// R fields every creature's particles sum to at `samples` evenly spaced
+/// points along it, each scaled to its own largest value: a cross-section of
+/// the landscape the heatmap shows from above.
+pub struct ProbeLinePlugin {
+    pub samples: usize,
+}
+
+#[derive(Resource)]
+struct ProbeSamples(usize);
+
+#[derive(Component)]
+struct ProbeLineText;
+
+impl Plugin for ProbeLinePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ProbeLine>()
+            .insert_resource(ProbeSamples(self.samples.max(2)))
+            .add_systems(Startup, spawn_probe_text)
+            .add_systems(Update, (toggle_probe_line, drag_probe_line, sample_probe_line, draw_probe_line, update_probe_text).chain());
+    }
+}
+
+fn spawn_probe_text(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section("", TextStyle {
+            font_size: 14.0,
+            color: Color::WHITE,
+            ..default()
+        })
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(PLOT_MARGIN + PLOT_SIZE.y + 4.0),
+            right: Val::Px(PLOT_MARGIN),
+            ..default()
+        }),
+        ProbeLineText,
+    ));
+}
+
+fn toggle_probe_line(keys: Res<Input<KeyCode>>, mut probe: ResMut<ProbeLine>) {
+    if keys.just_pressed(KeyCode::F5) {
+        probe.active = !probe.active;
+        probe.dragging = None;
+        info!("probe line {}", if probe.active { "enabled" } else { "disabled" });
+    }
+}
+
+// Presses on the kernel editor, the parameter map and the force panel are
+// for them.
+fn drag_probe_line(
+    buttons: Res<Input<MouseButton>>,
+    cursor: Cursor,
+    editor: Option<Res<KernelEditor>>,
+    param_map: Option<Res<ParamMap>>,
+    force_panel: Option<Res<ForcePanel>>,
+    mut probe: ResMut<ProbeLine>,
+    projection_query: Query<&OrthographicProjection, Without<MinimapCamera>>,
+) {
+    if !probe.active || !buttons.pressed(MouseButton::Left) {
+        probe.dragging = None;
+        return;
+    }
+    let Some(point) = cursor.world_position() else {
+        return;
+    };
+    if buttons.just_pressed(MouseButton::Left) {
+        if editor.is_some_and(|editor| cursor.screen_position().is_some_and(|point| editor.covers(point))) {
+            return;
+        }
+        if param_map.is_some_and(|map| cursor.screen_position().is_some_and(|point| map.covers(point))) {
+            return;
+        }
+        if force_panel.is_some_and(|panel| panel.hovered()) {
+            return;
+        }
+        let grab = GRAB_RADIUS * projection_query.iter().map(|projection| projection.scale).fold(0.0, f32::max);
+        let grabbed = probe.line
+            .map(|(start, end)| [start, end])
+            .and_then(|ends| (0..2).filter(|end| ends[*end].distance(point) < grab).min_by(|a, b| {
+                ends[*a].distance(point).total_cmp(&ends[*b].distance(point))
+            }));
+        if grabbed.is_none() {
+            probe.line = Some((point, point));
+        }
+        probe.dragging = Some(grabbed.unwrap_or(1));
+    }
+    let (Some(end), Some((start, finish))) = (probe.dragging, probe.line) else {
+        return;
+    };
+    probe.line = Some(if end == 0 { (point, finish) } else { (start, point) });
+}
+
+fn sample_probe_line(
+    samples: Res<ProbeSamples>,
+    mut probe: ResMut<ProbeLine>,
+    creature_query: Query<(&Transform, &Parameters, &Children), With<Creature>>,
+    particle_query: Query<&Transform, With<Particle>>,
+) {
+    let Some((start, end)) = probe.line.filter(|_| probe.active) else {
+        probe.samples.clear();
+        return;
+    };
+    let creatures = creature_positions(&creature_query, &particle_query);
+    probe.samples = (0..samples.0)
+        .map(|i| start.lerp(end, i as f32 / (samples.0 - 1) as f32).extend(0.0))
+        .map(|point| {
+            creatures.iter()
+                .flat_map(|(parameters, positions)| positions.iter().map(move |x| (parameters, point.distance(*x))))
+                .fold((0.0, 0.0), |(U, R), (parameters, r)| {
+                    let repulsion = if r < 1.0 { repulsion_field(r, parameters.c_rep).0 } else { 0.0 };
+                    (U + parameters.kernel_field(r).0, R + repulsion)
+                })
+        })
+        .collect();
+}
+
+fn plot_rect(window: &Window) -> Rect {
+    let min = Vec2::new(window.width() - PLOT_MARGIN - PLOT_SIZE.x, PLOT_MARGIN);
+    Rect::from_corners(min, min + PLOT_SIZE)
+}
+
+fn draw_probe_line(
+    mut gizmos: Gizmos,
+    probe: Res<ProbeLine>,
+    screen: ScreenSpace,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    projection_query: Query<&OrthographicProjection, Without<MinimapCamera>>,
+) {
+    let Some((start, end)) = probe.line.filter(|_| probe.active) else {
+        return;
+    };
+    let scale = projection_query.iter().map(|projection| projection.scale).fold(0.0, f32::max);
+    gizmos.line_2d(start, end, Color::WHITE);
+    for point in [start, end] {
+        gizmos.circle_2d(point, END_MARK * scale, Color::WHITE);
+    }
+
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let plot = plot_rect(window);
+    let corners = [plot.min, Vec2::new(plot.max.x, plot.min.y), plot.max, Vec2::new(plot.min.x, plot.max.y), plot.min];
+    gizmos.linestrip_2d(corners.into_iter().filter_map(|point| screen.world_point(point)), Color::GRAY);
+
+    let last = (probe.samples.len().max(2) - 1) as f32;
+    let (max_U, max_R) = field_maxima(&probe.samples);
+    for (field, max, color) in [(0, max_U, U_COLOR), (1, max_R, R_COLOR)] {
+        if max <= 0.0 {
+            continue;
+        }
+        let curve = probe.samples.iter().enumerate().filter_map(|(i, sample)| {
+            let value = if field == 0 { sample.0 } else { sample.1 };
+            screen.world_point(Vec2::new(plot.min.x + i as f32 / last * plot.width(), plot.max.y - value / max * plot.height()))
+        });
+        gizmos.linestrip_2d(curve, color);
+    }
+}
+
+fn field_maxima(samples: &[(f32, f32)]) -> (f32, f32) {
+    samples.iter().fold((0.0, 0.0), |(max_U, max_R), (U, R)| (f32::max(max_U, *U), f32::max(max_R, *R)))
+}
+
+fn update_probe_text(
+    probe: Res<ProbeLine>,
+    mut text_query: Query<(&mut Text, &mut Visibility), With<ProbeLineText>>,
+) {
+    for (mut text, mut visibility) in text_query.iter_mut() {
+        *visibility = if probe.active { Visibility::Inherited } else { Visibility::Hidden };
+        text.sections[0].value = match probe.line {
+            Some((start, end)) if !probe.samples.is_empty() => {
+                let (max_U, max_R) = field_maxima(&probe.samples);
+                format!("U (cyan) up to {:.3}, R (red) up to {:.3}, along {:.2}", max_U, max_R, start.distance(end))
+            }
+            _ => "probe line: drag across the field".to_string(),
+        };
+    }
+}
@@ -0,0 +1,83 @@
+use bevy::prelude::*;
+use clap::ValueEnum;
+
+use crate::{streamlines::Streamlines, ParticleStyle};
+
+/// One knob for the render settings that trade frame rate for smoothness.
+#[derive(ValueEnum, Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RenderQuality {
+    /// No antialiasing, coarse circles, streamlines traced every 30 frames
+    Performance,
+    /// 4x MSAA, streamlines traced every 10 frames
+    #[default]
+    Balanced,
+    /// 4x MSAA, smooth circles, streamlines traced every frame
+    Quality,
+}
+
+impl RenderQuality {
+    fn msaa(self) -> Msaa {
+        match self {
+            RenderQuality::Performance => Msaa::Off,
+            // 8x isn't supported by every adapter, so quality stops at 4x.
+            RenderQuality::Balanced | RenderQuality::Quality => Msaa::Sample4,
+        }
+    }
+
+    fn circle_vertices(self) -> usize {
+        match self {
+            RenderQuality::Performance => 12,
+            RenderQuality::Balanced => 32,
+            RenderQuality::Quality => 64,
+        }
+    }
+
+    fn streamline_refresh_frames(self) -> u32 {
+        match self {
+            RenderQuality::Performance => 30,
+            RenderQuality::Balanced => 10,
+            RenderQuality::Quality => 0,
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            RenderQuality::Performance => RenderQuality::Balanced,
+            RenderQuality::Balanced => RenderQuality::Quality,
+            RenderQuality::Quality => RenderQuality::Performance,
+        }
+    }
+}
+
+pub struct QualityPlugin {
+    pub quality: RenderQuality,
+}
+
+impl Plugin for QualityPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.quality)
+            .insert_resource(self.quality.msaa())
+            .add_systems(Update, (cycle_quality, apply_quality.run_if(resource_changed::<RenderQuality>())).chain());
+    }
+}
+
+// Q cycles through the presets.
+fn cycle_quality(keys: Res<Input<KeyCode>>, mut quality: ResMut<RenderQuality>) {
+    if keys.just_pressed(KeyCode::Q) {
+        *quality = quality.next();
+        info!("render quality: {:?}", *quality);
+    }
+}
+
+// Particle meshes are rebuilt every frame, so they pick up the new circle
+// resolution straight away.
+fn apply_quality(
+    quality: Res<RenderQuality>,
+    mut msaa: ResMut<Msaa>,
+    mut style: ResMut<ParticleStyle>,
+    mut streamlines: ResMut<Streamlines>,
+) {
+    *msaa = quality.msaa();
+    style.circle_vertices = quality.circle_vertices();
+    streamlines.refresh_frames = quality.streamline_refresh_frames();
+}
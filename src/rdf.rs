@@ -0,0 +1,216 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+};
+
+use bevy::{app::AppExit, prelude::*, window::PrimaryWindow};
+
+use crate::{
+    headless::exit_after_steps,
+    output::{OutputConfig, OutputName},
+    picking::ScreenSpace,
+    DirectPairSums, Scene, SimStep,
+};
+
+// Size of the plot and its gap from the top of the window, in logical pixels.
+const PLOT_SIZE: Vec2 = Vec2::new(320.0, 160.0);
+const PLOT_MARGIN: f32 = 16.0;
+
+/// Histogram of the distances between particles of the same creature out to
+/// `max_r`, filled by the pair loop while `recording`, and the radial
+/// distribution function g(r) it gives, whose peaks sit at the creature's
+/// characteristic spacings. Pairs aren't seen with `--barnes-hut` or `--gpu`.
+#[derive(Resource)]
+pub struct RadialDistribution {
+    pub recording: bool,
+    max_r: f32,
+    counts: Vec<u64>,
+    samples: u64,
+}
+
+impl RadialDistribution {
+    pub fn new(max_r: f32, bins: usize) -> Self {
+        RadialDistribution { recording: false, max_r, counts: vec![0; bins.max(1)], samples: 0 }
+    }
+
+    fn bin_width(&self) -> f32 {
+        self.max_r / self.counts.len() as f32
+    }
+
+    pub(crate) fn start_sample(&mut self) {
+        self.samples += 1;
+    }
+
+    pub(crate) fn record(&mut self, r: f32) {
+        if r < self.max_r {
+            let last = self.counts.len() - 1;
+            let bin = ((r / self.bin_width()) as usize).min(last);
+            self.counts[bin] += 1;
+        }
+    }
+
+    fn clear(&mut self) {
+        self.counts.iter_mut().for_each(|count| *count = 0);
+        self.samples = 0;
+    }
+
+    /// g(r) at each bin's center: the pair count per unit area of its annulus,
+    /// normalized so its area-weighted mean out to `max_r` is 1.
+    pub fn g(&self) -> Vec<(f32, f32)> {
+        let total = self.counts.iter().sum::<u64>().max(1) as f32;
+        let width = self.bin_width();
+        self.counts.iter().enumerate()
+            .map(|(bin, count)| {
+                let r = (bin as f32 + 0.5) * width;
+                (r, *count as f32 * self.max_r * self.max_r / (2.0 * r * width * total))
+            })
+            .collect()
+    }
+
+    /// Writes `r,g,count` rows, one per bin.
+    pub fn write_csv(&self, file: File) -> io::Result<()> {
+        let mut writer = BufWriter::new(file);
+        writeln!(writer, "r,g,count")?;
+        for ((r, g), count) in self.g().into_iter().zip(&self.counts) {
+            writeln!(writer, "{},{},{}", r, g, count)?;
+        }
+        writer.flush()
+    }
+}
+
+/// With `interactive`, D starts recording g(r) afresh and plots it at the top
+/// of the window, or stops, and Shift-D writes it to a CSV named by the output
+/// template. With `csv`, it's recorded from the start and written there on
+/// exit.
+pub struct RadialDistributionPlugin {
+    pub max_r: f32,
+    pub bins: usize,
+    pub interactive: bool,
+    pub csv: Option<File>,
+}
+
+#[derive(Resource)]
+struct ExitCsv(Option<File>);
+
+#[derive(Component)]
+struct RdfText;
+
+impl Plugin for RadialDistributionPlugin {
+    fn build(&self, app: &mut App) {
+        let mut rdf = RadialDistribution::new(self.max_r, self.bins);
+        rdf.recording = self.csv.is_some();
+        app.insert_resource(rdf);
+
+        if let Some(file) = &self.csv {
+            let file = file.try_clone().expect("failed to open the g(r) file");
+            app.insert_resource(ExitCsv(Some(file)))
+                .add_systems(Last, write_on_exit.after(exit_after_steps));
+        }
+        if self.interactive {
+            app.add_systems(Startup, spawn_rdf_text)
+                .add_systems(Update, (toggle_rdf, export_rdf, draw_rdf, update_rdf_text));
+        }
+    }
+}
+
+fn write_on_exit(rdf: Res<RadialDistribution>, mut csv: ResMut<ExitCsv>, mut exit: EventReader<AppExit>) {
+    if exit.iter().next().is_none() {
+        return;
+    }
+    if let Some(file) = csv.0.take() {
+        match rdf.write_csv(file) {
+            Ok(()) => info!("wrote g(r) over {} samples", rdf.samples),
+            Err(err) => error!("failed to write g(r): {}", err),
+        }
+    }
+}
+
+fn spawn_rdf_text(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section("", TextStyle {
+            font_size: 14.0,
+            color: Color::WHITE,
+            ..default()
+        })
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(PLOT_MARGIN + PLOT_SIZE.y + 4.0),
+            left: Val::Percent(50.0),
+            margin: UiRect::left(Val::Px(-PLOT_SIZE.x / 2.0)),
+            ..default()
+        }),
+        RdfText,
+    ));
+}
+
+fn toggle_rdf(keys: Res<Input<KeyCode>>, direct: Res<DirectPairSums>, mut rdf: ResMut<RadialDistribution>) {
+    if keys.just_pressed(KeyCode::D) && !keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        rdf.recording = !rdf.recording;
+        if rdf.recording {
+            rdf.clear();
+            direct.warn_unless("g(r)");
+        }
+    }
+}
+
+fn export_rdf(
+    keys: Res<Input<KeyCode>>,
+    rdf: Res<RadialDistribution>,
+    output: Res<OutputConfig>,
+    scene: Res<Scene>,
+    step: Res<SimStep>,
+) {
+    if !(keys.just_pressed(KeyCode::D) && keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight])) {
+        return;
+    }
+    let name = OutputName { step: step.0, seed: scene.seed, creature: None };
+    let written = output.path(&name, Some("csv"))
+        .and_then(|path| File::create(&path).and_then(|file| rdf.write_csv(file)).map(|_| path));
+    match written {
+        Ok(path) => info!("wrote g(r) over {} samples to {}", rdf.samples, path.display()),
+        Err(err) => error!("failed to write g(r) under {}: {}", output.dir.display(), err),
+    }
+}
+
+fn draw_rdf(
+    rdf: Res<RadialDistribution>,
+    screen: ScreenSpace,
+    mut gizmos: Gizmos,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    if !rdf.recording {
+        return;
+    }
+
+    let left = (window.width() - PLOT_SIZE.x) / 2.0;
+    let plot = Rect::from_corners(Vec2::new(left, PLOT_MARGIN), Vec2::new(left + PLOT_SIZE.x, PLOT_MARGIN + PLOT_SIZE.y));
+    let corners = [plot.min, Vec2::new(plot.max.x, plot.min.y), plot.max, Vec2::new(plot.min.x, plot.max.y), plot.min];
+    gizmos.linestrip_2d(corners.into_iter().filter_map(|point| screen.world_point(point)), Color::GRAY);
+
+    let g = rdf.g();
+    let max_g = g.iter().map(|(_, g)| *g).fold(1.0, f32::max);
+    // g = 1, what an even spread would give.
+    let one = plot.max.y - plot.height() / max_g;
+    gizmos.linestrip_2d([Vec2::new(plot.min.x, one), Vec2::new(plot.max.x, one)].into_iter().filter_map(|point| screen.world_point(point)), Color::DARK_GRAY);
+    let line = g.iter().filter_map(|(r, g)| {
+        screen.world_point(Vec2::new(plot.min.x + r / rdf.max_r * plot.width(), plot.max.y - g / max_g * plot.height()))
+    });
+    gizmos.linestrip_2d(line, Color::ORANGE);
+}
+
+fn update_rdf_text(
+    rdf: Res<RadialDistribution>,
+    mut text_query: Query<(&mut Text, &mut Visibility), With<RdfText>>,
+) {
+    let peak = rdf.g().into_iter().max_by(|(_, a), (_, b)| a.total_cmp(b));
+    for (mut text, mut visibility) in text_query.iter_mut() {
+        *visibility = if rdf.recording { Visibility::Inherited } else { Visibility::Hidden };
+        text.sections[0].value = match peak {
+            Some((r, g)) if rdf.samples > 0 => format!("g(r), r 0 .. {}, {} samples, peak {:.2} at r {:.2}", rdf.max_r, rdf.samples, g, r),
+            _ => format!("g(r), r 0 .. {}", rdf.max_r),
+        };
+    }
+}
@@ -0,0 +1,39 @@
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use bevy::prelude::*;
+
+/// Holds the simulation to at most `hz` steps per second of wall-clock time
+/// by sleeping at the end of any frame that finishes early, so recordings
+/// play back at a known pace. A frame that runs late starts the next one's
+/// deadline afresh instead of letting later frames rush to catch up.
+pub struct RealtimeCapPlugin {
+    pub hz: f32,
+}
+
+#[derive(Resource)]
+struct FramePacing {
+    period: Duration,
+    deadline: Option<Instant>,
+}
+
+impl Plugin for RealtimeCapPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(FramePacing { period: Duration::from_secs_f32(1.0 / self.hz), deadline: None })
+            .add_systems(Last, pace_frame);
+    }
+}
+
+fn pace_frame(mut pacing: ResMut<FramePacing>) {
+    let now = Instant::now();
+    let deadline = match pacing.deadline {
+        Some(deadline) if deadline > now => {
+            thread::sleep(deadline - now);
+            deadline
+        }
+        _ => now,
+    };
+    pacing.deadline = Some(deadline + pacing.period);
+}
@@ -0,0 +1,50 @@
+use bevy::prelude::*;
+
+use crate::{advance_step, update_position, Creature, Particle};
+
+/// Keeps each creature's particles near its own origin, where `f32` spacing
+/// is finest. Once their centroid drifts further than `max_distance` from
+/// the creature's origin, the drift is moved onto the creature's transform:
+/// world positions stay where they were, so nothing jumps on screen.
+pub struct RecenterPlugin {
+    pub max_distance: f32,
+}
+
+#[derive(Resource)]
+struct RecenterDistance(f32);
+
+impl Plugin for RecenterPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(RecenterDistance(self.max_distance))
+            .add_systems(Update, recenter_creatures.after(update_position).before(advance_step));
+    }
+}
+
+// The offset is rounded to whole units, which most coordinates shift by
+// exactly. The rest are rounded at their new, finer spacing, far less than
+// the error the drift was causing.
+fn recenter_creatures(
+    max_distance: Res<RecenterDistance>,
+    mut creature_query: Query<(Entity, &mut Transform, &Children), With<Creature>>,
+    mut particle_query: Query<&mut Transform, (With<Particle>, Without<Creature>)>,
+) {
+    for (creature, mut creature_transform, children) in creature_query.iter_mut() {
+        let (sum, count) = particle_query.iter_many(children)
+            .fold((Vec3::ZERO, 0), |(sum, count), transform| (sum + transform.translation, count + 1));
+        if count == 0 {
+            continue;
+        }
+        let centroid = sum / count as f32;
+        if centroid.length() <= max_distance.0 {
+            continue;
+        }
+
+        let offset = centroid.round();
+        let mut particles = particle_query.iter_many_mut(children);
+        while let Some(mut transform) = particles.fetch_next() {
+            transform.translation -= offset;
+        }
+        creature_transform.translation += offset;
+        debug!("recentered {:?} by {}", creature, offset);
+    }
+}
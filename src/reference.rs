@@ -0,0 +1,75 @@
+use bevy::prelude::*;
+
+// Depth of the reference image, behind the heatmap and every particle.
+const REFERENCE_Z: f32 = -20.0;
+
+/// Shows an image behind the simulation, centered on `center` and `width`
+/// world units across with its own aspect ratio, to compare creatures to.
+pub struct ReferencePlugin {
+    /// Image under assets/, or anywhere given an absolute path
+    pub path: String,
+    pub center: Vec2,
+    pub width: f32,
+    pub opacity: f32,
+}
+
+#[derive(Resource)]
+struct Reference {
+    path: String,
+    center: Vec2,
+    width: f32,
+    opacity: f32,
+}
+
+#[derive(Component)]
+struct ReferenceSprite;
+
+impl Plugin for ReferencePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Reference {
+                path: self.path.clone(),
+                center: self.center,
+                width: self.width,
+                opacity: self.opacity.clamp(0.0, 1.0),
+            })
+            .add_systems(Startup, spawn_reference)
+            .add_systems(Update, fit_reference);
+    }
+}
+
+fn spawn_reference(mut commands: Commands, reference: Res<Reference>, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        SpriteBundle {
+            texture: asset_server.load(reference.path.as_str()),
+            sprite: Sprite {
+                color: Color::WHITE.with_a(reference.opacity),
+                ..default()
+            },
+            transform: Transform::from_translation(reference.center.extend(REFERENCE_Z)),
+            // Hidden until it's loaded and its size is known.
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        ReferenceSprite,
+    ));
+}
+
+// Sizes the sprite to the image's aspect ratio once it's loaded.
+fn fit_reference(
+    reference: Res<Reference>,
+    images: Res<Assets<Image>>,
+    mut sprite_query: Query<(&Handle<Image>, &mut Sprite, &mut Visibility), With<ReferenceSprite>>,
+) {
+    for (texture, mut sprite, mut visibility) in sprite_query.iter_mut() {
+        if sprite.custom_size.is_some() {
+            continue;
+        }
+        let Some(image) = images.get(texture) else {
+            continue;
+        };
+        let size = image.size();
+        sprite.custom_size = Some(Vec2::new(reference.width, reference.width * size.y / size.x));
+        *visibility = Visibility::Inherited;
+        info!("showing reference {} ({}x{})", reference.path, size.x, size.y);
+    }
+}
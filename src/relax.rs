@@ -0,0 +1,112 @@
+use bevy::prelude::*;
+
+use crate::{selection::Selection, update_position, Creature, Fields, Particle, SimStep, TimeScale};
+
+/// F10 relaxes the selected creatures toward a local equilibrium: every other
+/// creature is held still while the simulation steps on, until the largest
+/// |E_grad| among the selected creatures' particles falls below `tolerance`
+/// or `max_steps` have gone by, and then reports how many steps it took.
+/// Pressing F10 again stops early.
+pub struct RelaxPlugin {
+    pub tolerance: f32,
+    pub max_steps: u64,
+}
+
+#[derive(Resource)]
+struct RelaxSettings {
+    tolerance: f32,
+    max_steps: u64,
+}
+
+// The creatures being relaxed, the step it started at, and the time scales
+// of the creatures held still, to give back once it's done.
+#[derive(Resource)]
+struct Relaxing {
+    creatures: Vec<Entity>,
+    start: u64,
+    held: Vec<(Entity, f32)>,
+}
+
+impl Plugin for RelaxPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(RelaxSettings { tolerance: self.tolerance, max_steps: self.max_steps })
+            .add_systems(Update, start_relaxing)
+            .add_systems(Update, check_relaxed.after(update_position));
+    }
+}
+
+fn start_relaxing(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    step: Res<SimStep>,
+    selection: Res<Selection>,
+    relaxing: Option<Res<Relaxing>>,
+    mut creature_query: Query<(Entity, &mut TimeScale), With<Creature>>,
+) {
+    if !keys.just_pressed(KeyCode::F10) {
+        return;
+    }
+    if let Some(relaxing) = relaxing {
+        info!("stopped relaxing after {} steps", step.0 - relaxing.start);
+        release(&mut commands, &relaxing, &mut creature_query);
+        return;
+    }
+    let creatures: Vec<Entity> = selection.0.iter().copied().filter(|entity| creature_query.contains(*entity)).collect();
+    if creatures.is_empty() {
+        info!("select a creature to relax");
+        return;
+    }
+
+    let mut held = Vec::new();
+    for (entity, mut time_scale) in creature_query.iter_mut() {
+        if !creatures.contains(&entity) {
+            held.push((entity, time_scale.0));
+            time_scale.0 = 0.0;
+        }
+    }
+    info!("relaxing {} creatures", creatures.len());
+    commands.insert_resource(Relaxing { creatures, start: step.0, held });
+}
+
+fn check_relaxed(
+    mut commands: Commands,
+    step: Res<SimStep>,
+    settings: Res<RelaxSettings>,
+    relaxing: Option<Res<Relaxing>>,
+    mut creature_query: Query<(Entity, &mut TimeScale), With<Creature>>,
+    children_query: Query<&Children, With<Creature>>,
+    particle_query: Query<&Fields, With<Particle>>,
+) {
+    let Some(relaxing) = relaxing else {
+        return;
+    };
+    // The gradients the step just moved the particles down.
+    let max_force = relaxing.creatures.iter()
+        .filter_map(|creature| children_query.get(*creature).ok())
+        .flat_map(|children| particle_query.iter_many(children))
+        .map(|fields| fields.E_grad.length())
+        .fold(0.0, f32::max);
+    let steps = step.0 - relaxing.start + 1;
+
+    if max_force < settings.tolerance {
+        info!("relaxed in {} steps, max |E_grad| {:.2e}", steps, max_force);
+    } else if steps >= settings.max_steps {
+        warn!("stopped relaxing after {} steps without converging, max |E_grad| {:.2e}", steps, max_force);
+    } else {
+        return;
+    }
+    release(&mut commands, &relaxing, &mut creature_query);
+}
+
+// Gives the held creatures their time scales back, unless a creature has
+// been given a new one in the meantime.
+fn release(commands: &mut Commands, relaxing: &Relaxing, creature_query: &mut Query<(Entity, &mut TimeScale), With<Creature>>) {
+    for (entity, time_scale) in relaxing.held.iter() {
+        if let Ok((_, mut held)) = creature_query.get_mut(*entity) {
+            if held.0 == 0.0 {
+                held.0 = *time_scale;
+            }
+        }
+    }
+    commands.remove_resource::<Relaxing>();
+}
@@ -0,0 +1,151 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use bevy::{input::InputSystem, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{picking::Cursor, SimStep};
+
+/// One change in user input, stamped with the simulation step it was seen at.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct InputRecord {
+    pub step: u64,
+    #[serde(flatten)]
+    pub event: InputEvent,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "input", rename_all = "snake_case")]
+pub enum InputEvent {
+    Key { key: KeyCode, pressed: bool },
+    Button { button: MouseButton, pressed: bool },
+    Cursor { position: Option<Vec2> },
+}
+
+pub fn load_recording(path: &Path) -> io::Result<Vec<InputRecord>> {
+    BufReader::new(File::open(path)?).lines()
+        .filter(|line| !matches!(line, Ok(line) if line.trim().is_empty()))
+        .map(|line| serde_json::from_str(&line?).map_err(io::Error::from))
+        .collect()
+}
+
+/// Cursor position in world space while a recording is played back, used by
+/// [`Cursor`] in place of the real one.
+#[derive(Resource, Default)]
+pub struct ReplayCursor(pub Option<Vec2>);
+
+/// Writes every input change, along with the cursor's world position whenever
+/// it moves, so that the session can be replayed step for step.
+pub struct RecordPlugin {
+    pub file: File,
+}
+
+#[derive(Resource)]
+struct Recorder {
+    writer: BufWriter<File>,
+    cursor: Option<Vec2>,
+}
+
+impl Plugin for RecordPlugin {
+    fn build(&self, app: &mut App) {
+        let file = self.file.try_clone().expect("failed to duplicate the recording file handle");
+        app.insert_resource(Recorder {
+                writer: BufWriter::new(file),
+                cursor: None,
+            })
+            .add_systems(PreUpdate, record_input.after(InputSystem));
+    }
+}
+
+fn record_input(
+    step: Res<SimStep>,
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<MouseButton>>,
+    cursor: Cursor,
+    mut recorder: ResMut<Recorder>,
+) {
+    let mut events = Vec::new();
+
+    let position = cursor.world_position();
+    if position != recorder.cursor {
+        recorder.cursor = position;
+        events.push(InputEvent::Cursor { position });
+    }
+    events.extend(keys.get_just_pressed().map(|key| InputEvent::Key { key: *key, pressed: true }));
+    events.extend(keys.get_just_released().map(|key| InputEvent::Key { key: *key, pressed: false }));
+    events.extend(buttons.get_just_pressed().map(|button| InputEvent::Button { button: *button, pressed: true }));
+    events.extend(buttons.get_just_released().map(|button| InputEvent::Button { button: *button, pressed: false }));
+    if events.is_empty() {
+        return;
+    }
+
+    let writer = &mut recorder.writer;
+    let result = events.into_iter()
+        .try_for_each(|event| {
+            serde_json::to_writer(&mut *writer, &InputRecord { step: step.0, event })?;
+            writeln!(writer)
+        })
+        .and_then(|_| writer.flush());
+    if let Err(err) = result {
+        error!("failed to write input recording: {}", err);
+    }
+}
+
+/// Replaces live keyboard, mouse and cursor input with a recording, applying
+/// each event at the step it was recorded at.
+pub struct ReplayPlugin {
+    pub records: Vec<InputRecord>,
+}
+
+#[derive(Resource)]
+struct Replay {
+    records: Vec<InputRecord>,
+    next: usize,
+    keys: Input<KeyCode>,
+    buttons: Input<MouseButton>,
+}
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Replay {
+                records: self.records.clone(),
+                next: 0,
+                keys: default(),
+                buttons: default(),
+            })
+            .init_resource::<ReplayCursor>()
+            .add_systems(PreUpdate, play_back_input.after(InputSystem));
+    }
+}
+
+fn play_back_input(
+    step: Res<SimStep>,
+    mut replay: ResMut<Replay>,
+    mut keys: ResMut<Input<KeyCode>>,
+    mut buttons: ResMut<Input<MouseButton>>,
+    mut cursor: ResMut<ReplayCursor>,
+) {
+    let replay = &mut *replay;
+    replay.keys.clear();
+    replay.buttons.clear();
+
+    while let Some(record) = replay.records.get(replay.next).filter(|record| record.step <= step.0) {
+        match record.event {
+            InputEvent::Key { key, pressed: true } => replay.keys.press(key),
+            InputEvent::Key { key, pressed: false } => replay.keys.release(key),
+            InputEvent::Button { button, pressed: true } => replay.buttons.press(button),
+            InputEvent::Button { button, pressed: false } => replay.buttons.release(button),
+            InputEvent::Cursor { position } => cursor.0 = position,
+        }
+        replay.next += 1;
+        if replay.next == replay.records.len() {
+            info!("replay finished at step {}", step.0);
+        }
+    }
+
+    keys.clone_from(&replay.keys);
+    buttons.clone_from(&replay.buttons);
+}
@@ -0,0 +1,100 @@
+use bevy::{app::AppExit, prelude::*, utils::HashSet};
+
+use crate::{
+    events::CreatureExploded,
+    headless::exit_after_steps,
+    creature_rng, spawn_creature,
+    stats::{update_creature_stats, CreatureStats},
+    Creature, CreatureSpawn, Integrator, Parameters, Scene, SceneIndex, SimStep,
+};
+
+/// Despawns a scene creature that explodes, or whose spread passes
+/// `max_spread`, and spawns a fresh cloud in its place from the scene's spawn
+/// with the creature's current parameters, so an unattended sweep keeps
+/// producing data. Deaths are counted per creature and reported on exit.
+pub struct RespawnPlugin {
+    pub max_spread: Option<f32>,
+}
+
+#[derive(Resource)]
+struct Respawns {
+    max_spread: Option<f32>,
+    // Creatures that have been within `max_spread`. Only they die of passing
+    // it, so a cloud spawned wider than that isn't respawned over and over.
+    contained: HashSet<Entity>,
+    // Deaths of each scene creature, by its index in the scene.
+    deaths: Vec<u32>,
+}
+
+impl Plugin for RespawnPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Respawns { max_spread: self.max_spread, contained: HashSet::new(), deaths: Vec::new() })
+            .add_systems(Update, respawn_dead_creatures.after(update_creature_stats))
+            .add_systems(Last, report_deaths.after(exit_after_steps));
+    }
+}
+
+fn respawn_dead_creatures(
+    mut commands: Commands,
+    mut respawns: ResMut<Respawns>,
+    scene: Res<Scene>,
+    integrator: Res<Integrator>,
+    step: Res<SimStep>,
+    mut exploded: EventReader<CreatureExploded>,
+    creature_query: Query<(Entity, &Parameters, &CreatureStats, Option<&SceneIndex>), With<Creature>>,
+) {
+    let mut dead: Vec<Entity> = exploded.iter().map(|event| event.creature).collect();
+    if let Some(max_spread) = respawns.max_spread {
+        for (entity, _, stats, _) in creature_query.iter() {
+            if stats.spread <= max_spread {
+                respawns.contained.insert(entity);
+            } else if respawns.contained.remove(&entity) {
+                dead.push(entity);
+            }
+        }
+    }
+    dead.sort();
+    dead.dedup();
+
+    for creature in dead {
+        let Ok((_, parameters, stats, index)) = creature_query.get(creature) else {
+            continue;
+        };
+        let Some(SceneIndex(index)) = index.copied() else {
+            warn!("step {}: {:?} died but wasn't spawned with the scene, so it isn't respawned", step.0, creature);
+            continue;
+        };
+
+        if respawns.deaths.len() <= index {
+            respawns.deaths.resize(index + 1, 0);
+        }
+        respawns.deaths[index] += 1;
+        warn!(step = step.0, creature = index, spread = stats.spread, deaths = respawns.deaths[index], "creature died, respawning it");
+
+        commands.entity(creature).despawn_recursive();
+        let spawn = CreatureSpawn {
+            parameters: parameters.clone(),
+            animation: None,
+            ..scene.creatures[index].clone()
+        };
+        let mut rng = creature_rng(scene.seed, index, respawns.deaths[index]);
+        let respawned = spawn_creature(&mut commands, &mut rng, &spawn, integrator.dt);
+        commands.entity(respawned).insert(SceneIndex(index));
+    }
+}
+
+fn report_deaths(respawns: Res<Respawns>, mut exit: EventReader<AppExit>) {
+    if exit.iter().next().is_none() {
+        return;
+    }
+    let total: u32 = respawns.deaths.iter().sum();
+    let each: Vec<_> = respawns.deaths.iter().enumerate()
+        .filter(|(_, deaths)| **deaths > 0)
+        .map(|(index, deaths)| format!("creature {}: {}", index, deaths))
+        .collect();
+    if each.is_empty() {
+        info!("respawn: no creature died");
+    } else {
+        info!("respawn: deaths {} ({})", total, each.join(", "));
+    }
+}
@@ -0,0 +1,127 @@
+use bevy::{app::AppExit, prelude::*};
+
+use crate::{
+    calculate_fields, clamp_forces, headless::exit_after_steps, update_position, Creature, Fields,
+    Integrator, Parameters, Particle, SimStep, Velocity,
+};
+
+// A step is rejected when the largest force grows more than this many times
+// over, once it is above the floor.
+const FORCE_SPIKE_RATIO: f32 = 10.0;
+const FORCE_SPIKE_FLOOR: f32 = 1.0;
+// ... or when the mean energy per particle rises by more than this in one step,
+// which overdamped descent never does and damped momentum rarely does.
+const ENERGY_JUMP: f32 = 0.05;
+
+/// Watches each step's fields for signs the previous step was too long: fields
+/// that aren't finite, a spike in the largest force, or a jump in energy. The
+/// previous step is then undone and redone with half the time step, up to
+/// `max_reductions` times over the run.
+pub struct SafeModePlugin {
+    pub max_reductions: u32,
+}
+
+#[derive(Resource)]
+struct SafeMode {
+    max_reductions: u32,
+    reductions: u32,
+    gave_up: bool,
+    checkpoint: Option<Checkpoint>,
+}
+
+// Particle states and forces just before the latest move, with the largest
+// force and mean energy they were computed at.
+struct Checkpoint {
+    particles: Vec<(Entity, Vec3, Vec3, Vec3)>,
+    max_force: f32,
+    mean_energy: f32,
+}
+
+impl Plugin for SafeModePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SafeMode {
+                max_reductions: self.max_reductions,
+                reductions: 0,
+                gave_up: false,
+                checkpoint: None,
+            })
+            .add_systems(Update, check_stability.after(calculate_fields).after(clamp_forces).before(update_position))
+            .add_systems(Last, report_reductions.after(exit_after_steps));
+    }
+}
+
+fn check_stability(
+    mut safe_mode: ResMut<SafeMode>,
+    mut integrator: ResMut<Integrator>,
+    mut step: ResMut<SimStep>,
+    creature_query: Query<(&Parameters, &Children), With<Creature>>,
+    mut particle_query: Query<(Entity, &mut Transform, &mut Velocity, &mut Fields), With<Particle>>,
+) {
+    let mut finite = true;
+    let mut max_force: f32 = 0.0;
+    let mut energy = 0.0;
+    let mut count = 0;
+    for (parameters, children) in creature_query.iter() {
+        for (_, transform, velocity, fields) in particle_query.iter_many(children) {
+            finite &= transform.translation.is_finite() && velocity.0.is_finite()
+                && fields.R_val.is_finite() && fields.U_val.is_finite() && fields.E_grad.is_finite();
+            max_force = max_force.max(fields.E_grad.length());
+            energy += fields.R_val - parameters.growth_field(fields.U_val).0;
+            count += 1;
+        }
+    }
+    let mean_energy = energy / count.max(1) as f32;
+
+    // Spawns and removals change the energy too, so only compare like for like.
+    let comparable = safe_mode.checkpoint.as_ref().filter(|checkpoint| checkpoint.particles.len() == count);
+    let reason = comparable.and_then(|checkpoint| {
+        if !finite {
+            Some("fields aren't finite".to_string())
+        } else if max_force > FORCE_SPIKE_FLOOR && max_force > FORCE_SPIKE_RATIO * checkpoint.max_force {
+            Some(format!("max |E_grad| spiked from {:.4} to {:.4}", checkpoint.max_force, max_force))
+        } else if mean_energy - checkpoint.mean_energy > ENERGY_JUMP {
+            Some(format!("mean energy jumped from {:.4} to {:.4}", checkpoint.mean_energy, mean_energy))
+        } else {
+            None
+        }
+    });
+
+    if let Some(reason) = reason {
+        if safe_mode.reductions < safe_mode.max_reductions {
+            // Undo the previous move and let update_position redo it from the
+            // same forces with half the step.
+            let checkpoint = safe_mode.checkpoint.take().unwrap();
+            for (entity, translation, velocity, E_grad) in checkpoint.particles.iter() {
+                if let Ok((_, mut transform, mut particle_velocity, mut fields)) = particle_query.get_mut(*entity) {
+                    transform.translation = *translation;
+                    particle_velocity.0 = *velocity;
+                    fields.E_grad = *E_grad;
+                }
+            }
+            step.0 = step.0.saturating_sub(1);
+            integrator.dt *= 0.5;
+            safe_mode.reductions += 1;
+            warn!(step = step.0, dt = integrator.dt, "{}, retrying with a smaller dt", reason);
+            safe_mode.checkpoint = Some(checkpoint);
+            return;
+        }
+        if !safe_mode.gave_up {
+            error!(step = step.0, reductions = safe_mode.reductions, "{}, but dt was already reduced too many times; no longer retrying", reason);
+            safe_mode.gave_up = true;
+        }
+    }
+
+    safe_mode.checkpoint = Some(Checkpoint {
+        particles: particle_query.iter()
+            .map(|(entity, transform, velocity, fields)| (entity, transform.translation, velocity.0, fields.E_grad))
+            .collect(),
+        max_force,
+        mean_energy,
+    });
+}
+
+fn report_reductions(safe_mode: Res<SafeMode>, integrator: Res<Integrator>, mut exit: EventReader<AppExit>) {
+    if exit.iter().next().is_some() {
+        info!("safe mode: dt reduced {} times, ending at {}", safe_mode.reductions, integrator.dt);
+    }
+}
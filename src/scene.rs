@@ -0,0 +1,266 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use clap::ValueEnum;
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use crate::{density::Density, presets::{AnimateParams, OscillateParam}, Parameters};
+
+pub const DEMO_SEED: u64 = 0x5eed;
+
+/// Distance of each `--compare` creature from the origin, far enough apart that
+/// neither feels the other even with inter-creature fields on.
+pub const COMPARE_OFFSET: f32 = 40.0;
+
+/// Radius of the default disk of particles.
+pub const DISK_RADIUS: f32 = 10.0;
+
+/// Lattice spacing giving roughly the particle density of the default disk.
+pub const LATTICE_SPACING: f32 = 1.25;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Demo {
+    /// Six creatures arranged evenly on a circle
+    Ring,
+    /// Two creatures side by side
+    Pair,
+    /// A 3x3 lattice of creatures
+    Grid,
+    /// Two creatures launched at each other in momentum mode
+    Collide,
+}
+
+#[derive(Clone, Debug)]
+pub enum SpawnPattern {
+    /// Particles sampled uniformly from a disk
+    Disk { count: usize, radius: f32 },
+    /// Particles on a square lattice centred on the creature origin
+    Grid { count: usize, spacing: f32 },
+    /// Particles at exact offsets from the creature origin
+    Explicit(Vec<Vec3>),
+    /// Particles of `particle_mass` each sampled uniformly from a disk, as
+    /// many as make up `total_mass`. The mass is carried for drawing and
+    /// export; the field sums weight every particle the same, so only the
+    /// resulting count affects the dynamics
+    ByMass { total_mass: f32, particle_mass: f32, radius: f32 },
+    /// `base_count` particles sampled uniformly from one `1 / fold` wedge of
+    /// the default disk, and copies of them turned into each of the others,
+    /// so the creature starts with exact `fold`-fold rotational symmetry
+    RotationalSymmetry { fold: u32, base_count: usize },
+    /// Particles rejection-sampled from a disk with probability proportional
+    /// to `density`
+    Density { count: usize, radius: f32, density: Density },
+}
+
+impl SpawnPattern {
+    pub fn count(&self) -> usize {
+        match self {
+            SpawnPattern::Disk { count, .. } | SpawnPattern::Grid { count, .. } | SpawnPattern::Density { count, .. } => *count,
+            SpawnPattern::Explicit(positions) => positions.len(),
+            SpawnPattern::ByMass { total_mass, particle_mass, .. } => (total_mass / particle_mass).round() as usize,
+            SpawnPattern::RotationalSymmetry { fold, base_count } => *fold as usize * base_count,
+        }
+    }
+
+    pub fn truncate(&mut self, max: usize) {
+        match self {
+            SpawnPattern::Disk { count, .. } | SpawnPattern::Grid { count, .. } | SpawnPattern::Density { count, .. } => *count = (*count).min(max),
+            SpawnPattern::Explicit(positions) => positions.truncate(max),
+            SpawnPattern::ByMass { total_mass, particle_mass, .. } => *total_mass = total_mass.min(max as f32 * *particle_mass),
+            SpawnPattern::RotationalSymmetry { fold, base_count } => *base_count = (*base_count).min(max / *fold as usize),
+        }
+    }
+
+    /// Mass of each particle spawned.
+    pub fn particle_mass(&self) -> f32 {
+        match self {
+            SpawnPattern::ByMass { particle_mass, .. } => *particle_mass,
+            _ => 1.0,
+        }
+    }
+
+    /// Positional noise used when a spawn doesn't set its own jitter.
+    pub fn default_jitter(&self) -> f32 {
+        match self {
+            SpawnPattern::Disk { .. } | SpawnPattern::ByMass { .. } => 0.01,
+            SpawnPattern::Grid { .. } | SpawnPattern::Explicit(_) | SpawnPattern::RotationalSymmetry { .. } | SpawnPattern::Density { .. } => 0.0,
+        }
+    }
+}
+
+impl Default for SpawnPattern {
+    fn default() -> Self {
+        SpawnPattern::Disk { count: 199, radius: DISK_RADIUS }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CreatureSpawn {
+    pub position: Vec3,
+    pub parameters: Parameters,
+    pub pattern: SpawnPattern,
+    /// Half-width of the uniform noise added to each particle's x and y,
+    /// falling back to the pattern's default
+    pub jitter: Option<f32>,
+    pub initial_velocity: Vec3,
+    pub time_scale: f32,
+    pub animation: Option<AnimateParams>,
+    pub oscillation: Option<OscillateParam>,
+    /// Relaxation steps run on the particles before they're spawned
+    pub settle_steps: usize,
+}
+
+impl Default for CreatureSpawn {
+    fn default() -> Self {
+        Self {
+            position: Vec3::ZERO,
+            parameters: Parameters::default(),
+            pattern: SpawnPattern::default(),
+            jitter: None,
+            initial_velocity: Vec3::ZERO,
+            time_scale: 1.0,
+            animation: None,
+            oscillation: None,
+            settle_steps: 0,
+        }
+    }
+}
+
+#[derive(Resource, Clone)]
+pub struct Scene {
+    pub seed: u64,
+    pub camera_scale: f32,
+    pub inter_creature: bool,
+    /// Momentum-mode damping the scene is designed for, if any
+    pub damping: Option<f32>,
+    pub creatures: Vec<CreatureSpawn>,
+}
+
+impl Scene {
+    pub fn single(seed: u64) -> Self {
+        Self {
+            seed,
+            camera_scale: 1.0 / 24.0,
+            inter_creature: false,
+            damping: None,
+            creatures: vec![CreatureSpawn::default()],
+        }
+    }
+
+    /// A square grid of `count` identical creatures of `particles` particles
+    /// each, used as a fixed benchmarking workload.
+    pub fn stress(count: usize, particles: usize, seed: u64) -> Self {
+        let spacing = 28.0;
+        let side = (count as f32).sqrt().ceil() as usize;
+        let offset = 0.5 * (side.max(1) - 1) as f32;
+        let creatures = (0..count)
+            .map(|i| CreatureSpawn {
+                position: Vec3::new((i % side) as f32 - offset, (i / side) as f32 - offset, 0.0) * spacing,
+                pattern: SpawnPattern::Disk { count: particles, radius: 10.0 },
+                ..default()
+            })
+            .collect();
+
+        Self {
+            seed,
+            camera_scale: 1.0 / 8.0,
+            inter_creature: true,
+            damping: None,
+            creatures,
+        }
+    }
+
+    /// The same particle cloud twice, side by side, under `left` and `right`.
+    pub fn compare(left: Parameters, right: Parameters, seed: u64) -> Self {
+        // Sampled once here rather than per creature while spawning, so both
+        // start from exactly the same positions, drawn like the default disk.
+        let mut rng = SmallRng::seed_from_u64(seed);
+        let (count, radius) = (199, 10.0);
+        let positions: Vec<Vec3> = (0..count)
+            .map(|_| {
+                let r = radius * rng.gen::<f32>().sqrt();
+                let theta = rng.gen::<f32>() * TAU;
+                Vec3::new(r * theta.cos(), r * theta.sin(), 0.0)
+            })
+            .collect();
+
+        let creatures = [(-COMPARE_OFFSET, left), (COMPARE_OFFSET, right)]
+            .into_iter()
+            .map(|(x, parameters)| CreatureSpawn {
+                position: Vec3::new(x, 0.0, 0.0),
+                parameters,
+                pattern: SpawnPattern::Explicit(positions.clone()),
+                ..default()
+            })
+            .collect();
+
+        Self {
+            seed,
+            camera_scale: 1.0 / 24.0,
+            inter_creature: false,
+            damping: None,
+            creatures,
+        }
+    }
+
+    pub fn demo(demo: Demo, seed: u64) -> Self {
+        let (camera_scale, creatures): (f32, Vec<CreatureSpawn>) = match demo {
+            Demo::Ring => {
+                let n = 6;
+                let ring_radius = 30.0;
+                let creatures = (0..n)
+                    .map(|i| {
+                        let theta = i as f32 * TAU / n as f32;
+                        CreatureSpawn {
+                            position: Vec3::new(ring_radius * theta.cos(), ring_radius * theta.sin(), 0.0),
+                            pattern: SpawnPattern::Disk { count: 120, radius: 10.0 },
+                            ..default()
+                        }
+                    })
+                    .collect();
+                (1.0 / 8.0, creatures)
+            }
+            Demo::Pair => {
+                let creatures = [-14.0, 14.0]
+                    .into_iter()
+                    .map(|x| CreatureSpawn {
+                        position: Vec3::new(x, 0.0, 0.0),
+                        ..default()
+                    })
+                    .collect();
+                (1.0 / 14.0, creatures)
+            }
+            Demo::Grid => {
+                let spacing = 28.0;
+                let creatures = (-1..=1)
+                    .flat_map(|y| (-1..=1).map(move |x| (x, y)))
+                    .map(|(x, y)| CreatureSpawn {
+                        position: Vec3::new(x as f32 * spacing, y as f32 * spacing, 0.0),
+                        pattern: SpawnPattern::Disk { count: 120, radius: 10.0 },
+                        ..default()
+                    })
+                    .collect();
+                (1.0 / 8.0, creatures)
+            }
+            Demo::Collide => {
+                let creatures = [-1.0, 1.0]
+                    .into_iter()
+                    .map(|side| CreatureSpawn {
+                        position: Vec3::new(side * 16.0, side * 2.0, 0.0),
+                        initial_velocity: Vec3::new(-side * 1.0, 0.0, 0.0),
+                        ..default()
+                    })
+                    .collect();
+                (1.0 / 14.0, creatures)
+            }
+        };
+
+        Self {
+            seed,
+            camera_scale,
+            inter_creature: true,
+            damping: (demo == Demo::Collide).then_some(0.2),
+            creatures,
+        }
+    }
+}
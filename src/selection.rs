@@ -0,0 +1,168 @@
+use bevy::prelude::*;
+
+use crate::{
+    events::CreatureMerged,
+    force_panel::ForcePanel,
+    kernel_editor::KernelEditor,
+    measure::Measurement,
+    minimap::{minimap_covers, MinimapCamera},
+    param_map::ParamMap,
+    picking::{nearest_particle, Cursor},
+    perturb::perturb_modifier_pressed,
+    pinning::pin_modifier_pressed,
+    probe_line::ProbeLine,
+    Creature, Particle, TimeScale,
+};
+
+pub const PICK_RADIUS: f32 = 1.5;
+
+/// Selected creatures, in the order they were clicked.
+#[derive(Resource, Default)]
+pub struct Selection(pub Vec<Entity>);
+
+pub struct SelectionPlugin;
+
+impl Plugin for SelectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Selection>()
+            .add_systems(Update, (select_creatures, merge_selected, scale_selected_time, draw_selection).chain());
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn select_creatures(
+    buttons: Res<Input<MouseButton>>,
+    keys: Res<Input<KeyCode>>,
+    cursor: Cursor,
+    editor: Option<Res<KernelEditor>>,
+    param_map: Option<Res<ParamMap>>,
+    measurement: Option<Res<Measurement>>,
+    probe_line: Option<Res<ProbeLine>>,
+    force_panel: Option<Res<ForcePanel>>,
+    mut selection: ResMut<Selection>,
+    minimap_query: Query<&Camera, With<MinimapCamera>>,
+    creature_query: Query<(Entity, &Transform, &Children), With<Creature>>,
+    particle_query: Query<&Transform, With<Particle>>,
+) {
+    // Ctrl-clicks pin particles and Alt-clicks perturb them instead, clicks
+    // while measuring pick its points, drags with the probe line on place it,
+    // and clicks on the kernel editor, the parameter map, the force panel and
+    // the minimap are for them.
+    if !buttons.just_pressed(MouseButton::Left) || pin_modifier_pressed(&keys) || perturb_modifier_pressed(&keys) {
+        return;
+    }
+    if measurement.is_some_and(|measurement| measurement.active) || probe_line.is_some_and(|probe| probe.active) {
+        return;
+    }
+    if editor.is_some_and(|editor| cursor.screen_position().is_some_and(|point| editor.covers(point))) {
+        return;
+    }
+    if param_map.is_some_and(|map| cursor.screen_position().is_some_and(|point| map.covers(point))) {
+        return;
+    }
+    if force_panel.is_some_and(|panel| panel.hovered()) {
+        return;
+    }
+    if cursor.screen_position().is_some_and(|point| minimap_query.iter().any(|camera| minimap_covers(camera, point))) {
+        return;
+    }
+    let Some(point) = cursor.world_position() else {
+        return;
+    };
+
+    let picked = nearest_particle(point, PICK_RADIUS, &creature_query, &particle_query)
+        .map(|(creature, _)| creature);
+
+    let additive = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    match (picked, additive) {
+        (Some(entity), true) => {
+            if let Some(index) = selection.0.iter().position(|selected| *selected == entity) {
+                selection.0.remove(index);
+            } else {
+                selection.0.push(entity);
+            }
+        }
+        (Some(entity), false) => selection.0 = vec![entity],
+        (None, true) => {}
+        (None, false) => selection.0.clear(),
+    }
+}
+
+fn merge_selected(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    mut selection: ResMut<Selection>,
+    mut merged: EventWriter<CreatureMerged>,
+    creature_query: Query<(&Transform, &Children), With<Creature>>,
+    mut particle_query: Query<&mut Transform, (With<Particle>, Without<Creature>)>,
+) {
+    if !keys.just_pressed(KeyCode::M) {
+        return;
+    }
+    let [a, b] = selection.0[..] else {
+        info!("select exactly two creatures to merge");
+        return;
+    };
+    let (Ok((transform_a, _)), Ok((transform_b, children_b))) = (creature_query.get(a), creature_query.get(b)) else {
+        return;
+    };
+
+    let offset = transform_b.translation - transform_a.translation;
+    for child in children_b.iter() {
+        if let Ok(mut transform) = particle_query.get_mut(*child) {
+            transform.translation += offset;
+        }
+    }
+
+    let particles: Vec<Entity> = children_b.iter().copied().collect();
+    commands.entity(a).push_children(&particles);
+    commands.entity(b).despawn();
+    selection.0 = vec![a];
+    merged.send(CreatureMerged { into: a, from: b });
+}
+
+fn scale_selected_time(
+    keys: Res<Input<KeyCode>>,
+    selection: Res<Selection>,
+    mut creature_query: Query<&mut TimeScale, With<Creature>>,
+) {
+    let factor = if keys.just_pressed(KeyCode::BracketLeft) {
+        0.5
+    } else if keys.just_pressed(KeyCode::BracketRight) {
+        2.0
+    } else {
+        return;
+    };
+
+    for entity in selection.0.iter() {
+        if let Ok(mut time_scale) = creature_query.get_mut(*entity) {
+            time_scale.0 *= factor;
+            info!("time scale of {:?}: {}", entity, time_scale.0);
+        }
+    }
+}
+
+fn draw_selection(
+    mut gizmos: Gizmos,
+    selection: Res<Selection>,
+    creature_query: Query<(&Transform, &Children), With<Creature>>,
+    particle_query: Query<&Transform, (With<Particle>, Without<Creature>)>,
+) {
+    for (index, entity) in selection.0.iter().enumerate() {
+        let Ok((creature_transform, children)) = creature_query.get(*entity) else {
+            continue;
+        };
+        let positions: Vec<Vec2> = children.iter()
+            .filter_map(|child| particle_query.get(*child).ok())
+            .map(|transform| (creature_transform.translation + transform.translation).truncate())
+            .collect();
+        if positions.is_empty() {
+            continue;
+        }
+
+        let centroid = positions.iter().sum::<Vec2>() / positions.len() as f32;
+        let radius = positions.iter().map(|x| x.distance(centroid)).fold(0.0, f32::max);
+        let color = if index == 0 { Color::GREEN } else { Color::YELLOW };
+        gizmos.circle_2d(centroid, radius + 1.0, color);
+    }
+}
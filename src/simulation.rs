@@ -0,0 +1,147 @@
+use std::ffi::OsString;
+
+use bevy::{ecs::schedule::ScheduleLabel, prelude::*};
+use clap::Parser;
+
+use crate::{
+    app, calculate_fields, cli::Args, species::InteractionMatrix, stats::CreatureStats, sum_pair_fields,
+    timings::TimingsPlugin, Creature, CreatureBundle, Fields, ForceTerms, InterCreature, InteractionRamp,
+    KernelCutoff, Mass, MetricScale, Parameters, Particle, ParticleBundle, SelfField, SimStep, StepDiagnostics, TimeScale, Velocity,
+};
+
+/// A headless simulation driven one step at a time by the embedding code.
+pub struct Simulation {
+    app: App,
+}
+
+/// Every particle after a step, creatures in spawn order and each creature's
+/// particles in child order.
+pub struct SimState {
+    pub step: u64,
+    pub particles: Vec<ParticleState>,
+}
+
+pub struct ParticleState {
+    pub creature: Entity,
+    pub position: Vec3,
+    pub fields: Fields,
+}
+
+impl Simulation {
+    /// Builds a simulation from the same options the binary takes, e.g.
+    /// `["--demo", "pair", "--seed", "3"]`. It always runs headless, and
+    /// `--steps` has no effect since the caller decides when to stop.
+    pub fn from_args<I, T>(args: I) -> Result<Self, clap::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let args = std::iter::once(OsString::from("particle-lenia"))
+            .chain(args.into_iter().map(Into::into))
+            .chain(std::iter::once(OsString::from("--headless")));
+        Ok(Simulation::new(&Args::try_parse_from(args)?))
+    }
+
+    pub(crate) fn new(args: &Args) -> Self {
+        let mut app = app(args);
+
+        // What `App::run` does before handing over to the runner.
+        while !app.ready() {
+            bevy::tasks::tick_global_task_pools_on_main_thread();
+        }
+        app.finish();
+        app.cleanup();
+
+        Simulation { app }
+    }
+
+    /// Runs the startup systems, spawning the scene, without taking a step,
+    /// so the state can be looked at before the first. Otherwise the first
+    /// step runs them.
+    pub fn start(&mut self) {
+        let labels: [Box<dyn ScheduleLabel>; 3] = [Box::new(PreStartup), Box::new(Startup), Box::new(PostStartup)];
+        for label in labels {
+            let _ = self.app.world.try_run_schedule(label.as_ref());
+            // So the first step doesn't run them again.
+            self.app.world.resource_mut::<Schedules>().remove(label.as_ref());
+        }
+    }
+
+    pub(crate) fn world_mut(&mut self) -> &mut World {
+        &mut self.app.world
+    }
+
+    pub fn step(&mut self) {
+        self.app.update();
+    }
+
+    /// Advances one step, then passes the state after it to `f`.
+    pub fn step_with_callback(&mut self, mut f: impl FnMut(&SimState)) {
+        self.step();
+        f(&self.state());
+    }
+
+    pub fn state(&mut self) -> SimState {
+        let world = &mut self.app.world;
+        let mut creature_query = world.query_filtered::<(Entity, &Transform, &Children), With<Creature>>();
+        let mut particle_query = world.query_filtered::<(&Transform, &Fields), With<Particle>>();
+
+        let mut creatures: Vec<_> = creature_query.iter(world).collect();
+        creatures.sort_by_key(|(entity, ..)| *entity);
+        let mut particles = Vec::new();
+        for (creature, creature_transform, children) in creatures {
+            particles.extend(particle_query.iter_many(world, children).map(|(transform, fields)| ParticleState {
+                creature,
+                position: creature_transform.translation + transform.translation,
+                fields: *fields,
+            }));
+        }
+
+        SimState {
+            step: world.resource::<SimStep>().0,
+            particles,
+        }
+    }
+}
+
+/// Fields of a lone creature of particles at `positions`, from a single pass
+/// of the field systems with default settings. Meant for checking the field
+/// computation against values worked out by hand.
+pub fn compute_fields(parameters: &Parameters, positions: &[Vec3]) -> Vec<Fields> {
+    let mut app = App::new();
+    app.add_plugins(TimingsPlugin)
+        .init_resource::<InterCreature>()
+        .init_resource::<MetricScale>()
+        .init_resource::<KernelCutoff>()
+        .init_resource::<SelfField>()
+        .init_resource::<InteractionMatrix>()
+        .init_resource::<ForceTerms>()
+        .init_resource::<InteractionRamp>()
+        .init_resource::<SimStep>()
+        .init_resource::<StepDiagnostics>()
+        .add_systems(Update, (sum_pair_fields, calculate_fields).chain());
+
+    let mut particles = Vec::with_capacity(positions.len());
+    app.world
+        .spawn(CreatureBundle {
+            spatial: SpatialBundle::default(),
+            parameters: parameters.clone(),
+            time_scale: TimeScale::default(),
+            stats: CreatureStats::default(),
+            creature: Creature,
+        })
+        .with_children(|parent| {
+            for position in positions {
+                particles.push(parent.spawn(ParticleBundle {
+                    spatial: SpatialBundle::from_transform(Transform::from_translation(*position)),
+                    fields: Fields::default(),
+                    mass: Mass::default(),
+                    velocity: Velocity::default(),
+                    particle: Particle,
+                }).id());
+            }
+        });
+    app.update();
+
+    particles.into_iter().map(|entity| *app.world.get::<Fields>(entity).unwrap()).collect()
+}
@@ -0,0 +1,52 @@
+use bevy::prelude::*;
+
+use crate::{
+    presets::AnimateParams,
+    stats::{update_creature_stats, CreatureStats},
+    Creature, Parameters, SimStep,
+};
+
+// Range mu_g is held in, and the most it moves in a step however far the
+// spread is from the target, so the loop can't swing past it.
+const MU_G_RANGE: (f32, f32) = (0.05, 2.0);
+const MAX_ADJUSTMENT: f32 = 1e-3;
+
+/// Proportional control of each creature's size through its mu_g: every step,
+/// mu_g moves by `gain` times the relative error of its spread, up when the
+/// creature is larger than `target_spread` since a higher target density packs
+/// it tighter. Creatures being morphed between presets are left alone.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct SizeController {
+    pub target_spread: f32,
+    pub gain: f32,
+    pub enabled: bool,
+}
+
+pub struct SizeControllerPlugin {
+    pub controller: SizeController,
+}
+
+impl Plugin for SizeControllerPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.controller)
+            .add_systems(Update, control_size.after(update_creature_stats).run_if(resource_changed::<SimStep>()));
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn control_size(
+    controller: Res<SizeController>,
+    mut creature_query: Query<(&mut Parameters, &CreatureStats), (With<Creature>, Without<AnimateParams>)>,
+) {
+    if !controller.enabled {
+        return;
+    }
+    for (mut parameters, stats) in creature_query.iter_mut() {
+        if !stats.spread.is_finite() {
+            continue;
+        }
+        let error = (stats.spread - controller.target_spread) / controller.target_spread;
+        let adjustment = (controller.gain * error).clamp(-MAX_ADJUSTMENT, MAX_ADJUSTMENT);
+        parameters.mu_g = (parameters.mu_g + adjustment).clamp(MU_G_RANGE.0, MU_G_RANGE.1);
+    }
+}
@@ -0,0 +1,118 @@
+use std::{fs, io::Write, path::PathBuf};
+
+use bevy::{app::AppExit, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    headless::exit_after_steps,
+    output::{create_file, OutputName},
+    Creature, Particle, Scene, SimStep,
+};
+
+/// World positions of every particle at the end of a headless run, creatures
+/// in spawn order and each creature's particles in child order.
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    step: u64,
+    creatures: Vec<Vec<[f32; 3]>>,
+}
+
+#[derive(Resource, Clone)]
+pub enum SnapshotMode {
+    Write(PathBuf),
+    /// Fail unless every position is within `tolerance` of the stored one
+    Check { path: PathBuf, tolerance: f32 },
+}
+
+pub struct SnapshotPlugin {
+    pub mode: SnapshotMode,
+}
+
+impl Plugin for SnapshotPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.mode.clone())
+            .add_systems(Last, take_snapshot.after(exit_after_steps));
+    }
+}
+
+fn take_snapshot(
+    mode: Res<SnapshotMode>,
+    scene: Res<Scene>,
+    step: Res<SimStep>,
+    mut exit: EventReader<AppExit>,
+    creature_query: Query<(Entity, &Transform, &Children), With<Creature>>,
+    particle_query: Query<&Transform, With<Particle>>,
+) {
+    if exit.iter().next().is_none() {
+        return;
+    }
+
+    let mut creatures: Vec<_> = creature_query.iter().collect();
+    creatures.sort_by_key(|(entity, ..)| *entity);
+    let snapshot = Snapshot {
+        step: step.0,
+        creatures: creatures.into_iter()
+            .map(|(_, creature_transform, children)| {
+                particle_query.iter_many(children)
+                    .map(|transform| (creature_transform.translation + transform.translation).to_array())
+                    .collect()
+            })
+            .collect(),
+    };
+
+    match &*mode {
+        SnapshotMode::Write(path) => {
+            let json = serde_json::to_string_pretty(&snapshot).unwrap();
+            let name = OutputName { step: snapshot.step, seed: scene.seed, creature: None };
+            let path = match create_file(path, &name).and_then(|(path, mut file)| file.write_all((json + "\n").as_bytes()).map(|_| path)) {
+                Ok(path) => path,
+                Err(err) => {
+                    eprintln!("failed to write {}: {}", path.display(), err);
+                    std::process::exit(1);
+                }
+            };
+            info!("wrote snapshot of step {} to {}", snapshot.step, path.display());
+        }
+        SnapshotMode::Check { path, tolerance } => {
+            let golden: Snapshot = match fs::read_to_string(path).map_err(|err| err.to_string())
+                .and_then(|json| serde_json::from_str(&json).map_err(|err| err.to_string()))
+            {
+                Ok(golden) => golden,
+                Err(err) => {
+                    eprintln!("failed to read {}: {}", path.display(), err);
+                    std::process::exit(1);
+                }
+            };
+            if let Err(mismatch) = compare(&golden, &snapshot, *tolerance) {
+                eprintln!("snapshot {} doesn't match: {}", path.display(), mismatch);
+                std::process::exit(1);
+            }
+            info!("snapshot of step {} matches {}", snapshot.step, path.display());
+        }
+    }
+}
+
+fn compare(golden: &Snapshot, snapshot: &Snapshot, tolerance: f32) -> Result<(), String> {
+    if golden.step != snapshot.step {
+        return Err(format!("stored at step {}, ran to step {}", golden.step, snapshot.step));
+    }
+    if golden.creatures.len() != snapshot.creatures.len() {
+        return Err(format!("{} creatures stored, {} simulated", golden.creatures.len(), snapshot.creatures.len()));
+    }
+
+    for (creature, (expected, actual)) in golden.creatures.iter().zip(&snapshot.creatures).enumerate() {
+        if expected.len() != actual.len() {
+            return Err(format!("creature {}: {} particles stored, {} simulated", creature, expected.len(), actual.len()));
+        }
+        for (particle, (expected, actual)) in expected.iter().zip(actual).enumerate() {
+            let deviation = Vec3::from_array(*expected).distance(Vec3::from_array(*actual));
+            if deviation > tolerance {
+                return Err(format!(
+                    "creature {} particle {} at {:?}, stored {:?} ({} apart, tolerance {})",
+                    creature, particle, actual, expected, deviation, tolerance,
+                ));
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,169 @@
+use bevy::{prelude::*, utils::HashSet};
+
+use crate::{
+    picking::Cursor,
+    scene::{CreatureSpawn, SpawnPattern},
+    selection::Selection,
+    spawn_creature, Creature, Fields, Integrator, Parameters, Particle, SimRng,
+};
+
+#[derive(Event)]
+pub struct SpawnCreature(pub CreatureSpawn);
+
+/// Upper bound on the total particle count across all creatures, if any.
+#[derive(Resource, Default)]
+pub struct ParticleBudget(pub Option<usize>);
+
+/// Relaxation steps given to creatures spawned from a random cloud.
+#[derive(Resource, Default)]
+pub struct SettleSteps(pub usize);
+
+pub struct SpawnerPlugin;
+
+impl Plugin for SpawnerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SpawnCreature>()
+            .init_resource::<ParticleBudget>()
+            .init_resource::<SettleSteps>()
+            .add_systems(Update, handle_spawn_requests);
+    }
+}
+
+pub fn spawn_from_input(
+    keys: Res<Input<KeyCode>>,
+    cursor: Cursor,
+    selection: Res<Selection>,
+    settle_steps: Res<SettleSteps>,
+    creature_query: Query<(&Parameters, &Children), With<Creature>>,
+    particle_query: Query<&Transform, With<Particle>>,
+    mut spawn_events: EventWriter<SpawnCreature>,
+) {
+    let Some(point) = cursor.world_position() else {
+        return;
+    };
+    let position = point.extend(0.0);
+
+    if keys.just_pressed(KeyCode::N) {
+        spawn_events.send(SpawnCreature(CreatureSpawn {
+            position,
+            settle_steps: settle_steps.0,
+            ..default()
+        }));
+    }
+
+    if keys.just_pressed(KeyCode::C) {
+        let Some((parameters, children)) = selection.0.first().and_then(|entity| creature_query.get(*entity).ok()) else {
+            info!("select a creature to clone");
+            return;
+        };
+
+        let offsets: Vec<Vec3> = children.iter()
+            .filter_map(|child| particle_query.get(*child).ok())
+            .map(|transform| transform.translation)
+            .collect();
+        if offsets.is_empty() {
+            return;
+        }
+        let centroid = offsets.iter().sum::<Vec3>() / offsets.len() as f32;
+
+        spawn_events.send(SpawnCreature(CreatureSpawn {
+            position,
+            parameters: parameters.clone(),
+            pattern: SpawnPattern::Explicit(offsets.iter().map(|x| *x - centroid).collect()),
+            ..default()
+        }));
+    }
+}
+
+// Several spawns can arrive in one frame, before any of their particles or
+// the removals that made room for them show up in the queries, so the total
+// is kept as it would be after each.
+fn handle_spawn_requests(
+    mut commands: Commands,
+    mut spawn_events: EventReader<SpawnCreature>,
+    budget: Res<ParticleBudget>,
+    integrator: Res<Integrator>,
+    mut rng: ResMut<SimRng>,
+    creature_query: Query<&Children, With<Creature>>,
+    particle_query: Query<&Fields, With<Particle>>,
+) {
+    let mut total: usize = creature_query.iter().map(|children| children.len()).sum();
+    let mut removed = HashSet::new();
+    for SpawnCreature(spawn) in spawn_events.iter() {
+        let mut spawn = spawn.clone();
+
+        if let Some(budget) = budget.0 {
+            if spawn.pattern.count() > budget {
+                warn!("spawn of {} particles exceeds the budget of {}, truncating", spawn.pattern.count(), budget);
+                spawn.pattern.truncate(budget);
+            }
+            let excess = (total + spawn.pattern.count()).saturating_sub(budget);
+            total -= make_room(&mut commands, budget, excess, &mut removed, &creature_query, &particle_query);
+            // What earlier spawns this frame take can't be removed yet.
+            let room = budget.saturating_sub(total);
+            if room == 0 {
+                warn!("no room left within the budget of {} for another creature this frame", budget);
+                continue;
+            }
+            if spawn.pattern.count() > room {
+                warn!("only room for {} of {} particles within the budget of {}, truncating", room, spawn.pattern.count(), budget);
+                spawn.pattern.truncate(room);
+            }
+        }
+
+        spawn_creature(&mut commands, &mut rng.0, &spawn, integrator.dt);
+        total += spawn.pattern.count();
+    }
+}
+
+// Despawns `excess` of the least dense particles of existing creatures that
+// aren't already in `removed`, in proportion to each creature's size, and
+// returns how many it despawned.
+fn make_room(
+    commands: &mut Commands,
+    budget: usize,
+    excess: usize,
+    removed: &mut HashSet<Entity>,
+    creature_query: &Query<&Children, With<Creature>>,
+    particle_query: &Query<&Fields, With<Particle>>,
+) -> usize {
+    let sizes: Vec<(&Children, usize)> = creature_query.iter()
+        .map(|children| (children, children.iter().filter(|child| !removed.contains(*child)).count()))
+        .collect();
+    let total: usize = sizes.iter().map(|(_, size)| size).sum();
+    let excess = excess.min(total);
+    if excess == 0 {
+        return 0;
+    }
+
+    let mut quotas: Vec<(&Children, usize, usize)> = sizes.into_iter()
+        .map(|(children, size)| (children, size, excess * size / total))
+        .collect();
+    let mut remaining = excess - quotas.iter().map(|(_, _, quota)| quota).sum::<usize>();
+    quotas.sort_by_key(|(_, size, _)| std::cmp::Reverse(*size));
+    for (_, size, quota) in quotas.iter_mut() {
+        if remaining == 0 {
+            break;
+        }
+        if *quota < *size {
+            *quota += 1;
+            remaining -= 1;
+        }
+    }
+
+    for (children, _, quota) in quotas {
+        let mut by_density: Vec<(Entity, f32)> = children.iter()
+            .filter(|child| !removed.contains(*child))
+            .filter_map(|child| particle_query.get(*child).ok().map(|fields| (*child, fields.U_val)))
+            .collect();
+        by_density.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        for (entity, _) in by_density.into_iter().take(quota) {
+            commands.entity(entity).despawn_recursive();
+            removed.insert(entity);
+        }
+    }
+
+    info!("removed {} particles to stay within the budget of {}", excess, budget);
+    excess
+}
@@ -0,0 +1,181 @@
+use std::{fs, path::Path};
+
+use bevy::{prelude::*, render::view::RenderLayers, transform::TransformSystem};
+use serde::{Deserialize, Serialize};
+
+use crate::{minimap::MinimapCamera, Parameters, Particle};
+
+/// Which row and column of the [`InteractionMatrix`] a particle uses.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Species(pub u8);
+
+/// Interaction kernel one species feels from another.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct KernelParams {
+    pub mu_k: f32,
+    pub sigma_k: f32,
+    pub w_k: f32,
+}
+
+/// Kernel parameters for every ordered pair of species: row `i`, column `j`
+/// is how a particle of species `i` feels one of species `j`, so the matrix
+/// needn't be symmetric. Without a matrix there's a single species and each
+/// creature's own parameters are used.
+#[derive(Resource, Default, Serialize, Deserialize)]
+pub struct InteractionMatrix {
+    pub kernels: Vec<Vec<KernelParams>>,
+}
+
+impl InteractionMatrix {
+    /// Reads a square matrix of kernels from JSON, e.g.
+    /// `{"kernels": [[{"mu_k": 4.0, "sigma_k": 1.0, "w_k": 0.022}]]}`.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = fs::read_to_string(path).map_err(|err| err.to_string())?;
+        let matrix: Self = serde_json::from_str(&json).map_err(|err| err.to_string())?;
+        let n = matrix.kernels.len();
+        if n == 0 || n > u8::MAX as usize + 1 || matrix.kernels.iter().any(|row| row.len() != n) {
+            return Err(format!("expected a square matrix of 1 to 256 species, got {} rows", n));
+        }
+        // Same invariants as the creature's own kernel parameters.
+        for (i, row) in matrix.kernels.iter().enumerate() {
+            for (j, kernel) in row.iter().enumerate() {
+                let parameters = Parameters { mu_k: kernel.mu_k, sigma_k: kernel.sigma_k, w_k: kernel.w_k, ..default() };
+                parameters.validate().map_err(|err| format!("kernel {}, {}: {}", i, j, err))?;
+            }
+        }
+        Ok(matrix)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.kernels.is_empty()
+    }
+
+    pub fn species(&self) -> usize {
+        self.kernels.len().max(1)
+    }
+
+    /// Kernel value and derivative at `r` felt by species `i` from species `j`,
+    /// in the creature's kernel shape.
+    pub fn kernel_field(&self, parameters: &Parameters, i: Species, j: Species, r: f32) -> (f32, f32) {
+        match self.kernels.get(i.0 as usize).and_then(|row| row.get(j.0 as usize)) {
+            Some(kernel) => parameters.kernel.field(r, kernel.mu_k, kernel.sigma_k, kernel.w_k),
+            None => parameters.kernel_field(r),
+        }
+    }
+}
+
+pub struct SpeciesPlugin;
+
+impl Plugin for SpeciesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreUpdate, assign_species);
+    }
+}
+
+// New particles that weren't given a species take turns through them, so
+// every creature mixes all species evenly.
+fn assign_species(
+    mut commands: Commands,
+    matrix: Res<InteractionMatrix>,
+    mut next: Local<usize>,
+    particle_query: Query<Entity, (Added<Particle>, Without<Species>)>,
+) {
+    for entity in particle_query.iter() {
+        commands.entity(entity).insert(Species((*next % matrix.species()) as u8));
+        *next += 1;
+    }
+}
+
+// Layer 0 is left to everything else, and species past the last layer share it.
+const FIRST_SPECIES_LAYER: u8 = 1;
+// Depth between neighboring species in the draw order, more than the span
+// the z-order modes use.
+const SPECIES_DEPTH: f32 = 1.0;
+
+/// Draws each species on its own render layer, toggled with the number keys,
+/// and stacks species in a draw order, with Shift and a number key bringing
+/// that species to the top. Both only change what the cameras draw.
+pub struct SpeciesLayersPlugin;
+
+/// Species from bottom to top of the draw order.
+#[derive(Resource)]
+struct SpeciesOrder(Vec<u8>);
+
+impl Plugin for SpeciesLayersPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, init_species_order)
+            .add_systems(Update, (attach_layers, toggle_species).chain())
+            .add_systems(PostUpdate, apply_species_order.after(TransformSystem::TransformPropagate));
+    }
+}
+
+fn species_layer(species: Species) -> u8 {
+    (FIRST_SPECIES_LAYER as usize + species.0 as usize).min(RenderLayers::TOTAL_LAYERS - 1) as u8
+}
+
+fn init_species_order(mut commands: Commands, matrix: Res<InteractionMatrix>) {
+    commands.insert_resource(SpeciesOrder((0..matrix.species()).map(|species| species as u8).collect()));
+}
+
+// Cameras start out drawing every layer.
+fn attach_layers(
+    mut commands: Commands,
+    particle_query: Query<(Entity, &Species), Without<RenderLayers>>,
+    camera_query: Query<Entity, (With<Camera>, Without<RenderLayers>)>,
+) {
+    for (entity, species) in particle_query.iter() {
+        commands.entity(entity).insert(RenderLayers::layer(species_layer(*species)));
+    }
+    for entity in camera_query.iter() {
+        commands.entity(entity).insert(RenderLayers::all());
+    }
+}
+
+const SPECIES_KEYS: [KeyCode; 9] = [
+    KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4, KeyCode::Key5,
+    KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9,
+];
+
+fn toggle_species(
+    keys: Res<Input<KeyCode>>,
+    matrix: Res<InteractionMatrix>,
+    mut order: ResMut<SpeciesOrder>,
+    mut camera_query: Query<&mut RenderLayers, (With<Camera>, Without<MinimapCamera>)>,
+) {
+    let raise = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    for (index, key) in SPECIES_KEYS.iter().enumerate() {
+        if !keys.just_pressed(*key) || index >= matrix.species() {
+            continue;
+        }
+        let species = Species(index as u8);
+
+        if raise {
+            order.0.retain(|other| *other != species.0);
+            order.0.push(species.0);
+            info!("species {} drawn on top", species.0);
+            continue;
+        }
+
+        let layer = species_layer(species);
+        for mut layers in camera_query.iter_mut() {
+            *layers = if layers.intersects(&RenderLayers::layer(layer)) {
+                layers.without(layer)
+            } else {
+                layers.with(layer)
+            };
+        }
+        info!("toggled species {}", species.0);
+    }
+}
+
+fn apply_species_order(
+    order: Res<SpeciesOrder>,
+    mut particle_query: Query<(&Species, &mut GlobalTransform), With<Particle>>,
+) {
+    for (species, mut global_transform) in particle_query.iter_mut() {
+        let rank = order.0.iter().position(|other| *other == species.0).unwrap_or(0);
+        let mut affine = global_transform.affine();
+        affine.translation.z += rank as f32 * SPECIES_DEPTH;
+        *global_transform = affine.into();
+    }
+}
@@ -0,0 +1,246 @@
+use std::{fs, path::Path};
+
+use bevy::{ecs::query::Has, prelude::*};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    emitter::{Emission, Emitter}, lifecycle::Starving, lifespan::Age, pinning::Pinned, presets::{AnimateParams, OscillateParam},
+    scene::Scene, species::Species, stats::CreatureStats, Creature, CreatureBundle, Dynamics, Fields, GrowthOverride,
+    Integrator, InterCreature, Mass, Parameters, Particle, ParticleBundle, SimRng, SimStep, TimeScale, Velocity,
+};
+
+/// Everything needed to pick a run back up at the step it was saved, written
+/// as RON, along with the RNG new spawns draw from so they come out as they
+/// would have. Fields are left out since the next step recomputes them from
+/// the positions.
+#[derive(Resource, Serialize, Deserialize)]
+pub struct SavedState {
+    pub seed: u64,
+    pub step: u64,
+    pub camera_scale: f32,
+    pub inter_creature: bool,
+    /// Momentum-mode damping, or none for overdamped dynamics
+    pub damping: Option<f32>,
+    creatures: Vec<SavedCreature>,
+    #[serde(default)]
+    rng: Option<SavedRng>,
+    #[serde(default)]
+    emitters: Vec<SavedEmitter>,
+}
+
+// Where the RNG is in its stream; states saved before it was kept have none.
+#[derive(Serialize, Deserialize)]
+struct SavedRng {
+    seed: [u8; 32],
+    stream: u64,
+    // The generator counts in 68 bits, but no run gets near 2^64 words.
+    word_pos: u64,
+}
+
+// An emitter and how far along it is, feeding the creature at that index.
+#[derive(Serialize, Deserialize)]
+struct SavedEmitter {
+    emitter: Emitter,
+    creature: Option<usize>,
+    owed: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedCreature {
+    position: Vec3,
+    parameters: Parameters,
+    time_scale: f32,
+    animation: Option<AnimateParams>,
+    #[serde(default)]
+    oscillation: Option<OscillateParam>,
+    particles: Vec<SavedParticle>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedParticle {
+    position: Vec3,
+    velocity: Vec3,
+    mass: f32,
+    growth: Option<GrowthOverride>,
+    #[serde(default)]
+    species: Option<Species>,
+    #[serde(default)]
+    pinned: bool,
+    #[serde(default)]
+    age: Option<u32>,
+    #[serde(default)]
+    starving: Option<u32>,
+}
+
+impl SavedState {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let ron = fs::read_to_string(path).map_err(|err| err.to_string())?;
+        let state: Self = ron::from_str(&ron).map_err(|err| err.to_string())?;
+        for (index, creature) in state.creatures.iter().enumerate() {
+            creature.parameters.validate().map_err(|err| format!("creature {}: {}", index, err))?;
+        }
+        Ok(state)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let ron = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).map_err(|err| err.to_string())?;
+        fs::write(path, ron).map_err(|err| err.to_string())
+    }
+
+    /// Each creature's parameters, in spawn order.
+    pub fn parameters(&self) -> impl Iterator<Item = &Parameters> {
+        self.creatures.iter().map(|creature| &creature.parameters)
+    }
+
+    /// Whether emitters were saved, which then stand in for --emitter's.
+    pub fn has_emitters(&self) -> bool {
+        !self.emitters.is_empty()
+    }
+
+    /// The RNG as it was when the state was saved.
+    pub fn rng(&self) -> Option<ChaCha8Rng> {
+        self.rng.as_ref().map(|saved| {
+            let mut rng = ChaCha8Rng::from_seed(saved.seed);
+            rng.set_stream(saved.stream);
+            rng.set_word_pos(saved.word_pos as u128);
+            rng
+        })
+    }
+
+    /// The scene to build the app around; its creatures come from [`spawn_saved_state`].
+    pub fn scene(&self) -> Scene {
+        Scene {
+            seed: self.seed,
+            camera_scale: self.camera_scale,
+            inter_creature: self.inter_creature,
+            damping: self.damping,
+            creatures: Vec::new(),
+        }
+    }
+}
+
+/// Saves the current frame, creatures in spawn order and each creature's
+/// particles in child order so the field sums run in the same order on reload.
+#[allow(clippy::type_complexity)]
+#[derive(bevy::ecs::system::SystemParam)]
+pub struct StateCapture<'w, 's> {
+    step: Res<'w, SimStep>,
+    scene: Res<'w, Scene>,
+    inter_creature: Res<'w, InterCreature>,
+    integrator: Res<'w, Integrator>,
+    rng: Option<Res<'w, SimRng>>,
+    creature_query: Query<'w, 's, (Entity, &'static Transform, &'static Parameters, &'static TimeScale, Option<&'static AnimateParams>, Option<&'static OscillateParam>, &'static Children), With<Creature>>,
+    particle_query: Query<'w, 's, (&'static Transform, &'static Velocity, &'static Mass, Option<&'static GrowthOverride>, Option<&'static Species>, Has<Pinned>, Option<&'static Age>, Option<&'static Starving>), With<Particle>>,
+    emitter_query: Query<'w, 's, (&'static Emitter, &'static Emission)>,
+}
+
+impl StateCapture<'_, '_> {
+    pub fn capture(&self) -> SavedState {
+        let mut creatures: Vec<_> = self.creature_query.iter().collect();
+        creatures.sort_by_key(|(entity, ..)| *entity);
+        let emitters = self.emitter_query.iter()
+            .map(|(emitter, emission)| SavedEmitter {
+                emitter: *emitter,
+                creature: emission.creature.and_then(|creature| creatures.iter().position(|(entity, ..)| *entity == creature)),
+                owed: emission.owed,
+            })
+            .collect();
+
+        SavedState {
+            seed: self.scene.seed,
+            step: self.step.0,
+            camera_scale: self.scene.camera_scale,
+            inter_creature: self.inter_creature.enabled,
+            damping: match self.integrator.dynamics {
+                Dynamics::Overdamped => None,
+                Dynamics::Momentum { damping } => Some(damping),
+            },
+            creatures: creatures.into_iter()
+                .map(|(_, transform, parameters, time_scale, animation, oscillation, children)| SavedCreature {
+                    position: transform.translation,
+                    parameters: parameters.clone(),
+                    time_scale: time_scale.0,
+                    animation: animation.cloned(),
+                    oscillation: oscillation.cloned(),
+                    particles: self.particle_query.iter_many(children)
+                        .map(|(transform, velocity, mass, growth, species, pinned, age, starving)| SavedParticle {
+                            position: transform.translation,
+                            velocity: velocity.0,
+                            mass: mass.0,
+                            growth: growth.copied(),
+                            species: species.copied(),
+                            pinned,
+                            age: age.map(|age| age.0),
+                            starving: starving.map(|starving| starving.0),
+                        })
+                        .collect(),
+                })
+                .collect(),
+            rng: self.rng.as_ref().map(|rng| SavedRng {
+                seed: rng.0.get_seed(),
+                stream: rng.0.get_stream(),
+                word_pos: rng.0.get_word_pos() as u64,
+            }),
+            emitters,
+        }
+    }
+}
+
+pub fn spawn_saved_state(mut commands: Commands, state: Res<SavedState>) {
+    let mut creatures = Vec::new();
+    for saved in state.creatures.iter() {
+        let mut creature = commands.spawn(CreatureBundle {
+            spatial: SpatialBundle {
+                transform: Transform::from_translation(saved.position),
+                ..default()
+            },
+            parameters: saved.parameters.clone(),
+            time_scale: TimeScale(saved.time_scale),
+            stats: CreatureStats::default(),
+            creature: Creature,
+        });
+        if let Some(animation) = &saved.animation {
+            creature.insert(animation.clone());
+        }
+        if let Some(oscillation) = &saved.oscillation {
+            creature.insert(oscillation.clone());
+        }
+        creature.with_children(|parent| {
+            for particle in saved.particles.iter() {
+                let mut entity = parent.spawn(ParticleBundle {
+                    spatial: SpatialBundle {
+                        transform: Transform::from_translation(particle.position),
+                        ..default()
+                    },
+                    fields: Fields::default(),
+                    mass: Mass(particle.mass),
+                    velocity: Velocity(particle.velocity),
+                    particle: Particle,
+                });
+                if let Some(growth) = particle.growth {
+                    entity.insert(growth);
+                }
+                if let Some(species) = particle.species {
+                    entity.insert(species);
+                }
+                if particle.pinned {
+                    entity.insert(Pinned);
+                }
+                if let Some(age) = particle.age {
+                    entity.insert(Age(age));
+                }
+                if let Some(starving) = particle.starving {
+                    entity.insert(Starving(starving));
+                }
+            }
+        });
+        creatures.push(creature.id());
+    }
+
+    for saved in state.emitters.iter() {
+        let creature = saved.creature.and_then(|index| creatures.get(index).copied());
+        commands.spawn((saved.emitter, Emission { creature, owed: saved.owed }));
+    }
+}
@@ -0,0 +1,140 @@
+use bevy::prelude::*;
+use serde::Serialize;
+
+use crate::{
+    calculate_fields, effective_radius,
+    events::{CreatureExploded, CreatureFragmented},
+    update_position, Creature, Fields, Parameters, Particle,
+};
+
+// Particles further apart than the radius where the kernels fall below this
+// fraction of their peak (about 2%) are treated as disconnected.
+const CLUSTER_TOLERANCE: f32 = 0.018;
+
+#[derive(Component, Default, Clone, Debug)]
+pub struct CreatureStats {
+    pub centroid: Vec3,
+    pub spread: f32,
+    pub clusters: usize,
+    pub energy: f32,
+    pub shape: ShapeDescriptors,
+}
+
+/// Shape of a cloud of particles in the plane, from the eigenvalues of the
+/// covariance of their x and y about the centroid, in descending order.
+#[derive(Serialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct ShapeDescriptors {
+    /// Root mean square distance from the centroid
+    pub radius_of_gyration: f32,
+    /// Ratio of the spread along the major principal axis to that along the
+    /// minor one, 1 for a round creature and infinite for a line
+    pub aspect_ratio: f32,
+    /// `((l1 - l2) / (l1 + l2))^2`, 0 for a round creature and 1 for a line
+    pub asphericity: f32,
+}
+
+pub fn shape_descriptors(positions: &[Vec3]) -> ShapeDescriptors {
+    if positions.is_empty() {
+        return ShapeDescriptors::default();
+    }
+    let n = positions.len() as f32;
+    let centroid = positions.iter().sum::<Vec3>() / n;
+    let (xx, xy, yy) = positions.iter().fold((0.0, 0.0, 0.0), |(xx, xy, yy), x| {
+        let d = (*x - centroid).truncate();
+        (xx + d.x * d.x, xy + d.x * d.y, yy + d.y * d.y)
+    });
+    let (xx, xy, yy) = (xx / n, xy / n, yy / n);
+
+    let mean = (xx + yy) / 2.0;
+    let half_gap = (((xx - yy) / 2.0).powi(2) + xy * xy).sqrt();
+    let (major, minor) = (mean + half_gap, (mean - half_gap).max(0.0));
+    let trace = major + minor;
+    ShapeDescriptors {
+        radius_of_gyration: trace.sqrt(),
+        aspect_ratio: if trace > 0.0 { (major / minor).sqrt() } else { 1.0 },
+        asphericity: if trace > 0.0 { ((major - minor) / trace).powi(2) } else { 0.0 },
+    }
+}
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, update_creature_stats.after(calculate_fields).before(update_position));
+    }
+}
+
+pub fn update_creature_stats(
+    mut creature_query: Query<(Entity, &Transform, &Parameters, &Children, &mut CreatureStats), With<Creature>>,
+    particle_query: Query<(&Transform, &Fields), With<Particle>>,
+    mut fragmented: EventWriter<CreatureFragmented>,
+    mut exploded: EventWriter<CreatureExploded>,
+) {
+    for (creature, creature_transform, parameters, children, mut stats) in creature_query.iter_mut() {
+        let mut positions = Vec::with_capacity(children.len());
+        let mut energy = 0.0;
+
+        for child in children.iter() {
+            let Ok((transform, fields)) = particle_query.get(*child) else {
+                continue;
+            };
+            positions.push(creature_transform.translation + transform.translation);
+
+            let (G, _) = parameters.growth_field(fields.U_val);
+            energy += fields.R_val - G;
+        }
+
+        if positions.is_empty() {
+            *stats = CreatureStats::default();
+            continue;
+        }
+
+        let n = positions.len() as f32;
+        let centroid = positions.iter().sum::<Vec3>() / n;
+        let spread = (positions.iter().map(|x| x.distance_squared(centroid)).sum::<f32>() / n).sqrt();
+
+        let link = effective_radius(parameters, CLUSTER_TOLERANCE);
+        let clusters = count_clusters(&positions, link);
+
+        // Compared with the previous step, so each is reported once as it happens.
+        if stats.clusters > 0 && clusters > stats.clusters {
+            fragmented.send(CreatureFragmented { creature, clusters });
+        }
+        let was_finite = stats.centroid.is_finite() && stats.energy.is_finite();
+        if was_finite && !(centroid.is_finite() && energy.is_finite()) {
+            exploded.send(CreatureExploded { creature });
+        }
+
+        *stats = CreatureStats {
+            centroid,
+            spread,
+            clusters,
+            energy: energy / n,
+            shape: shape_descriptors(&positions),
+        };
+    }
+}
+
+pub fn count_clusters(positions: &[Vec3], link: f32) -> usize {
+    fn find(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+
+    let mut parent: Vec<usize> = (0..positions.len()).collect();
+    for i in 0..positions.len() {
+        for j in (i + 1)..positions.len() {
+            if positions[i].distance_squared(positions[j]) < link * link {
+                let (a, b) = (find(&mut parent, i), find(&mut parent, j));
+                if a != b {
+                    parent[a] = b;
+                }
+            }
+        }
+    }
+
+    (0..positions.len()).filter(|&i| find(&mut parent, i) == i).count()
+}
@@ -0,0 +1,118 @@
+use bevy::prelude::*;
+
+use crate::{
+    probe::{creature_positions, probe_energy_gradient},
+    Creature, Parameters, Particle,
+};
+
+#[derive(Resource)]
+pub struct Streamlines {
+    pub enabled: bool,
+    pub seeds_per_axis: usize,
+    pub steps: usize,
+    pub step_length: f32,
+    pub refresh_frames: u32,
+    frames_since_refresh: u32,
+    lines: Vec<Vec<Vec2>>,
+}
+
+impl Default for Streamlines {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            seeds_per_axis: 16,
+            steps: 20,
+            step_length: 0.5,
+            refresh_frames: 10,
+            frames_since_refresh: 0,
+            lines: Vec::new(),
+        }
+    }
+}
+
+pub struct StreamlinePlugin;
+
+impl Plugin for StreamlinePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Streamlines>()
+            .add_systems(Update, (toggle_streamlines, trace_streamlines, draw_streamlines).chain());
+    }
+}
+
+fn toggle_streamlines(
+    keys: Res<Input<KeyCode>>,
+    mut streamlines: ResMut<Streamlines>,
+) {
+    if keys.just_pressed(KeyCode::L) {
+        streamlines.enabled = !streamlines.enabled;
+        streamlines.frames_since_refresh = streamlines.refresh_frames;
+        streamlines.lines.clear();
+    }
+}
+
+fn trace_streamlines(
+    mut streamlines: ResMut<Streamlines>,
+    creature_query: Query<(&Transform, &Parameters, &Children), With<Creature>>,
+    particle_query: Query<&Transform, With<Particle>>,
+) {
+    if !streamlines.enabled {
+        return;
+    }
+    if streamlines.frames_since_refresh < streamlines.refresh_frames {
+        streamlines.frames_since_refresh += 1;
+        return;
+    }
+    streamlines.frames_since_refresh = 0;
+
+    let creatures = creature_positions(&creature_query, &particle_query);
+    let Some((min, max)) = creatures.iter()
+        .flat_map(|(parameters, positions)| positions.iter().map(move |x| (x.truncate(), parameters.mu_k + parameters.sigma_k)))
+        .fold(None, |bounds: Option<(Vec2, Vec2)>, (x, pad)| {
+            let (min, max) = bounds.unwrap_or((x, x));
+            Some((min.min(x - pad), max.max(x + pad)))
+        })
+    else {
+        streamlines.lines.clear();
+        return;
+    };
+
+    let n = streamlines.seeds_per_axis;
+    let mut lines = Vec::with_capacity(n * n);
+    for i in 0..n {
+        for j in 0..n {
+            let t = Vec2::new((i as f32 + 0.5) / n as f32, (j as f32 + 0.5) / n as f32);
+            let mut point = min + t * (max - min);
+            let mut line = vec![point];
+
+            for _ in 0..streamlines.steps {
+                let flow = -probe_energy_gradient(point.extend(0.0), &creatures).truncate();
+                let speed = flow.length();
+                if speed < 1e-6 {
+                    break;
+                }
+                point += flow / speed * streamlines.step_length;
+                line.push(point);
+            }
+
+            if line.len() > 1 {
+                lines.push(line);
+            }
+        }
+    }
+    streamlines.lines = lines;
+}
+
+fn draw_streamlines(
+    mut gizmos: Gizmos,
+    streamlines: Res<Streamlines>,
+) {
+    if !streamlines.enabled {
+        return;
+    }
+    for line in streamlines.lines.iter() {
+        gizmos.linestrip_gradient_2d(line.iter().enumerate().map(|(index, point)| {
+            let t = index as f32 / (line.len() - 1) as f32;
+            (*point, Color::rgba(0.2, 0.6, 1.0, 0.1 + 0.5 * t))
+        }));
+    }
+}
@@ -0,0 +1,27 @@
+use bevy::{ecs::schedule::ScheduleLabel, prelude::*};
+
+use crate::{calculate_fields, clamp_forces, sum_pair_fields, update_position, Integrator};
+
+/// The field and integration systems alone, run between Update's own passes
+/// over them. Force terms added between `calculate_fields` and `clamp_forces`
+/// belong here as well so every substep feels them.
+#[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Substep;
+
+/// Splits each step into `Integrator::substeps` moves of a fraction of the
+/// time step each, recomputing the fields before every one. The last is
+/// Update's own pass, so everything else still sees one step per frame.
+pub struct SubstepPlugin;
+
+impl Plugin for SubstepPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Substep, (sum_pair_fields, calculate_fields, clamp_forces, update_position).chain())
+            .add_systems(Update, run_substeps.before(sum_pair_fields));
+    }
+}
+
+fn run_substeps(world: &mut World) {
+    for _ in 1..world.resource::<Integrator>().substeps {
+        world.run_schedule(Substep);
+    }
+}
@@ -0,0 +1,47 @@
+use std::{fmt::Write, fs, io, path::Path};
+
+use bevy::prelude::*;
+
+/// A filled circle in world space.
+pub struct SvgCircle {
+    pub center: Vec2,
+    pub radius: f32,
+    pub color: Color,
+}
+
+/// Writes `circles`, in drawing order, as an SVG of `size` pixels framing the
+/// world-space `view` the way the camera does, y pointing up.
+pub fn export_svg(path: &Path, view: Rect, size: Vec2, background: Color, circles: &[SvgCircle]) -> io::Result<()> {
+    let scale = size / view.size();
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = size.x, h = size.y,
+    );
+    let _ = writeln!(svg, r#"<rect width="100%" height="100%" fill="{}"/>"#, hex(background));
+
+    for circle in circles {
+        let x = (circle.center.x - view.min.x) * scale.x;
+        let y = (view.max.y - circle.center.y) * scale.y;
+        if x + circle.radius * scale.x < 0.0 || x - circle.radius * scale.x > size.x
+            || y + circle.radius * scale.y < 0.0 || y - circle.radius * scale.y > size.y
+        {
+            continue;
+        }
+        let [_, _, _, a] = circle.color.as_rgba_f32();
+        let _ = writeln!(
+            svg,
+            r#"<circle cx="{:.2}" cy="{:.2}" r="{:.2}" fill="{}" fill-opacity="{:.3}"/>"#,
+            x, y, circle.radius * scale.x, hex(circle.color), a,
+        );
+    }
+
+    svg.push_str("</svg>\n");
+    fs::write(path, svg)
+}
+
+fn hex(color: Color) -> String {
+    let [r, g, b, _] = color.as_rgba_u8();
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
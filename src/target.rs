@@ -0,0 +1,67 @@
+use bevy::prelude::*;
+
+use crate::{calculate_fields, clamp_forces, substeps::Substep, Creature, Fields, ForceTerms, Particle};
+
+/// Radial density profile each creature is sculpted toward: `center` at its
+/// centroid, changing linearly to `edge` at `radius` and staying there beyond.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct TargetProfile {
+    pub center: f32,
+    pub edge: f32,
+    pub radius: f32,
+    /// Stiffness of the pull, the `k` in `k/2 (R_val - target)^2`
+    pub strength: f32,
+}
+
+impl TargetProfile {
+    /// Target density at distance `d` from the centroid and its derivative in `d`.
+    pub fn density(&self, d: f32) -> (f32, f32) {
+        if d >= self.radius {
+            return (self.edge, 0.0);
+        }
+        let slope = (self.edge - self.center) / self.radius;
+        (self.center + slope * d, slope)
+    }
+}
+
+pub struct TargetProfilePlugin {
+    pub profile: TargetProfile,
+}
+
+impl Plugin for TargetProfilePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.profile)
+            .add_systems(Update, pull_toward_profile.after(calculate_fields).before(clamp_forces))
+            .add_systems(Substep, pull_toward_profile.after(calculate_fields).before(clamp_forces));
+    }
+}
+
+// Descends k/2 (R_val - target(d))^2 alongside the particle's own energy,
+// treating the centroid and the other particles' densities as fixed.
+fn pull_toward_profile(
+    force_terms: Res<ForceTerms>,
+    profile: Res<TargetProfile>,
+    creature_query: Query<&Children, With<Creature>>,
+    mut particle_query: Query<(&Transform, &mut Fields), With<Particle>>,
+) {
+    if !force_terms.external {
+        return;
+    }
+    for children in creature_query.iter() {
+        let (sum, count) = particle_query.iter_many(children)
+            .fold((Vec3::ZERO, 0), |(sum, count), (transform, _)| (sum + transform.translation, count + 1));
+        if count == 0 {
+            continue;
+        }
+        let centroid = sum / count as f32;
+
+        let mut particles = particle_query.iter_many_mut(children);
+        while let Some((transform, mut fields)) = particles.fetch_next() {
+            let offset = transform.translation - centroid;
+            let (target, slope) = profile.density(offset.length());
+            let target_grad = slope * offset.normalize_or_zero();
+            let pull = profile.strength * (fields.R_val - target) * (fields.R_grad - target_grad);
+            fields.E_grad += pull;
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use std::{fs, path::Path};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    scene::{CreatureSpawn, SpawnPattern},
+    Parameters,
+};
+
+/// A settled creature to spawn again: its parameters and its particles'
+/// offsets from their centroid, written as RON by Shift-P and spawned with
+/// `--template`, so it starts in its settled shape.
+#[derive(Serialize, Deserialize)]
+pub struct CreatureTemplate {
+    pub parameters: Parameters,
+    pub offsets: Vec<Vec3>,
+}
+
+impl CreatureTemplate {
+    /// The template of a creature with particles at `positions`.
+    pub fn from_positions(parameters: &Parameters, positions: &[Vec3]) -> Self {
+        let centroid = positions.iter().sum::<Vec3>() / positions.len().max(1) as f32;
+        Self {
+            parameters: parameters.clone(),
+            offsets: positions.iter().map(|position| *position - centroid).collect(),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let ron = fs::read_to_string(path).map_err(|err| err.to_string())?;
+        let template: Self = ron::from_str(&ron).map_err(|err| err.to_string())?;
+        template.parameters.validate()?;
+        if template.offsets.is_empty() {
+            return Err("the template has no particles".to_string());
+        }
+        Ok(template)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let ron = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).map_err(|err| err.to_string())?;
+        fs::write(path, ron).map_err(|err| err.to_string())
+    }
+
+    /// A spawn of the template with its centroid at `position`.
+    pub fn spawn(&self, position: Vec3) -> CreatureSpawn {
+        CreatureSpawn {
+            position,
+            parameters: self.parameters.clone(),
+            pattern: SpawnPattern::Explicit(self.offsets.clone()),
+            ..default()
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{presets::AnimateParams, Creature, Parameters, SimStep};
+
+/// Parameters an event changes, leaving the rest as they are.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ParameterChanges {
+    pub mu_k: Option<f32>,
+    pub sigma_k: Option<f32>,
+    pub w_k: Option<f32>,
+    pub mu_g: Option<f32>,
+    pub sigma_g: Option<f32>,
+    pub c_rep: Option<f32>,
+}
+
+impl ParameterChanges {
+    fn apply(&self, parameters: &Parameters) -> Parameters {
+        let mut changed = parameters.clone();
+        for (value, field) in [
+            (self.mu_k, &mut changed.mu_k),
+            (self.sigma_k, &mut changed.sigma_k),
+            (self.w_k, &mut changed.w_k),
+            (self.mu_g, &mut changed.mu_g),
+            (self.sigma_g, &mut changed.sigma_g),
+            (self.c_rep, &mut changed.c_rep),
+        ] {
+            if let Some(value) = value {
+                *field = value;
+            }
+        }
+        changed
+    }
+}
+
+/// A creature by its index in spawn order, or by a name the timeline gives
+/// one.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum CreatureRef {
+    Index(usize),
+    Name(String),
+}
+
+/// A change to one creature's parameters, or every creature's when none is
+/// named, made at `step`. With a `duration` the parameters are morphed over
+/// that much simulation time, as `--morph-to` does, instead of set at once.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TimelineEvent {
+    pub step: u64,
+    #[serde(default)]
+    pub creature: Option<CreatureRef>,
+    #[serde(default)]
+    pub duration: Option<f32>,
+    pub set: ParameterChanges,
+}
+
+/// Scripted parameter changes over a run, in the order they happen.
+#[derive(Resource, Clone, Debug, Deserialize)]
+pub struct Timeline {
+    #[serde(default)]
+    names: HashMap<String, usize>,
+    events: Vec<TimelineEvent>,
+}
+
+impl Timeline {
+    /// Reads a timeline from JSON, e.g.
+    /// `{"names": {"A": 0}, "events": [{"step": 500, "creature": "A", "set": {"mu_g": 0.8}, "duration": 50}, {"step": 900, "set": {"c_rep": 1.5}}]}`.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = fs::read_to_string(path).map_err(|err| err.to_string())?;
+        let mut timeline: Self = serde_json::from_str(&json).map_err(|err| err.to_string())?;
+        for (index, event) in timeline.events.iter().enumerate() {
+            if let Some(CreatureRef::Name(name)) = &event.creature {
+                if !timeline.names.contains_key(name) {
+                    return Err(format!("event {} names creature {:?}, which isn't in names", index + 1, name));
+                }
+            }
+            if event.duration.is_some_and(|duration| duration < 0.0) {
+                return Err(format!("event {} has a negative duration", index + 1));
+            }
+        }
+        // Stable, so events at the same step keep the file's order.
+        timeline.events.sort_by_key(|event| event.step);
+        Ok(timeline)
+    }
+
+    fn index(&self, creature: &CreatureRef) -> usize {
+        match creature {
+            CreatureRef::Index(index) => *index,
+            CreatureRef::Name(name) => self.names[name],
+        }
+    }
+}
+
+/// Applies each timeline event once the step count reaches it. A run that
+/// starts past an event, as a loaded state can, applies it straight away.
+pub struct TimelinePlugin {
+    pub timeline: Timeline,
+}
+
+// Events already applied, from the front of the timeline.
+#[derive(Resource, Default)]
+struct TimelineProgress(usize);
+
+impl Plugin for TimelinePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.timeline.clone())
+            .init_resource::<TimelineProgress>()
+            // Before Update, so the step's fields already use the changes.
+            .add_systems(PreUpdate, apply_timeline);
+    }
+}
+
+// Changes made while a creature is being morphed carry on to where it's
+// being morphed to, so a later event doesn't cut an earlier one short.
+fn apply_timeline(
+    mut commands: Commands,
+    timeline: Res<Timeline>,
+    step: Res<SimStep>,
+    mut progress: ResMut<TimelineProgress>,
+    mut creature_query: Query<(Entity, &mut Parameters, Option<&mut AnimateParams>), With<Creature>>,
+) {
+    let due = timeline.events[progress.0..].iter().take_while(|event| event.step <= step.0).count();
+    if due == 0 {
+        return;
+    }
+    let mut creatures: Vec<_> = creature_query.iter_mut().collect();
+    creatures.sort_by_key(|(entity, ..)| *entity);
+
+    for event in &timeline.events[progress.0..progress.0 + due] {
+        let targets: Vec<usize> = match &event.creature {
+            Some(creature) => vec![timeline.index(creature)],
+            None => (0..creatures.len()).collect(),
+        };
+        for index in targets {
+            let Some((entity, parameters, animation)) = creatures.get_mut(index) else {
+                warn!(step = step.0, creature = index, "timeline event for a creature that doesn't exist");
+                continue;
+            };
+            let target = event.set.apply(animation.as_ref().map_or(&**parameters, |animation| &animation.to));
+            if let Err(err) = target.validate() {
+                warn!(step = step.0, creature = index, "kept the previous parameters: {}", err);
+                continue;
+            }
+            match (event.duration.filter(|duration| *duration > 0.0), animation) {
+                (Some(duration), _) => {
+                    commands.entity(*entity).insert(AnimateParams {
+                        from: parameters.clone(),
+                        to: target,
+                        duration,
+                        elapsed: 0.0,
+                    });
+                }
+                (None, Some(animation)) => {
+                    animation.from = event.set.apply(&animation.from);
+                    animation.to = target;
+                    **parameters = event.set.apply(parameters);
+                }
+                (None, None) => **parameters = target,
+            }
+            info!(step = step.0, creature = index, "timeline event applied");
+        }
+    }
+    progress.0 += due;
+}
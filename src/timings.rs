@@ -0,0 +1,54 @@
+use bevy::{
+    diagnostic::{Diagnostic, DiagnosticId, Diagnostics, RegisterDiagnostic},
+    prelude::*,
+    utils::Instant,
+};
+
+pub const SUM_PAIR_FIELDS: DiagnosticId = DiagnosticId::from_u128(0x9f1c_3a52_6d0e_4b7a_8c21_0e55_a1f3_0001);
+pub const CALCULATE_FIELDS: DiagnosticId = DiagnosticId::from_u128(0x9f1c_3a52_6d0e_4b7a_8c21_0e55_a1f3_0002);
+pub const UPDATE_POSITION: DiagnosticId = DiagnosticId::from_u128(0x9f1c_3a52_6d0e_4b7a_8c21_0e55_a1f3_0003);
+pub const UPDATE_SIZE: DiagnosticId = DiagnosticId::from_u128(0x9f1c_3a52_6d0e_4b7a_8c21_0e55_a1f3_0004);
+
+/// Every timed system, in the order they run.
+pub const SYSTEM_TIMINGS: [(DiagnosticId, &str); 4] = [
+    (SUM_PAIR_FIELDS, "sum_pair_fields"),
+    (CALCULATE_FIELDS, "calculate_fields"),
+    (UPDATE_POSITION, "update_position"),
+    (UPDATE_SIZE, "update_size"),
+];
+
+/// Registers a milliseconds diagnostic for each timed system, which measures
+/// itself with a [`SystemTimer`].
+pub struct TimingsPlugin;
+
+impl Plugin for TimingsPlugin {
+    fn build(&self, app: &mut App) {
+        for (id, name) in SYSTEM_TIMINGS {
+            app.register_diagnostic(Diagnostic::new(id, name, 60).with_suffix("ms"));
+        }
+    }
+}
+
+/// Records the time from its creation until it's dropped as a measurement of `id`.
+pub struct SystemTimer<'a, 'w, 's> {
+    diagnostics: &'a mut Diagnostics<'w, 's>,
+    id: DiagnosticId,
+    start: Instant,
+}
+
+impl<'a, 'w, 's> SystemTimer<'a, 'w, 's> {
+    pub fn start(diagnostics: &'a mut Diagnostics<'w, 's>, id: DiagnosticId) -> Self {
+        SystemTimer {
+            diagnostics,
+            id,
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Drop for SystemTimer<'_, '_, '_> {
+    fn drop(&mut self) {
+        let elapsed = self.start.elapsed();
+        self.diagnostics.add_measurement(self.id, || elapsed.as_secs_f64() * 1000.0);
+    }
+}
@@ -0,0 +1,192 @@
+use std::{collections::VecDeque, fmt::Write};
+
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    picking::{nearest_particle, Cursor, ScreenSpace},
+    selection::PICK_RADIUS,
+    Creature, Fields, Particle, SimStep, Velocity,
+};
+
+// Steps of history kept for each traced particle.
+const HISTORY: usize = 500;
+// Size of each plot and the gaps around them, in logical pixels.
+const PLOT_SIZE: Vec2 = Vec2::new(280.0, 72.0);
+const PLOT_MARGIN: f32 = 16.0;
+const PLOT_GAP: f32 = 8.0;
+const MARKER_RADIUS: f32 = 6.0;
+// Colors handed out to traces in turn.
+const TRACE_COLORS: [Color; 6] = [Color::ORANGE, Color::CYAN, Color::LIME_GREEN, Color::FUCHSIA, Color::YELLOW, Color::TOMATO];
+const QUANTITIES: [&str; 3] = ["U_val", "R_val", "speed"];
+
+/// T traces the particle under the cursor, or stops tracing it, and Shift-T
+/// stops tracing every particle. Each traced particle gets a color and plots
+/// of its U_val, R_val and speed over its last steps in the bottom-right
+/// corner.
+pub struct TracePlugin;
+
+struct Trace {
+    particle: Entity,
+    color: Color,
+    history: VecDeque<[f32; 3]>,
+}
+
+#[derive(Resource, Default)]
+struct Traces {
+    traces: Vec<Trace>,
+    next_color: usize,
+}
+
+#[derive(Component)]
+struct TraceText;
+
+impl Plugin for TracePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Traces>()
+            .add_systems(Startup, spawn_trace_text)
+            .add_systems(Update, (toggle_trace, record_traces, draw_traces, update_trace_text).chain());
+    }
+}
+
+fn spawn_trace_text(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section("", TextStyle {
+            font_size: 14.0,
+            color: Color::WHITE,
+            ..default()
+        })
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(PLOT_MARGIN + 3.0 * (PLOT_SIZE.y + PLOT_GAP)),
+            right: Val::Px(PLOT_MARGIN),
+            ..default()
+        }),
+        TraceText,
+    ));
+}
+
+fn toggle_trace(
+    keys: Res<Input<KeyCode>>,
+    cursor: Cursor,
+    mut traces: ResMut<Traces>,
+    creature_query: Query<(Entity, &Transform, &Children), With<Creature>>,
+    particle_query: Query<&Transform, With<Particle>>,
+) {
+    if !keys.just_pressed(KeyCode::T) {
+        return;
+    }
+    if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        traces.traces.clear();
+        return;
+    }
+    let Some((_, particle)) = cursor.world_position()
+        .and_then(|point| nearest_particle(point, PICK_RADIUS, &creature_query, &particle_query))
+    else {
+        return;
+    };
+
+    if let Some(index) = traces.traces.iter().position(|trace| trace.particle == particle) {
+        traces.traces.remove(index);
+        return;
+    }
+    let color = TRACE_COLORS[traces.next_color % TRACE_COLORS.len()];
+    traces.next_color += 1;
+    traces.traces.push(Trace { particle, color, history: VecDeque::with_capacity(HISTORY) });
+}
+
+// Only on frames that advanced the step, so pausing doesn't fill the history
+// with copies of the same sample.
+fn record_traces(
+    step: Res<SimStep>,
+    mut traces: ResMut<Traces>,
+    particle_query: Query<(&Fields, &Velocity), With<Particle>>,
+) {
+    traces.traces.retain(|trace| particle_query.contains(trace.particle));
+    if !step.is_changed() {
+        return;
+    }
+    for trace in traces.traces.iter_mut() {
+        let Ok((fields, velocity)) = particle_query.get(trace.particle) else {
+            continue;
+        };
+        if trace.history.len() == HISTORY {
+            trace.history.pop_front();
+        }
+        trace.history.push_back([fields.U_val, fields.R_val, velocity.0.length()]);
+    }
+}
+
+// Range of each quantity over every trace, widened when it's flat.
+fn ranges(traces: &Traces) -> [(f32, f32); 3] {
+    let mut ranges = [(f32::INFINITY, f32::NEG_INFINITY); 3];
+    for sample in traces.traces.iter().flat_map(|trace| trace.history.iter()) {
+        for (range, value) in ranges.iter_mut().zip(sample) {
+            *range = (range.0.min(*value), range.1.max(*value));
+        }
+    }
+    ranges.map(|(min, max)| if max - min > f32::EPSILON { (min, max) } else { (min - 0.5, min + 0.5) })
+}
+
+fn draw_traces(
+    traces: Res<Traces>,
+    screen: ScreenSpace,
+    mut gizmos: Gizmos,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    creature_query: Query<&Transform, With<Creature>>,
+    particle_query: Query<(&Parent, &Transform), With<Particle>>,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    if traces.traces.is_empty() {
+        return;
+    }
+
+    // Markers keep their size on screen as the camera zooms.
+    let radius = screen.world_length(Vec2::new(window.width(), window.height()) / 2.0, MARKER_RADIUS).unwrap_or(PICK_RADIUS);
+    for trace in traces.traces.iter() {
+        let Ok((parent, transform)) = particle_query.get(trace.particle) else {
+            continue;
+        };
+        let offset = creature_query.get(parent.get()).map_or(Vec3::ZERO, |transform| transform.translation);
+        gizmos.circle_2d((offset + transform.translation).truncate(), radius, trace.color);
+    }
+
+    // U_val at the top, speed at the bottom.
+    for (quantity, (min, max)) in ranges(&traces).into_iter().enumerate() {
+        let bottom = window.height() - PLOT_MARGIN - (2 - quantity) as f32 * (PLOT_SIZE.y + PLOT_GAP);
+        let plot = Rect::from_corners(
+            Vec2::new(window.width() - PLOT_MARGIN - PLOT_SIZE.x, bottom - PLOT_SIZE.y),
+            Vec2::new(window.width() - PLOT_MARGIN, bottom),
+        );
+        let corners = [plot.min, Vec2::new(plot.max.x, plot.min.y), plot.max, Vec2::new(plot.min.x, plot.max.y), plot.min];
+        gizmos.linestrip_2d(corners.into_iter().filter_map(|point| screen.world_point(point)), Color::GRAY);
+
+        for trace in traces.traces.iter() {
+            let line = trace.history.iter().enumerate().filter_map(|(i, sample)| {
+                let x = plot.min.x + i as f32 / (HISTORY - 1) as f32 * plot.width();
+                let y = plot.max.y - (sample[quantity] - min) / (max - min) * plot.height();
+                screen.world_point(Vec2::new(x, y))
+            });
+            gizmos.linestrip_2d(line, trace.color);
+        }
+    }
+}
+
+fn update_trace_text(
+    traces: Res<Traces>,
+    mut text_query: Query<(&mut Text, &mut Visibility), With<TraceText>>,
+) {
+    let mut text = String::new();
+    if !traces.traces.is_empty() {
+        for (name, (min, max)) in QUANTITIES.iter().zip(ranges(&traces)) {
+            let _ = writeln!(text, "{} {:.4} .. {:.4}", name, min, max);
+        }
+        let _ = write!(text, "last {} steps, top to bottom", HISTORY);
+    }
+
+    for (mut trace_text, mut visibility) in text_query.iter_mut() {
+        *visibility = if text.is_empty() { Visibility::Hidden } else { Visibility::Inherited };
+        trace_text.sections[0].value.clone_from(&text);
+    }
+}
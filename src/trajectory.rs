@@ -0,0 +1,95 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+use bevy::{app::AppExit, prelude::*};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use crate::{advance_step, update_position, Creature, Particle, SimStep};
+
+/// Writes particle positions as CSV rows of `step,creature,particle,x,y,z`,
+/// keeping only every `step_stride`th step and a `particle_fraction` of the
+/// particles.
+pub struct TrajectoryPlugin {
+    pub file: File,
+    pub seed: u64,
+    pub step_stride: u64,
+    pub particle_fraction: f32,
+}
+
+#[derive(Resource)]
+struct TrajectoryLog {
+    writer: Option<BufWriter<File>>,
+    seed: u64,
+    step_stride: u64,
+    particle_fraction: f32,
+}
+
+impl TrajectoryLog {
+    // Whether a particle is in the logged subset. It depends only on the seed
+    // and the entity, so a rerun with the same options logs the same particles
+    // at every step.
+    fn keeps(&self, particle: Entity) -> bool {
+        self.particle_fraction >= 1.0
+            || SmallRng::seed_from_u64(self.seed ^ particle.to_bits()).gen::<f32>() < self.particle_fraction
+    }
+}
+
+impl Plugin for TrajectoryPlugin {
+    fn build(&self, app: &mut App) {
+        let mut writer = BufWriter::new(self.file.try_clone().expect("failed to open the trajectory file"));
+        let writer = writeln!(writer, "step,creature,particle,x,y,z").is_ok().then_some(writer);
+        app.insert_resource(TrajectoryLog {
+                writer,
+                seed: self.seed,
+                step_stride: self.step_stride,
+                particle_fraction: self.particle_fraction,
+            })
+            .add_systems(Update, log_trajectory.after(update_position).before(advance_step))
+            .add_systems(Last, flush_trajectory);
+    }
+}
+
+fn log_trajectory(
+    step: Res<SimStep>,
+    mut log: ResMut<TrajectoryLog>,
+    creature_query: Query<(Entity, &Transform, &Children), With<Creature>>,
+    particle_query: Query<(Entity, &Transform), With<Particle>>,
+) {
+    if !step.0.is_multiple_of(log.step_stride) {
+        return;
+    }
+
+    let mut creatures: Vec<_> = creature_query.iter().collect();
+    creatures.sort_by_key(|(entity, ..)| *entity);
+    let mut rows = String::new();
+    for (creature, creature_transform, children) in creatures {
+        for (particle, transform) in particle_query.iter_many(children) {
+            if !log.keeps(particle) {
+                continue;
+            }
+            let position = creature_transform.translation + transform.translation;
+            rows += &format!("{},{},{},{},{},{}\n", step.0, creature.index(), particle.index(), position.x, position.y, position.z);
+        }
+    }
+
+    let Some(writer) = log.writer.as_mut() else {
+        return;
+    };
+    if let Err(err) = writer.write_all(rows.as_bytes()) {
+        error!("failed to write the trajectory, no longer logging it: {}", err);
+        log.writer = None;
+    }
+}
+
+fn flush_trajectory(mut log: ResMut<TrajectoryLog>, mut exit: EventReader<AppExit>) {
+    if exit.iter().next().is_none() {
+        return;
+    }
+    if let Some(writer) = log.writer.as_mut() {
+        if let Err(err) = writer.flush() {
+            error!("failed to write the trajectory: {}", err);
+        }
+    }
+}
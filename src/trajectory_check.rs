@@ -0,0 +1,119 @@
+use std::{fs, path::{Path, PathBuf}};
+
+use bevy::{app::AppExit, prelude::*};
+use serde::Deserialize;
+
+use crate::{advance_step, headless::exit_after_steps, Creature, Particle, SimStep};
+
+/// Particle positions exported from another implementation of the model,
+/// every particle's x and y at step 0, 1, 2 and so on, in a fixed order.
+#[derive(Clone, Deserialize)]
+pub struct ReferenceTrajectory {
+    /// Time step the reference was integrated with, checked against ours
+    /// when given
+    #[serde(default)]
+    pub dt: Option<f32>,
+    pub steps: Vec<Vec<[f32; 2]>>,
+}
+
+impl ReferenceTrajectory {
+    pub fn load(path: &Path) -> Result<ReferenceTrajectory, String> {
+        let json = fs::read_to_string(path).map_err(|err| err.to_string())?;
+        let reference: ReferenceTrajectory = serde_json::from_str(&json).map_err(|err| err.to_string())?;
+        let count = match reference.steps.first() {
+            Some(first) if !first.is_empty() => first.len(),
+            _ => return Err("no particles at step 0".to_string()),
+        };
+        if let Some(step) = reference.steps.iter().position(|positions| positions.len() != count) {
+            return Err(format!("{} particles at step {}, {} at step 0", reference.steps[step].len(), step, count));
+        }
+        Ok(reference)
+    }
+
+    /// Where the particles start, as offsets from the origin.
+    pub fn initial_positions(&self) -> Vec<Vec3> {
+        self.steps[0].iter().map(|[x, y]| Vec3::new(*x, *y, 0.0)).collect()
+    }
+}
+
+/// Compares every particle's position after each step with the reference's
+/// at the same step, exiting with an error as soon as one is further than
+/// `tolerance` from it, and successfully after the reference's last step.
+pub struct TrajectoryCheckPlugin {
+    pub path: PathBuf,
+    pub reference: ReferenceTrajectory,
+    pub tolerance: f32,
+}
+
+#[derive(Resource)]
+struct TrajectoryCheck {
+    path: PathBuf,
+    reference: ReferenceTrajectory,
+    tolerance: f32,
+    checked: u64,
+    largest: f32,
+}
+
+impl Plugin for TrajectoryCheckPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(TrajectoryCheck {
+                path: self.path.clone(),
+                reference: self.reference.clone(),
+                tolerance: self.tolerance,
+                checked: 0,
+                largest: 0.0,
+            })
+            .add_systems(Update, check_trajectory.after(advance_step))
+            .add_systems(Last, report_trajectory.after(exit_after_steps));
+    }
+}
+
+fn check_trajectory(
+    mut check: ResMut<TrajectoryCheck>,
+    step: Res<SimStep>,
+    mut exit: EventWriter<AppExit>,
+    creature_query: Query<(Entity, &Transform, &Children), With<Creature>>,
+    particle_query: Query<&Transform, With<Particle>>,
+) {
+    let check = &mut *check;
+    let Some(expected) = check.reference.steps.get(step.0 as usize) else {
+        return;
+    };
+
+    // In spawn order, as the reference's particles were read in.
+    let mut creatures: Vec<_> = creature_query.iter().collect();
+    creatures.sort_by_key(|(entity, ..)| *entity);
+    let actual: Vec<Vec2> = creatures.into_iter()
+        .flat_map(|(_, creature_transform, children)| {
+            particle_query.iter_many(children).map(|transform| (creature_transform.translation + transform.translation).truncate())
+        })
+        .collect();
+    if actual.len() != expected.len() {
+        eprintln!("{} particles at step {}, {} in {}", actual.len(), step.0, expected.len(), check.path.display());
+        std::process::exit(1);
+    }
+
+    for (particle, (expected, actual)) in expected.iter().zip(&actual).enumerate() {
+        let deviation = Vec2::from_array(*expected).distance(*actual);
+        if deviation > check.tolerance || deviation.is_nan() {
+            eprintln!(
+                "step {} particle {} at {:?}, {:?} in {} ({} apart, tolerance {})",
+                step.0, particle, actual.to_array(), expected, check.path.display(), deviation, check.tolerance,
+            );
+            std::process::exit(1);
+        }
+        check.largest = check.largest.max(deviation);
+    }
+    check.checked = step.0;
+
+    if step.0 as usize + 1 == check.reference.steps.len() {
+        exit.send(AppExit);
+    }
+}
+
+fn report_trajectory(check: Res<TrajectoryCheck>, mut exit: EventReader<AppExit>) {
+    if exit.iter().next().is_none() {
+        return;
+    }
+    info!("tracked {} for {} steps, at most {} apart", check.path.display(), check.checked, check.largest);
+}
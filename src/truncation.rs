@@ -0,0 +1,122 @@
+use bevy::{math::DVec3, prelude::*};
+
+use crate::{
+    calculate_fields, species::{InteractionMatrix, Species}, sum_pair_fields, Creature, Fields, KernelCutoff,
+    MetricScale, Parameters, Particle, SelfField, SimStep, WideFields,
+};
+
+// Smallest full sum relative errors are taken against, so a gradient that
+// vanishes, as at the centre of a symmetric creature, doesn't divide by zero.
+const RELATIVE_FLOOR: f64 = 1e-12;
+
+/// Every `every` steps, and in the window whenever F6 is pressed, sums the
+/// kernel field directly over every other particle of the creature for
+/// `sample` particles spread across the scene, with no cutoff or tree, and
+/// logs how far the cut or tree sums the step used are from it: how much of
+/// U and its gradient `--kernel-cutoff` or `--barnes-hut` throws away.
+pub struct TruncationReportPlugin {
+    pub every: Option<u64>,
+    pub sample: usize,
+    pub interactive: bool,
+}
+
+#[derive(Resource)]
+struct TruncationReport {
+    every: Option<u64>,
+    sample: usize,
+    requested: bool,
+}
+
+impl Plugin for TruncationReportPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(TruncationReport { every: self.every, sample: self.sample.max(1), requested: false })
+            .add_systems(Update, report_truncation.after(sum_pair_fields).before(calculate_fields));
+        if self.interactive {
+            app.add_systems(Update, request_report.before(report_truncation));
+        }
+    }
+}
+
+fn request_report(keys: Res<Input<KeyCode>>, mut report: ResMut<TruncationReport>) {
+    if keys.just_pressed(KeyCode::F6) {
+        report.requested = true;
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn report_truncation(
+    step: Res<SimStep>,
+    mut report: ResMut<TruncationReport>,
+    metric: Res<MetricScale>,
+    matrix: Res<InteractionMatrix>,
+    cutoff: Res<KernelCutoff>,
+    self_field: Res<SelfField>,
+    creature_query: Query<(Entity, &Parameters, &Children), With<Creature>>,
+    particle_query: Query<(&Transform, &Fields, Option<&WideFields>, Option<&Species>), With<Particle>>,
+) {
+    let due = report.every.is_some_and(|every| step.0.is_multiple_of(every));
+    if !due && !report.requested {
+        return;
+    }
+    report.requested = false;
+
+    let mut creatures: Vec<_> = creature_query.iter().collect();
+    creatures.sort_by_key(|(entity, ..)| *entity);
+    let particles: Vec<(&Parameters, &Children, Entity)> = creatures.iter()
+        .flat_map(|(_, parameters, children)| children.iter().map(move |child| (*parameters, *children, *child)))
+        .collect();
+    if particles.is_empty() {
+        return;
+    }
+    let stride = particles.len().div_ceil(report.sample);
+    let species = |entity: Entity| particle_query.get(entity).ok().and_then(|(.., species)| species.copied()).unwrap_or_default();
+
+    // Absolute errors, and errors relative to the full sums.
+    let (mut U_errors, mut U_grad_errors) = (Vec::new(), Vec::new());
+    for (parameters, children, child_i) in particles.into_iter().step_by(stride) {
+        let Ok((transform_i, fields, wide, _)) = particle_query.get(child_i) else {
+            continue;
+        };
+        let species_i = species(child_i);
+        let kernel = |r: f32, child_j: Entity| if matrix.is_empty() {
+            parameters.kernel_field(r)
+        } else {
+            matrix.kernel_field(parameters, species_i, species(child_j), r)
+        };
+        let (mut U_val, mut U_grad) = (if self_field.0 { kernel(0.0, child_i).0 as f64 } else { 0.0 }, DVec3::ZERO);
+        for child_j in children.iter().filter(|child_j| **child_j != child_i) {
+            let Ok((transform_j, ..)) = particle_query.get(*child_j) else {
+                continue;
+            };
+            let (r, r_grad) = metric.distance(transform_i.translation - transform_j.translation);
+            let (K, dK) = kernel(r, *child_j);
+            U_val += K as f64;
+            U_grad += (r_grad * dK).as_dvec3();
+        }
+
+        // With wide sums the fields haven't been narrowed into Fields yet.
+        let (cut_U_val, cut_U_grad) = match wide {
+            Some(wide) => (wide.U_val, wide.U_grad),
+            None => (fields.U_val as f64, fields.U_grad.as_dvec3()),
+        };
+        let (U_error, U_grad_error) = ((cut_U_val - U_val).abs(), (cut_U_grad - U_grad).length());
+        U_errors.push((U_error, U_error / U_val.abs().max(RELATIVE_FLOOR)));
+        U_grad_errors.push((U_grad_error, U_grad_error / U_grad.length().max(RELATIVE_FLOOR)));
+    }
+
+    let summary = |errors: &[(f64, f64)]| {
+        let count = errors.len().max(1) as f64;
+        let (absolute, relative): (Vec<f64>, Vec<f64>) = errors.iter().copied().unzip();
+        [absolute, relative].map(|errors| (errors.iter().sum::<f64>() / count, errors.iter().copied().fold(0.0, f64::max)))
+    };
+    for (field, errors) in [("U", &U_errors), ("U_grad", &U_grad_errors)] {
+        let [(mean, max), (mean_relative, max_relative)] = summary(errors);
+        info!(
+            step = step.0,
+            particles = errors.len(),
+            cutoff = ?cutoff.0,
+            "truncation error in {}: absolute mean {:.2e} max {:.2e}, relative mean {:.2e} max {:.2e}",
+            field, mean, max, mean_relative, max_relative,
+        );
+    }
+}
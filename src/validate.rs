@@ -0,0 +1,161 @@
+use bevy::{math::DVec3, prelude::*};
+
+use crate::{
+    calculate_fields, repulsion_field, species::{InteractionMatrix, Species}, sum_pair_fields, Creature, Fields,
+    InterCreature, KernelCutoff, MetricScale, Parameters, Particle, SelfField, SimStep, WideFields,
+};
+
+/// Recomputes every particle's pair fields on its own, summing over every
+/// other particle rather than over each pair once with equal and opposite
+/// contributions, and panics if that disagrees with `sum_pair_fields` by more
+/// than `tolerance`, relative to the size of the field where that's above
+/// one and absolute below it, so fields near zero aren't held to rounding
+/// error relative to themselves. It's a quadratically slower cross-check of
+/// the symmetric sums, for debugging.
+pub struct FieldValidationPlugin {
+    pub tolerance: f32,
+}
+
+#[derive(Resource)]
+struct ValidationTolerance(f32);
+
+impl Plugin for FieldValidationPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ValidationTolerance(self.tolerance))
+            .add_systems(Update, validate_fields.after(sum_pair_fields).before(calculate_fields));
+    }
+}
+
+// One particle's pair fields, summed in f64 so the reference isn't itself
+// off by rounding.
+#[derive(Default)]
+struct Sums {
+    R_val: f64,
+    R_grad: DVec3,
+    U_val: f64,
+    U_grad: DVec3,
+}
+
+impl Sums {
+    fn is_finite(&self) -> bool {
+        self.R_val.is_finite() && self.R_grad.is_finite() && self.U_val.is_finite() && self.U_grad.is_finite()
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn validate_fields(
+    step: Res<SimStep>,
+    tolerance: Res<ValidationTolerance>,
+    inter_creature: Res<InterCreature>,
+    metric: Res<MetricScale>,
+    matrix: Res<InteractionMatrix>,
+    cutoff: Res<KernelCutoff>,
+    self_field: Res<SelfField>,
+    creature_query: Query<(Entity, &Transform, &Parameters, &Children), With<Creature>>,
+    particle_query: Query<(&Transform, &Fields, Option<&WideFields>, Option<&Species>), With<Particle>>,
+) {
+    let species = |entity: Entity| particle_query.get(entity).ok().and_then(|(.., species)| species.copied()).unwrap_or_default();
+
+    let mut worst = (0.0, None);
+    for (creature, creature_transform, parameters, children) in creature_query.iter() {
+        for child_i in children.iter() {
+            let Ok((transform_i, fields, wide, _)) = particle_query.get(*child_i) else {
+                continue;
+            };
+            let species_i = species(*child_i);
+            let mut sums = match self_field.0 {
+                false => Sums::default(),
+                true => Sums {
+                    R_val: repulsion_field(0.0, parameters.c_rep).0 as f64,
+                    U_val: if matrix.is_empty() {
+                        parameters.kernel_field(0.0).0
+                    } else {
+                        matrix.kernel_field(parameters, species_i, species_i, 0.0).0
+                    } as f64,
+                    ..default()
+                },
+            };
+
+            for child_j in children.iter().filter(|child_j| *child_j != child_i) {
+                let Ok((transform_j, ..)) = particle_query.get(*child_j) else {
+                    continue;
+                };
+                let (r, r_grad) = metric.distance(transform_i.translation - transform_j.translation);
+                if r < 1.0 {
+                    let (R, dR) = repulsion_field(r, parameters.c_rep);
+                    sums.R_val += R as f64;
+                    sums.R_grad += (r_grad * dR).as_dvec3();
+                }
+                if !cutoff.reaches(r) {
+                    continue;
+                }
+                let (K, dK) = if matrix.is_empty() {
+                    cutoff.apply(r, parameters.kernel_field(r))
+                } else {
+                    cutoff.apply(r, matrix.kernel_field(parameters, species_i, species(*child_j), r))
+                };
+                sums.U_val += K as f64;
+                sums.U_grad += (r_grad * dK).as_dvec3();
+            }
+
+            if inter_creature.enabled {
+                let x_i = creature_transform.translation + transform_i.translation;
+                for (other, other_transform, other_parameters, other_children) in creature_query.iter() {
+                    if other == creature {
+                        continue;
+                    }
+                    let c_rep = 0.5 * (parameters.c_rep + other_parameters.c_rep);
+                    for (transform_j, ..) in particle_query.iter_many(other_children) {
+                        let (r, r_grad) = metric.distance(x_i - (other_transform.translation + transform_j.translation));
+                        if r < 1.0 {
+                            let (R, dR) = repulsion_field(r, c_rep);
+                            sums.R_val += R as f64;
+                            sums.R_grad += (r_grad * dR).as_dvec3();
+                        }
+                    }
+                }
+            }
+
+            // With wide sums the fields haven't been narrowed into Fields yet.
+            let symmetric = match wide {
+                Some(wide) => Sums { R_val: wide.R_val, R_grad: wide.R_grad, U_val: wide.U_val, U_grad: wide.U_grad },
+                None => Sums {
+                    R_val: fields.R_val as f64,
+                    R_grad: fields.R_grad.as_dvec3(),
+                    U_val: fields.U_val as f64,
+                    U_grad: fields.U_grad.as_dvec3(),
+                },
+            };
+            for (name, difference) in differences(&symmetric, &sums) {
+                // A particle that's already blown up is left to the explosion
+                // checks.
+                let difference = if difference.is_nan() { f64::INFINITY } else { difference };
+                if difference > worst.0 && sums.is_finite() {
+                    worst = (difference, Some((*child_i, name)));
+                }
+            }
+        }
+    }
+
+    let (difference, at) = worst;
+    if let Some((particle, field)) = at.filter(|_| difference > tolerance.0 as f64) {
+        panic!(
+            "step {}: the symmetric and single-sided pair sums disagree on {} of {:?} by {:e}, above the tolerance of {:e}",
+            step.0, field, particle, difference, tolerance.0,
+        );
+    }
+    trace!(step = step.0, max_difference = difference, "pair fields validated");
+}
+
+// Each field's difference relative to its single-sided size, or absolute
+// where that's under one.
+fn differences(symmetric: &Sums, single_sided: &Sums) -> [(&'static str, f64); 4] {
+    let relative = |a: f64, b: f64| (a - b).abs() / b.abs().max(1.0);
+    let relative_vec = |a: DVec3, b: DVec3| (a - b).length() / b.length().max(1.0);
+    [
+        ("R_val", relative(symmetric.R_val, single_sided.R_val)),
+        ("R_grad", relative_vec(symmetric.R_grad, single_sided.R_grad)),
+        ("U_val", relative(symmetric.U_val, single_sided.U_val)),
+        ("U_grad", relative_vec(symmetric.U_grad, single_sided.U_grad)),
+    ]
+}
@@ -0,0 +1,63 @@
+use particle_lenia::Density;
+use rand::{rngs::SmallRng, SeedableRng};
+
+const EPSILON: f32 = 1e-5;
+
+fn eval(source: &str, x: f32, y: f32) -> f32 {
+    Density::parse(source).unwrap_or_else(|err| panic!("{}: {}", source, err)).eval(x, y)
+}
+
+#[test]
+fn follows_operator_precedence() {
+    assert!((eval("1 + 2 * 3 ^ 2", 0.0, 0.0) - 19.0).abs() < EPSILON);
+    assert!((eval("2 ^ 3 ^ 2", 0.0, 0.0) - 512.0).abs() < EPSILON);
+    assert!((eval("(1 + 2) * 3 - 8 / 4", 0.0, 0.0) - 7.0).abs() < EPSILON);
+    // A leading minus applies after the power, so this is 1 - 4 and clamps.
+    assert_eq!(eval("1 + -x^2", 2.0, 0.0), 0.0);
+    assert!((eval("2.5e-1 * 4", 0.0, 0.0) - 1.0).abs() < EPSILON);
+}
+
+#[test]
+fn reads_variables_and_functions() {
+    assert!((eval("r", 3.0, 4.0) - 5.0).abs() < EPSILON);
+    assert!((eval("1 + cos(theta)", 0.0, 1.0) - 1.0).abs() < EPSILON);
+    assert!((eval("max(x, y) + min(x, y)", 2.0, 5.0) - 7.0).abs() < EPSILON);
+    assert!((eval("exp(-(r - 6)^2)", 6.0, 0.0) - 1.0).abs() < EPSILON);
+    assert_eq!(eval("step(4 - r)", 3.0, 0.0), 1.0);
+    assert_eq!(eval("step(4 - r)", 5.0, 0.0), 0.0);
+}
+
+#[test]
+fn rejects_malformed_expressions() {
+    for source in ["", "sin(", "foo(x)", "z + 1", "max(x)", "2 + * 3", "(x", "x)", "x # y"] {
+        assert!(Density::parse(source).is_err(), "{:?} parsed", source);
+    }
+}
+
+#[test]
+fn peak_is_zero_where_the_density_is_nowhere_positive() {
+    assert_eq!(Density::parse("-1 - r").unwrap().peak(10.0), 0.0);
+    assert_eq!(Density::parse("step(r - 20)").unwrap().peak(10.0), 0.0);
+    assert!((Density::parse("10 - r").unwrap().peak(10.0) - 10.0).abs() < 0.2);
+}
+
+// The same seed draws the same points, all inside the disk and where the
+// density is positive; a ring's hole stays empty.
+#[test]
+fn sampling_is_seeded_and_follows_the_density() {
+    let (count, radius) = (500, 10.0);
+    for source in ["step(r - 5)", "exp(-(r - 6)^2) * (1 + cos(theta))"] {
+        let density = Density::parse(source).unwrap();
+        let first = density.sample(&mut SmallRng::seed_from_u64(3), count, radius);
+        let second = density.sample(&mut SmallRng::seed_from_u64(3), count, radius);
+        assert_eq!(first, second, "{}", source);
+        assert_eq!(first.len(), count);
+        for point in first.iter() {
+            assert!(point.truncate().length() <= radius, "{} at {}", source, point);
+            assert!(density.eval(point.x, point.y) > 0.0, "{} at {}", source, point);
+        }
+    }
+
+    let ring = Density::parse("step(r - 5)").unwrap().sample(&mut SmallRng::seed_from_u64(3), count, radius);
+    assert!(ring.iter().all(|point| point.truncate().length() >= 5.0));
+}
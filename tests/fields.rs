@@ -0,0 +1,59 @@
+use bevy::math::Vec3;
+use particle_lenia::{compute_fields, Parameters};
+
+const EPSILON: f32 = 1e-6;
+
+// Within the repulsion range R(r) = c_rep / 2 * (1 - r)^2, and each particle
+// also counts itself at r = 0.
+#[test]
+fn two_particles_at_half_distance_repel() {
+    let parameters = Parameters { c_rep: 1.0, ..Parameters::default() };
+    let fields = compute_fields(&parameters, &[Vec3::ZERO, Vec3::new(0.5, 0.0, 0.0)]);
+
+    for particle in fields.iter() {
+        assert!((particle.R_val - (0.5 + 0.125)).abs() < EPSILON, "R_val {}", particle.R_val);
+    }
+    // dR/dr = -c_rep * (1 - r), pointing each particle's gradient toward the other.
+    assert!(fields[0].R_grad.distance(Vec3::new(0.5, 0.0, 0.0)) < EPSILON, "R_grad {}", fields[0].R_grad);
+    assert!(fields[1].R_grad.distance(Vec3::new(-0.5, 0.0, 0.0)) < EPSILON, "R_grad {}", fields[1].R_grad);
+}
+
+// Beyond the repulsion range only the kernel acts, K(r) = w_k exp(-((r - mu_k) / sigma_k)^2).
+#[test]
+fn particles_at_kernel_peak_feel_full_weight() {
+    let parameters = Parameters::default();
+    let fields = compute_fields(&parameters, &[Vec3::ZERO, Vec3::new(0.0, parameters.mu_k, 0.0)]);
+
+    let self_term = parameters.w_k * (-(parameters.mu_k / parameters.sigma_k).powi(2)).exp();
+    for particle in fields.iter() {
+        assert!((particle.R_val - 0.5 * parameters.c_rep).abs() < EPSILON);
+        assert!((particle.U_val - (parameters.w_k + self_term)).abs() < EPSILON, "U_val {}", particle.U_val);
+        assert!(particle.U_grad.length() < EPSILON, "U_grad {}", particle.U_grad);
+    }
+}
+
+// With a kernel as narrow as the smallest positive f32, (r - mu_k) / sigma_k
+// overflows to infinity at every distance here, and the kernel and its slope
+// must come out as 0 rather than NaN.
+#[test]
+fn tiny_kernel_width_gives_finite_fields() {
+    let parameters = Parameters { sigma_k: f32::from_bits(1), ..Parameters::default() };
+    let fields = compute_fields(&parameters, &[Vec3::ZERO, Vec3::new(10.0, 0.0, 0.0), Vec3::new(0.0, 30.0, 0.0)]);
+
+    for particle in fields.iter() {
+        assert_eq!(particle.U_val, 0.0, "U_val {}", particle.U_val);
+        assert_eq!(particle.U_grad, Vec3::ZERO, "U_grad {}", particle.U_grad);
+        assert!(particle.E_grad.is_finite(), "E_grad {}", particle.E_grad);
+    }
+}
+
+// Likewise for the growth mapping, at densities far from mu_g.
+#[test]
+fn tiny_growth_width_gives_finite_fields() {
+    let parameters = Parameters { sigma_g: f32::from_bits(1), ..Parameters::default() };
+    let fields = compute_fields(&parameters, &[Vec3::ZERO, Vec3::new(parameters.mu_k, 0.0, 0.0), Vec3::new(50.0, 0.0, 0.0)]);
+
+    for particle in fields.iter() {
+        assert!(particle.E_grad.is_finite(), "E_grad {}", particle.E_grad);
+    }
+}
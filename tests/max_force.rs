@@ -0,0 +1,38 @@
+use std::{env, fs};
+
+use bevy::math::Vec3;
+use particle_lenia::{compute_fields, Parameters, Simulation};
+
+const EPSILON: f32 = 1e-4;
+
+// Two particles a thousandth apart under a huge c_rep push on each other with
+// |E_grad| of about c_rep. With --max-force the step that moves them sees it
+// cut down to exactly the limit, pointing the same way.
+#[test]
+fn max_force_clamps_huge_gradient_keeping_direction() {
+    let (c_rep, max_force) = (1e6, 5.0);
+    let parameters = Parameters { c_rep, ..Parameters::default() };
+    let positions = [Vec3::new(-0.0005, 0.0, 0.0), Vec3::new(0.0005, 0.0, 0.0)];
+    let unclamped = compute_fields(&parameters, &positions);
+    for fields in unclamped.iter() {
+        assert!(fields.E_grad.length() > 1e5, "E_grad {}", fields.E_grad);
+    }
+
+    let path = env::temp_dir().join(format!("particle-lenia-max-force-{}.ron", std::process::id()));
+    fs::write(&path, format!(
+        "(parameters: (mu_k: 4.0, sigma_k: 1.0, w_k: 0.022, kernel: Gaussian, mu_g: 0.6, sigma_g: 0.15, growth: SingleGaussian, growth_kernel: Gaussian, c_rep: {:?}), \
+         offsets: [({:?}, 0.0, 0.0), ({:?}, 0.0, 0.0)])",
+        c_rep, positions[0].x, positions[1].x,
+    )).unwrap();
+    let mut simulation = Simulation::from_args(["--template".into(), path.clone().into_os_string(), "--max-force".into(), max_force.to_string().into()]).unwrap();
+    simulation.step();
+    let state = simulation.state();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(state.particles.len(), 2);
+    for (particle, unclamped) in state.particles.iter().zip(unclamped.iter()) {
+        let clamped = particle.fields.E_grad;
+        assert!((clamped.length() - max_force).abs() < EPSILON, "|E_grad| {}", clamped.length());
+        assert!(clamped.normalize().distance(unclamped.E_grad.normalize()) < EPSILON, "E_grad {} was {}", clamped, unclamped.E_grad);
+    }
+}
@@ -0,0 +1,35 @@
+use particle_lenia::{effective_radius, KernelKind, Parameters};
+
+const EPSILON: f32 = 1e-4;
+
+// Past the repulsion range the Gaussian kernel has fallen to exactly `tol` of
+// its peak w_k at mu_k + sigma_k sqrt(ln(1 / tol)).
+#[test]
+fn gaussian_radius_is_where_kernel_falls_to_tolerance() {
+    for (mu_k, sigma_k, tol) in [(4.0, 1.0, (-4.0f32).exp()), (4.0, 1.0, 1e-3), (6.0, 0.5, 0.1), (2.5, 2.0, 1e-6)] {
+        let parameters = Parameters { mu_k, sigma_k, ..Parameters::default() };
+        let radius = effective_radius(&parameters, tol);
+        assert!((radius - (mu_k + sigma_k * (1.0 / tol).ln().sqrt())).abs() < EPSILON, "radius {} for {:?}", radius, (mu_k, sigma_k, tol));
+
+        let (k, _) = parameters.kernel_field(radius);
+        assert!((k / parameters.w_k - tol).abs() < tol * 1e-3, "K / w_k {} at {} for {:?}", k / parameters.w_k, radius, (mu_k, sigma_k, tol));
+        // And it only falls further beyond.
+        assert!(parameters.kernel_field(radius + 0.1).0 < k);
+    }
+}
+
+#[test]
+fn quartic_radius_is_where_kernel_falls_to_tolerance() {
+    for tol in [(-4.0f32).exp(), 0.1, 1e-3] {
+        let parameters = Parameters { kernel: KernelKind::Quartic, ..Parameters::default() };
+        let radius = effective_radius(&parameters, tol);
+        let (k, _) = parameters.kernel_field(radius);
+        assert!((k / parameters.w_k - tol).abs() < tol * 1e-2, "K / w_k {} at {} for tol {}", k / parameters.w_k, radius, tol);
+    }
+}
+
+#[test]
+fn spline_radius_is_its_last_control_point() {
+    let parameters = Parameters { kernel: KernelKind::Spline(vec![(0.0, 0.0), (3.0, 1.0), (7.5, 0.0)]), ..Parameters::default() };
+    assert_eq!(effective_radius(&parameters, 1e-3), 7.5);
+}
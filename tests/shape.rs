@@ -0,0 +1,39 @@
+use std::f32::consts::TAU;
+
+use bevy::math::{Quat, Vec3};
+use particle_lenia::shape_descriptors;
+
+const EPSILON: f32 = 1e-4;
+
+// Points evenly spaced around an ellipse with semi-axes a and b, centered on
+// `center` and turned by `angle`.
+fn ellipse(a: f32, b: f32, center: Vec3, angle: f32) -> Vec<Vec3> {
+    let rotation = Quat::from_rotation_z(angle);
+    (0..360)
+        .map(|i| i as f32 / 360.0 * TAU)
+        .map(|theta| center + rotation * Vec3::new(a * theta.cos(), b * theta.sin(), 0.0))
+        .collect()
+}
+
+#[test]
+fn circle_is_round() {
+    let shape = shape_descriptors(&ellipse(3.0, 3.0, Vec3::new(5.0, -2.0, 0.0), 0.0));
+    assert!((shape.radius_of_gyration - 3.0).abs() < EPSILON, "{:?}", shape);
+    assert!((shape.aspect_ratio - 1.0).abs() < EPSILON, "{:?}", shape);
+    assert!(shape.asphericity.abs() < EPSILON, "{:?}", shape);
+}
+
+// Around an ellipse the variance along each axis is half its semi-axis
+// squared, whichever way the ellipse is turned.
+#[test]
+fn ellipse_is_elongated_along_its_major_axis() {
+    let (a, b) = (4.0_f32, 2.0_f32);
+    for angle in [0.0, 0.4, TAU / 4.0] {
+        let shape = shape_descriptors(&ellipse(a, b, Vec3::ZERO, angle));
+        let (major, minor) = (a * a / 2.0, b * b / 2.0);
+        assert!((shape.radius_of_gyration - (major + minor).sqrt()).abs() < EPSILON, "{:?}", shape);
+        assert!((shape.aspect_ratio - a / b).abs() < EPSILON, "{:?}", shape);
+        let asphericity = ((major - minor) / (major + minor)).powi(2);
+        assert!((shape.asphericity - asphericity).abs() < EPSILON, "{:?}", shape);
+    }
+}
@@ -0,0 +1,16 @@
+use std::process::Command;
+
+// Runs a fixed headless scene and checks the final particle positions against
+// the committed snapshot. After an intended change to the dynamics, regenerate
+// it with the same arguments and --write-snapshot in place of --check-snapshot.
+#[test]
+fn single_creature_matches_snapshot() {
+    let output = Command::new(env!("CARGO_BIN_EXE_particle-lenia"))
+        .args(["--headless", "--steps", "200", "--seed", "7"])
+        .arg("--check-snapshot")
+        .arg(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/snapshots/single_seed7.json"))
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+}
@@ -0,0 +1,92 @@
+use std::{env, ffi::OsString, fs, path::Path};
+
+use bevy::math::Vec3;
+use particle_lenia::{compute_fields, Parameters, Simulation};
+
+const EPSILON: f32 = 1e-5;
+
+// Runs five steps, checkpointing the last, loads the checkpoint with
+// --recompute-fields-on-load and checks the fields it starts with against a
+// fresh computation from the loaded positions. Without the option they start
+// at zero.
+#[test]
+fn loaded_fields_match_fresh_computation() {
+    let dir = env::temp_dir().join(format!("particle-lenia-state-{}", std::process::id()));
+    let args: Vec<OsString> = vec!["--seed".into(), "3".into(), "--checkpoint-interval".into(), "5".into(), "--checkpoint-dir".into(), dir.clone().into()];
+    let mut running = Simulation::from_args(args).unwrap();
+    for _ in 0..5 {
+        running.step();
+    }
+    let checkpoint = dir.join("checkpoint-000005.ron");
+
+    let mut loaded = load(&checkpoint, true);
+    let state = loaded.state();
+    assert_eq!(state.step, 5);
+    let positions: Vec<Vec3> = state.particles.iter().map(|particle| particle.position).collect();
+    assert!(!positions.is_empty());
+    let fresh = compute_fields(&Parameters::default(), &positions);
+    for (particle, fresh) in state.particles.iter().zip(fresh.iter()) {
+        let fields = particle.fields;
+        assert!((fields.R_val - fresh.R_val).abs() < EPSILON, "R_val {} != {}", fields.R_val, fresh.R_val);
+        assert!((fields.U_val - fresh.U_val).abs() < EPSILON, "U_val {} != {}", fields.U_val, fresh.U_val);
+        assert!(fields.R_grad.distance(fresh.R_grad) < EPSILON, "R_grad {} != {}", fields.R_grad, fresh.R_grad);
+        assert!(fields.U_grad.distance(fresh.U_grad) < EPSILON, "U_grad {} != {}", fields.U_grad, fresh.U_grad);
+        assert!(fields.E_grad.distance(fresh.E_grad) < EPSILON, "E_grad {} != {}", fields.E_grad, fresh.E_grad);
+    }
+
+    let mut stale = load(&checkpoint, false);
+    assert!(stale.state().particles.iter().all(|particle| particle.fields.R_val == 0.0));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+fn load(path: &Path, recompute: bool) -> Simulation {
+    let mut args: Vec<OsString> = vec!["--load-state".into(), path.into()];
+    if recompute {
+        args.push("--recompute-fields-on-load".into());
+    }
+    let mut simulation = Simulation::from_args(args).unwrap();
+    simulation.start();
+    simulation
+}
+
+// Ages, and where the emitter is in its stream, are saved with the rest, so a
+// run resumed from a checkpoint carries on exactly as the original did. The
+// budget caps replacements, which otherwise count from the first step run.
+#[test]
+fn resumed_lifespans_and_emitters_match_the_original_run() {
+    assert_resumes_exactly("lifespan", &["--max-lifespan", "4", "--emitter", "30", "0", "--particle-budget", "400"]);
+}
+
+// As are how long particles have been starving.
+#[test]
+fn resumed_lifecycle_matches_the_original_run() {
+    assert_resumes_exactly("lifecycle", &["--lifecycle", "10", "0.5", "--starve-steps", "4", "--particle-budget", "400"]);
+}
+
+// Runs twelve steps with `options`, checkpointing the fifth, and checks that
+// seven more from the checkpoint end up where the twelve did.
+fn assert_resumes_exactly(name: &str, options: &[&str]) {
+    let dir = env::temp_dir().join(format!("particle-lenia-state-{}-{}", name, std::process::id()));
+    let mut args: Vec<OsString> = vec!["--seed".into(), "3".into(), "--checkpoint-interval".into(), "5".into(), "--checkpoint-dir".into(), dir.clone().into()];
+    args.extend(options.iter().map(OsString::from));
+    let mut running = Simulation::from_args(args.clone()).unwrap();
+    for _ in 0..12 {
+        running.step();
+    }
+    let original = running.state();
+
+    args.extend(["--load-state".into(), dir.join("checkpoint-000005.ron").into_os_string()]);
+    let mut resumed = Simulation::from_args(args).unwrap();
+    for _ in 0..7 {
+        resumed.step();
+    }
+    let resumed = resumed.state();
+    let _ = fs::remove_dir_all(&dir);
+
+    assert_eq!(resumed.step, original.step);
+    assert_eq!(resumed.particles.len(), original.particles.len());
+    for (resumed, original) in resumed.particles.iter().zip(original.particles.iter()) {
+        assert!(resumed.position.distance(original.position) < EPSILON, "{} != {}", resumed.position, original.position);
+    }
+}
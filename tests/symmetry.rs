@@ -0,0 +1,26 @@
+use std::f32::consts::TAU;
+
+use bevy::math::{Quat, Vec3};
+use particle_lenia::Simulation;
+
+const EPSILON: f32 = 1e-4;
+
+// Spawns a creature with --symmetry 5 and checks that turning it a fifth of
+// the way about its centroid lands every particle on one of the others.
+#[test]
+fn symmetric_spawn_maps_onto_itself() {
+    let fold = 5;
+    let mut simulation = Simulation::from_args(["--seed", "3", "--symmetry", &fold.to_string()]).unwrap();
+    simulation.start();
+    let positions: Vec<Vec3> = simulation.state().particles.iter().map(|particle| particle.position).collect();
+    assert!(!positions.is_empty());
+    assert_eq!(positions.len() % fold, 0, "{} particles", positions.len());
+
+    let centroid = positions.iter().sum::<Vec3>() / positions.len() as f32;
+    let rotation = Quat::from_rotation_z(TAU / fold as f32);
+    for position in positions.iter() {
+        let turned = centroid + rotation * (*position - centroid);
+        let nearest = positions.iter().map(|other| other.distance(turned)).fold(f32::INFINITY, f32::min);
+        assert!(nearest < EPSILON, "{} turned to {} is {} from the nearest particle", position, turned, nearest);
+    }
+}
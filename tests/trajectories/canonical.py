@@ -0,0 +1,71 @@
+"""Writes canonical.json: the canonical Particle Lenia update, written out
+independently of the crate in double precision, from 24 particles scattered
+over a disk of radius 4 with the default parameters.
+
+    python3 tests/trajectories/canonical.py > tests/trajectories/canonical.json
+"""
+
+import json
+import math
+import random
+
+MU_K, SIGMA_K, W_K = 4.0, 1.0, 0.022
+MU_G, SIGMA_G = 0.6, 0.15
+C_REP = 1.0
+DT = 0.1
+STEPS = 100
+
+
+def bump(x, mu, sigma, w):
+    """w exp(-((x - mu) / sigma)^2) and its derivative in x."""
+    t = (x - mu) / sigma
+    y = w * math.exp(-t * t)
+    return y, -2.0 * t / sigma * y
+
+
+def step(points):
+    moved = []
+    for i, (xi, yi) in enumerate(points):
+        # Each particle's own r = 0 term only adds to the values.
+        u = bump(0.0, MU_K, SIGMA_K, W_K)[0]
+        u_grad = [0.0, 0.0]
+        r_grad = [0.0, 0.0]
+        for j, (xj, yj) in enumerate(points):
+            if i == j:
+                continue
+            dx, dy = xi - xj, yi - yj
+            r = math.hypot(dx, dy)
+            if r == 0.0:
+                continue
+            k, dk = bump(r, MU_K, SIGMA_K, W_K)
+            u += k
+            u_grad[0] += dk * dx / r
+            u_grad[1] += dk * dy / r
+            if r < 1.0:
+                dr = -C_REP * (1.0 - r)
+                r_grad[0] += dr * dx / r
+                r_grad[1] += dr * dy / r
+        dg = bump(u, MU_G, SIGMA_G, 1.0)[1]
+        e_grad = [r_grad[k] - dg * u_grad[k] for k in range(2)]
+        moved.append((xi - DT * e_grad[0], yi - DT * e_grad[1]))
+    return moved
+
+
+def main():
+    rng = random.Random(7)
+    points = []
+    for _ in range(24):
+        r = 4.0 * math.sqrt(rng.random())
+        theta = rng.random() * math.tau
+        # Rounded to what an f32 holds to start both from the same place.
+        points.append((round(r * math.cos(theta), 6), round(r * math.sin(theta), 6)))
+
+    steps = [points]
+    for _ in range(STEPS):
+        steps.append(step(steps[-1]))
+    rounded = [[[round(x, 7), round(y, 7)] for x, y in points] for points in steps]
+    print(json.dumps({"dt": DT, "steps": rounded}, separators=(",", ":")))
+
+
+if __name__ == "__main__":
+    main()
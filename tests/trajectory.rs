@@ -0,0 +1,16 @@
+use std::process::Command;
+
+// Runs the particles of tests/trajectories/canonical.json, written by the
+// canonical.py beside it, and checks every step against the positions that
+// independent double-precision implementation of the model took.
+#[test]
+fn default_parameters_track_canonical_model() {
+    let output = Command::new(env!("CARGO_BIN_EXE_particle-lenia"))
+        .args(["--headless", "--deterministic", "--trajectory-tolerance", "1e-4"])
+        .arg("--check-trajectory")
+        .arg(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/trajectories/canonical.json"))
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+}
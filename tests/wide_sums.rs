@@ -0,0 +1,66 @@
+use bevy::math::DVec3;
+use particle_lenia::{Fields, Parameters, Simulation};
+
+const PARTICLES: usize = 800;
+
+// One particle's U field summed in f64 over every other particle,
+// with its own r = 0 term, from the same f32 positions.
+fn reference(parameters: &Parameters, positions: &[DVec3], i: usize) -> (f64, DVec3) {
+    let (mu_k, sigma_k, w_k) = (parameters.mu_k as f64, parameters.sigma_k as f64, parameters.w_k as f64);
+    let kernel = |r: f64| {
+        let t = (r - mu_k) / sigma_k;
+        let k = w_k * (-t * t).exp();
+        (k, -2.0 * t / sigma_k * k)
+    };
+    let (mut u_val, mut u_grad) = (kernel(0.0).0, DVec3::ZERO);
+    for (_, x_j) in positions.iter().enumerate().filter(|(j, _)| *j != i) {
+        let d = positions[i] - *x_j;
+        let r = d.length();
+        let (k, dk) = kernel(r);
+        u_val += k;
+        u_grad += d / r * dk;
+    }
+    (u_val, u_grad)
+}
+
+// Fields summed at the spawn positions, which the first step sums from, and
+// those positions.
+fn first_fields(wide: bool) -> (Vec<DVec3>, Vec<Fields>) {
+    let mut args = vec!["--stress".to_string(), "1".to_string(), PARTICLES.to_string(), "--seed".to_string(), "1".to_string()];
+    if wide {
+        args.push("--wide-sums".to_string());
+    }
+    let mut simulation = Simulation::from_args(args).unwrap();
+    simulation.start();
+    let positions = simulation.state().particles.iter().map(|particle| particle.position.as_dvec3()).collect();
+    simulation.step();
+    (positions, simulation.state().particles.into_iter().map(|particle| particle.fields).collect())
+}
+
+// Largest error in U_val and in U_grad against the f64 reference.
+fn errors(parameters: &Parameters, positions: &[DVec3], fields: &[Fields]) -> (f64, f64) {
+    fields.iter().enumerate().fold((0.0, 0.0), |(val, grad), (i, fields)| {
+        let (u_val, u_grad) = reference(parameters, positions, i);
+        (val.max((fields.U_val as f64 - u_val).abs()), grad.max((fields.U_grad.as_dvec3() - u_grad).length()))
+    })
+}
+
+// The first step's U fields of a dense creature with and without --wide-sums,
+// both measured against a sum done entirely in f64. Run with --nocapture to
+// see the errors.
+#[test]
+fn wide_sums_are_closer_to_f64_than_f32_sums() {
+    let parameters = Parameters::default();
+    let (positions, narrow) = first_fields(false);
+    let (wide_positions, wide) = first_fields(true);
+    assert_eq!(positions, wide_positions);
+    assert_eq!(narrow.len(), PARTICLES);
+
+    let (narrow_val, narrow_grad) = errors(&parameters, &positions, &narrow);
+    let (wide_val, wide_grad) = errors(&parameters, &positions, &wide);
+    println!("f32 sums:  U_val error {:e}, U_grad error {:e}", narrow_val, narrow_grad);
+    println!("wide sums: U_val error {:e}, U_grad error {:e}", wide_val, wide_grad);
+    // What's left of the wide error is mostly the final rounding to f32.
+    assert!(wide_val * 4.0 < narrow_val, "U_val error {:e} with wide sums, {:e} without", wide_val, narrow_val);
+    assert!(wide_grad < narrow_grad, "U_grad error {:e} with wide sums, {:e} without", wide_grad, narrow_grad);
+}